@@ -0,0 +1,40 @@
+-- Allow the finer grained staff roles
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_level_check;
+ALTER TABLE users ADD CONSTRAINT users_level_check
+    CHECK (level IN ('SuperAdmin', 'Admin', 'Editor', 'Agent', 'User', 'Viewer'));
+
+-- The seeded admin owns the installation
+UPDATE users SET level = 'SuperAdmin' WHERE id = '00000000-0000-0000-0000-000000000001';
+
+-- Create role permissions table (SuperAdmin implicitly holds every permission)
+CREATE TABLE IF NOT EXISTS role_permissions (
+    level TEXT NOT NULL CHECK (level IN ('Admin', 'Editor', 'Agent', 'User', 'Viewer')),
+    permission TEXT NOT NULL,
+    PRIMARY KEY (level, permission)
+);
+
+-- Insert default permission matrix
+INSERT INTO role_permissions (level, permission) VALUES
+    ('Admin', 'estates.view'),
+    ('Admin', 'estates.create'),
+    ('Admin', 'estates.edit'),
+    ('Admin', 'estates.delete'),
+    ('Admin', 'estates.publish'),
+    ('Admin', 'users.view'),
+    ('Admin', 'users.manage'),
+    ('Admin', 'leads.view'),
+    ('Admin', 'roles.manage'),
+    ('Editor', 'estates.view'),
+    ('Editor', 'estates.create'),
+    ('Editor', 'estates.edit'),
+    ('Editor', 'estates.publish'),
+    ('Editor', 'users.view'),
+    ('Editor', 'leads.view'),
+    ('Agent', 'estates.view'),
+    ('Agent', 'estates.create'),
+    ('Agent', 'estates.edit'),
+    ('Agent', 'leads.view'),
+    ('User', 'estates.view'),
+    ('User', 'users.view'),
+    ('Viewer', 'estates.view')
+ON CONFLICT (level, permission) DO NOTHING;
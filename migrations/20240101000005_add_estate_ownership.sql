@@ -0,0 +1,20 @@
+-- Track who created a listing and which agent is responsible for it
+ALTER TABLE estates
+    ADD COLUMN IF NOT EXISTS created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS assigned_agent UUID REFERENCES users(id) ON DELETE SET NULL;
+
+-- Create indexes for "my listings" lookups
+CREATE INDEX IF NOT EXISTS idx_estates_created_by ON estates(created_by);
+CREATE INDEX IF NOT EXISTS idx_estates_assigned_agent ON estates(assigned_agent);
+
+-- Existing listings belong to the seeded admin
+UPDATE estates
+SET created_by = '00000000-0000-0000-0000-000000000001',
+    assigned_agent = '00000000-0000-0000-0000-000000000001'
+WHERE created_by IS NULL
+  AND EXISTS (SELECT 1 FROM users WHERE id = '00000000-0000-0000-0000-000000000001');
+
+-- Editing or reassigning listings owned by others
+INSERT INTO role_permissions (level, permission) VALUES
+    ('Admin', 'estates.manage_all')
+ON CONFLICT (level, permission) DO NOTHING;
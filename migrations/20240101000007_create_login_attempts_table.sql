@@ -0,0 +1,12 @@
+-- Create failed login tracking table, keyed by username or client IP
+CREATE TABLE IF NOT EXISTS login_attempts (
+    kind TEXT NOT NULL CHECK (kind IN ('username', 'ip')),
+    key TEXT NOT NULL,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ,
+    PRIMARY KEY (kind, key)
+);
+
+-- Create index on last_failed_at for cleanup of stale rows
+CREATE INDEX IF NOT EXISTS idx_login_attempts_last_failed ON login_attempts(last_failed_at);
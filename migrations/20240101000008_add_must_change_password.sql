@@ -0,0 +1,8 @@
+-- Add flag forcing a password change on next login
+ALTER TABLE users ADD COLUMN IF NOT EXISTS must_change_password BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Force the seeded admin to replace the well-known default password
+UPDATE users
+SET must_change_password = TRUE
+WHERE id = '00000000-0000-0000-0000-000000000001'
+  AND password = '$argon2id$v=19$m=19456,t=2,p=1$d2xhxafgf42yOaQfX3hwQQ$UxypuGP0fwpcl0KqBhpl79aeDZ/n0MUYHRIor0kb/IU';
@@ -0,0 +1,22 @@
+-- Fold Arabic spelling variants (hamza forms of alef, waw and yaa, alef maqsura,
+-- taa marbuta) and strip tashkeel and tatweel, so a word matches however it was typed
+CREATE OR REPLACE FUNCTION normalize_arabic(input TEXT) RETURNS TEXT
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT translate(
+        regexp_replace(input, '[\u064B-\u065F\u0670\u0640]', '', 'g'),
+        'أإآٱؤئىة',
+        'ااااوييه'
+    )
+$$;
+
+-- Create the weighted search document: name, then address, then description
+ALTER TABLE estates ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('arabic', normalize_arabic(name)), 'A') ||
+        setweight(to_tsvector('arabic', normalize_arabic(address)), 'B') ||
+        setweight(to_tsvector('arabic', normalize_arabic(description)), 'C')
+    ) STORED;
+
+-- Create index for full-text search
+CREATE INDEX IF NOT EXISTS idx_estates_search ON estates USING GIN (search_vector);
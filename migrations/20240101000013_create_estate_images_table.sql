@@ -0,0 +1,25 @@
+-- Create estate images table holding each listing's photo gallery
+CREATE TABLE IF NOT EXISTS estate_images (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    estate_id UUID NOT NULL REFERENCES estates(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    is_cover BOOLEAN NOT NULL DEFAULT FALSE,
+    caption TEXT NOT NULL DEFAULT '',
+    alt_text TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for loading a gallery in order
+CREATE INDEX IF NOT EXISTS idx_estate_images_estate ON estate_images(estate_id, position);
+
+-- At most one cover image per estate
+CREATE UNIQUE INDEX IF NOT EXISTS idx_estate_images_cover ON estate_images(estate_id) WHERE is_cover;
+
+-- Existing single images become the cover of their gallery. estates.image_url
+-- stays as a copy of the cover's url for listing cards.
+INSERT INTO estate_images (estate_id, url, position, is_cover, alt_text)
+SELECT id, image_url, 0, TRUE, name
+FROM estates
+WHERE image_url <> ''
+  AND NOT EXISTS (SELECT 1 FROM estate_images i WHERE i.estate_id = estates.id);
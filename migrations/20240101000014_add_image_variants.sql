@@ -0,0 +1,8 @@
+-- Add resized WebP variants to gallery images. They stay NULL for images
+-- uploaded before processing existed, which fall back to the original.
+ALTER TABLE estate_images ADD COLUMN IF NOT EXISTS thumbnail_url TEXT;
+ALTER TABLE estate_images ADD COLUMN IF NOT EXISTS medium_url TEXT;
+ALTER TABLE estate_images ADD COLUMN IF NOT EXISTS large_url TEXT;
+
+-- Add the cover's thumbnail next to estates.image_url for listing cards
+ALTER TABLE estates ADD COLUMN IF NOT EXISTS thumbnail_url TEXT;
@@ -0,0 +1,31 @@
+-- Store object keys instead of absolute urls; urls are built from the
+-- configured public base when rows are read
+ALTER TABLE estates RENAME COLUMN image_url TO image_key;
+ALTER TABLE estates RENAME COLUMN thumbnail_url TO thumbnail_key;
+ALTER TABLE estate_images RENAME COLUMN url TO image_key;
+ALTER TABLE estate_images RENAME COLUMN thumbnail_url TO thumbnail_key;
+ALTER TABLE estate_images RENAME COLUMN medium_url TO medium_key;
+ALTER TABLE estate_images RENAME COLUMN large_url TO large_key;
+
+-- Uploaded objects are named after a UUID, so an url whose last segment starts
+-- with one is cut down to that segment. Other urls point at images hosted
+-- elsewhere and are kept as they are.
+CREATE FUNCTION pg_temp.object_key(url TEXT) RETURNS TEXT
+LANGUAGE SQL IMMUTABLE
+AS $$
+    SELECT CASE
+        WHEN name ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}' THEN name
+        ELSE url
+    END
+    FROM (SELECT regexp_replace(split_part(url, '?', 1), '^.*/', '') AS name) AS x
+$$;
+
+UPDATE estates
+SET image_key = pg_temp.object_key(image_key),
+    thumbnail_key = pg_temp.object_key(thumbnail_key);
+
+UPDATE estate_images
+SET image_key = pg_temp.object_key(image_key),
+    thumbnail_key = pg_temp.object_key(thumbnail_key),
+    medium_key = pg_temp.object_key(medium_key),
+    large_key = pg_temp.object_key(large_key);
@@ -0,0 +1,13 @@
+-- Create image uploads table for files the browser puts straight into storage.
+-- A row lives from issuing the upload url until the file is finalized.
+CREATE TABLE IF NOT EXISTS image_uploads (
+    object_key TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Only a SHA-256 hash of the token authorizing uploads served by the app itself
+    token_hash TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size BIGINT NOT NULL CHECK (size > 0),
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
@@ -0,0 +1,38 @@
+-- Create estate videos table holding each listing's walkthroughs. A video is
+-- only added once its container and codecs have been checked.
+CREATE TABLE IF NOT EXISTS estate_videos (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    estate_id UUID NOT NULL REFERENCES estates(id) ON DELETE CASCADE,
+    video_key TEXT NOT NULL,
+    -- A frame the agent picked, stored as an image
+    poster_key TEXT,
+    content_type TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    duration_secs DOUBLE PRECISION NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    video_codec TEXT NOT NULL,
+    audio_codec TEXT,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for loading an estate's videos in order
+CREATE INDEX IF NOT EXISTS idx_estate_videos_estate ON estate_videos(estate_id, position);
+
+-- Create video uploads table for multipart uploads in progress. Parts live in
+-- storage; a row lets an interrupted upload of the same file be resumed.
+CREATE TABLE IF NOT EXISTS video_uploads (
+    upload_id TEXT PRIMARY KEY,
+    object_key TEXT NOT NULL UNIQUE,
+    estate_id UUID NOT NULL REFERENCES estates(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size BIGINT NOT NULL CHECK (size > 0),
+    -- Only a SHA-256 hash of the token authorizing parts served by the app itself
+    token_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_video_uploads_resume ON video_uploads(estate_id, user_id, file_name, size);
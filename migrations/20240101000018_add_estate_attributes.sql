@@ -0,0 +1,13 @@
+-- Typed facts about an estate that used to be buried in its description
+ALTER TABLE estates
+    ADD COLUMN property_type TEXT NOT NULL DEFAULT 'apartment'
+        CHECK (property_type IN ('apartment', 'villa', 'penthouse', 'duplex', 'land', 'commercial')),
+    ADD COLUMN bedrooms INTEGER CHECK (bedrooms >= 0),
+    ADD COLUMN bathrooms INTEGER CHECK (bathrooms >= 0),
+    ADD COLUMN floor INTEGER,
+    ADD COLUMN finishing TEXT
+        CHECK (finishing IN ('unfinished', 'semi_finished', 'finished', 'super_lux', 'ultra_lux')),
+    ADD COLUMN furnished BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Create index for filtering listings by type
+CREATE INDEX IF NOT EXISTS idx_estates_property_type ON estates(property_type);
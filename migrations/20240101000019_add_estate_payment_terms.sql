@@ -0,0 +1,17 @@
+-- Sale or rent, and the developer installment plan of a sale: a down
+-- payment followed by monthly or quarterly installments over some years
+ALTER TABLE estates
+    ADD COLUMN purpose TEXT NOT NULL DEFAULT 'sale'
+        CHECK (purpose IN ('sale', 'rent_monthly', 'rent_yearly')),
+    ADD COLUMN down_payment_in_cents BIGINT CHECK (down_payment_in_cents >= 0),
+    ADD COLUMN installment_years INTEGER CHECK (installment_years BETWEEN 1 AND 30),
+    ADD COLUMN installment_frequency TEXT
+        CHECK (installment_frequency IN ('monthly', 'quarterly')),
+    ADD COLUMN installment_in_cents BIGINT CHECK (installment_in_cents > 0),
+    ADD CONSTRAINT estates_installment_plan CHECK (
+        num_nulls(down_payment_in_cents, installment_years, installment_frequency, installment_in_cents) IN (0, 4)
+        AND (purpose = 'sale' OR installment_years IS NULL)
+    );
+
+-- Create index for filtering listings by purpose
+CREATE INDEX IF NOT EXISTS idx_estates_purpose ON estates(purpose);
@@ -0,0 +1,15 @@
+-- Listing lifecycle: new estates start as drafts that only the dashboard
+-- shows, and every transition stamps when the estate last entered a status
+ALTER TABLE estates
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'draft'
+        CHECK (status IN ('draft', 'published', 'reserved', 'sold', 'archived')),
+    ADD COLUMN published_at TIMESTAMPTZ,
+    ADD COLUMN reserved_at TIMESTAMPTZ,
+    ADD COLUMN sold_at TIMESTAMPTZ,
+    ADD COLUMN archived_at TIMESTAMPTZ;
+
+-- Estates listed before statuses existed were already public
+UPDATE estates SET status = 'published', published_at = created_at;
+
+-- Create index for filtering listings by status
+CREATE INDEX IF NOT EXISTS idx_estates_status ON estates(status);
@@ -0,0 +1,10 @@
+-- Scheduled publishing and expiry: the server publishes drafts once their
+-- publish_at has passed and archives published estates past their expire_at
+ALTER TABLE estates
+    ADD COLUMN publish_at TIMESTAMPTZ,
+    ADD COLUMN expire_at TIMESTAMPTZ,
+    ADD CONSTRAINT estates_schedule CHECK (expire_at > publish_at);
+
+-- Create indexes for the scheduler's lookups
+CREATE INDEX IF NOT EXISTS idx_estates_publish_at ON estates(publish_at) WHERE status = 'draft';
+CREATE INDEX IF NOT EXISTS idx_estates_expire_at ON estates(expire_at) WHERE status = 'published';
@@ -0,0 +1,8 @@
+-- Soft deletion: deleted estates and users stay in the trash, restorable,
+-- until the server purges them after the retention period
+ALTER TABLE estates ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Create indexes for the trash page and the purge
+CREATE INDEX IF NOT EXISTS idx_estates_deleted_at ON estates(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_users_deleted_at ON users(deleted_at) WHERE deleted_at IS NOT NULL;
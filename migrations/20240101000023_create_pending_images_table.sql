@@ -0,0 +1,12 @@
+-- Create pending images table for direct uploads that have been checked and
+-- processed but whose estate form has not been submitted yet. The form only
+-- carries the row ids, so an estate can only take images its author uploaded.
+CREATE TABLE IF NOT EXISTS pending_images (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    image_key TEXT NOT NULL,
+    thumbnail_key TEXT,
+    medium_key TEXT,
+    large_key TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
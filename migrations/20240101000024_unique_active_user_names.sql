@@ -0,0 +1,5 @@
+-- Names only have to be unique among users outside the trash, so a deleted
+-- staff member's name can be given to someone new right away
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_name_key;
+DROP INDEX IF EXISTS idx_users_name;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_name ON users(name) WHERE deleted_at IS NULL;
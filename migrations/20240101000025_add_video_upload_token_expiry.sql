@@ -0,0 +1,3 @@
+-- Part urls served by the app itself stop working when their plan expires,
+-- like presigned ones do; a resumed upload gets a new token and expiry
+ALTER TABLE video_uploads ADD COLUMN IF NOT EXISTS token_expires_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,675 @@
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get},
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{ApiCaller, ApiError, ErrorBody};
+use crate::{
+    AppState,
+    auth::Permission,
+    models::{
+        AuditEntity, Estate, EstateAttributes, EstateCursor, EstateFilter, EstateImage, EstatePage,
+        EstateSort, EstateStatus, Finishing, ImageKeys, ListingPurpose, ListingSchedule, NewEstate,
+        PaymentTerms, PropertyType, is_remote_url, validate_price, validate_space,
+    },
+};
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+const MAX_UPLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/estates", get(list_estates).post(create_estate))
+        .route(
+            "/estates/{id}",
+            get(get_estate).patch(update_estate).delete(delete_estate),
+        )
+        .route(
+            "/estates/{id}/images",
+            get(list_images)
+                .post(upload_images)
+                .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/estates/{id}/images/{image_id}", delete(delete_image))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    pub min_space: Option<i32>,
+    pub max_space: Option<i32>,
+    pub property_type: Option<PropertyType>,
+    pub min_bedrooms: Option<i32>,
+    pub min_bathrooms: Option<i32>,
+    /// `0` is the ground floor, negative floors are basements.
+    pub min_floor: Option<i32>,
+    pub max_floor: Option<i32>,
+    pub finishing: Option<Finishing>,
+    pub furnished: Option<bool>,
+    pub purpose: Option<ListingPurpose>,
+    /// Only estates with (`true`) or without (`false`) an installment plan.
+    pub installments: Option<bool>,
+    pub max_down_payment: Option<i64>,
+    /// Only published and reserved estates are listed unless the token may
+    /// view all estates.
+    pub status: Option<EstateStatus>,
+    /// Full-text search over name, address and description.
+    pub text: Option<String>,
+    /// Defaults to `relevance` when searching and `newest` otherwise.
+    pub sort: Option<EstateSort>,
+    /// The `next` cursor of the previous page.
+    pub after: Option<String>,
+    /// Page size, at most 100.
+    pub limit: Option<i64>,
+}
+
+/// Fields to change; absent fields are left as they are.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct EstatePatch {
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub description: Option<String>,
+    pub price_in_cents: Option<i64>,
+    pub space_in_meters: Option<i32>,
+    pub assigned_agent: Option<Uuid>,
+    /// Replaces all typed attributes at once; facts left out are cleared.
+    pub attributes: Option<EstateAttributes>,
+    /// Replaces the purpose and installment plan at once.
+    pub terms: Option<PaymentTerms>,
+    /// Moves the estate along its lifecycle, e.g. from `draft` to
+    /// `published` or from `reserved` to `sold`.
+    pub status: Option<EstateStatus>,
+    /// Replaces both scheduled times at once; times left out are cleared.
+    pub schedule: Option<ListingSchedule>,
+}
+
+/// One or more `image` parts; the first image of a gallery becomes its cover.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ImageUpload {
+    #[schema(value_type = Vec<String>, format = Binary)]
+    image: Vec<Vec<u8>>,
+}
+
+fn validate(
+    name: Option<&str>,
+    price_in_cents: Option<i64>,
+    space_in_meters: Option<i32>,
+    attributes: Option<&EstateAttributes>,
+    terms: Option<&PaymentTerms>,
+    schedule: Option<&ListingSchedule>,
+) -> Result<(), ApiError> {
+    if name.is_some_and(|x| x.trim().is_empty()) {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+    if let Some(price_in_cents) = price_in_cents {
+        validate_price(price_in_cents).map_err(ApiError::BadRequest)?;
+    }
+    if let Some(space_in_meters) = space_in_meters {
+        validate_space(space_in_meters).map_err(ApiError::BadRequest)?;
+    }
+    if let Some(attributes) = attributes {
+        attributes.validate().map_err(ApiError::BadRequest)?;
+    }
+    if let Some(terms) = terms {
+        terms.validate().map_err(ApiError::BadRequest)?;
+    }
+    if let Some(schedule) = schedule {
+        schedule.validate().map_err(ApiError::BadRequest)?;
+    }
+    Ok(())
+}
+
+/// Lists estates page by page; pass the returned `next` as `after` to get
+/// the following page.
+#[utoipa::path(
+    get,
+    path = "/estates",
+    tag = "estates",
+    params(ListQuery),
+    responses(
+        (status = 200, body = EstatePage),
+        (status = 400, body = ErrorBody)
+    )
+)]
+pub async fn list_estates(
+    State(app_state): State<AppState>,
+    caller: Option<ApiCaller>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<EstatePage>, ApiError> {
+    let filter = EstateFilter {
+        min_price: query.min_price,
+        max_price: query.max_price,
+        min_space: query.min_space,
+        max_space: query.max_space,
+        property_type: query.property_type,
+        min_bedrooms: query.min_bedrooms,
+        min_bathrooms: query.min_bathrooms,
+        min_floor: query.min_floor,
+        max_floor: query.max_floor,
+        finishing: query.finishing,
+        furnished: query.furnished,
+        purpose: query.purpose,
+        installments: query.installments,
+        max_down_payment: query.max_down_payment,
+        status: query.status,
+        sort: query
+            .sort
+            .unwrap_or_else(|| EstateSort::default_for(query.text.as_deref())),
+        text: query.text,
+        managed_by: None,
+        listed_only: caller.is_none_or(|x| x.require(Permission::EstatesView).is_err()),
+    };
+    let after = match query.after.as_deref() {
+        Some(after) => Some(
+            EstateCursor::parse(after)
+                .ok_or_else(|| ApiError::BadRequest("invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let page = crate::db::estates::get_estates_page(&app_state.pool, &filter, after, limit).await?;
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/estates/{id}",
+    tag = "estates",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Estate),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub async fn get_estate(
+    State(app_state): State<AppState>,
+    caller: Option<ApiCaller>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Estate>, ApiError> {
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, id).await?;
+    require_visible(&estate, caller.as_ref())?;
+    Ok(Json(estate))
+}
+
+/// Drafts and archived estates exist only for those who manage them;
+/// everyone else gets a 404.
+fn require_visible(estate: &Estate, caller: Option<&ApiCaller>) -> Result<(), ApiError> {
+    if estate.status.is_public()
+        || caller.is_some_and(|x| x.require_estate(estate, Permission::EstatesView).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(ApiError::NotFound)
+    }
+}
+
+/// Creates an estate owned by the token holder. `image_url`, an absolute
+/// http(s) url, becomes the gallery's cover; it may be left empty and filled later
+/// through the images endpoint. The estate starts as a draft until its
+/// `status` is patched to `published` or its `publish_at` passes.
+#[utoipa::path(
+    post,
+    path = "/estates",
+    tag = "estates",
+    request_body = NewEstate,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, body = Estate),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody)
+    )
+)]
+pub async fn create_estate(
+    State(app_state): State<AppState>,
+    caller: ApiCaller,
+    Json(estate): Json<NewEstate>,
+) -> Result<(StatusCode, Json<Estate>), ApiError> {
+    let user_id = caller.require(Permission::EstatesCreate)?;
+    // The scheduler publishes on the caller's behalf.
+    if estate.schedule.publish_at.is_some() {
+        caller.require(Permission::EstatesPublish)?;
+    }
+    validate(
+        Some(&estate.name),
+        Some(estate.price_in_cents),
+        Some(estate.space_in_meters),
+        Some(&estate.attributes),
+        Some(&estate.terms),
+        Some(&estate.schedule),
+    )?;
+    if !estate.image_url.is_empty() && !is_remote_url(&estate.image_url) {
+        return Err(ApiError::BadRequest(
+            "image_url must be an absolute http or https url".to_string(),
+        ));
+    }
+
+    let estate_id = crate::db::estates::create_estate(&app_state.pool, &estate, user_id).await?;
+    if !estate.image_url.is_empty() {
+        crate::db::estate_images::add_estate_images(
+            &app_state.pool,
+            estate_id,
+            &[ImageKeys {
+                original: estate.image_url.clone(),
+                thumbnail: None,
+                medium: None,
+                large: None,
+            }],
+        )
+        .await?;
+    }
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.create",
+        AuditEntity::Estate,
+        Some(estate_id),
+        None,
+        Some(serde_json::json!(estate)),
+    )
+    .await?;
+
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, estate_id).await?;
+    Ok((StatusCode::CREATED, Json(estate)))
+}
+
+/// Applies every given field, or none of them if any is invalid.
+#[utoipa::path(
+    patch,
+    path = "/estates/{id}",
+    tag = "estates",
+    params(("id" = Uuid, Path)),
+    request_body = EstatePatch,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = Estate),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 409, body = ErrorBody)
+    )
+)]
+pub async fn update_estate(
+    State(app_state): State<AppState>,
+    caller: ApiCaller,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<EstatePatch>,
+) -> Result<Json<Estate>, ApiError> {
+    let pool = &app_state.pool;
+    let estate = crate::db::estates::get_estate_by_id(pool, id).await?;
+    let user_id = caller.require_estate(&estate, Permission::EstatesEdit)?;
+    if patch.assigned_agent.is_some() {
+        caller.require(Permission::EstatesManageAll)?;
+    }
+    validate(
+        patch.name.as_deref(),
+        patch.price_in_cents,
+        patch.space_in_meters,
+        patch.attributes.as_ref(),
+        patch.terms.as_ref(),
+        patch.schedule.as_ref(),
+    )?;
+    if let Some(status) = patch.status {
+        estate
+            .status
+            .transition(status)
+            .map_err(ApiError::BadRequest)?;
+        if let Some(permission) = estate.status.transition_permission(status) {
+            caller.require_estate(&estate, permission)?;
+        }
+    }
+    if patch
+        .schedule
+        .as_ref()
+        .is_some_and(|x| x.publish_at.is_some())
+    {
+        caller.require_estate(&estate, Permission::EstatesPublish)?;
+    }
+    if let Some(assigned_agent) = patch.assigned_agent {
+        crate::db::users::get_user_by_id(pool, assigned_agent)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    ApiError::BadRequest("assigned_agent is not a user".to_string())
+                }
+                e => e.into(),
+            })?;
+    }
+
+    // Every field is checked above, so the patch is applied all at once or
+    // not at all.
+    let mut tx = pool.begin().await?;
+
+    let entity = AuditEntity::Estate;
+    // Taken before the fields below are moved into the audit log.
+    let (before_attributes, before_terms, before_schedule) =
+        (estate.attributes(), estate.terms(), estate.schedule());
+    if let Some(status) = patch.status {
+        if !crate::db::estates::update_estate_status(&mut *tx, id, estate.status, status).await? {
+            return Err(ApiError::Conflict(
+                "the estate's status was changed meanwhile".to_string(),
+            ));
+        }
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "status",
+            estate.status,
+            status,
+        )
+        .await?;
+    }
+    if let Some(name) = patch.name {
+        crate::db::estates::update_estate_name(&mut *tx, id, name.clone()).await?;
+        crate::db::audit::record_change(&mut *tx, user_id, entity, id, "name", estate.name, name)
+            .await?;
+    }
+    if let Some(address) = patch.address {
+        crate::db::estates::update_estate_address(&mut *tx, id, address.clone()).await?;
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "address",
+            estate.address,
+            address,
+        )
+        .await?;
+    }
+    if let Some(description) = patch.description {
+        crate::db::estates::update_description(&mut *tx, id, description.clone()).await?;
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "description",
+            estate.description,
+            description,
+        )
+        .await?;
+    }
+    if let Some(price_in_cents) = patch.price_in_cents {
+        crate::db::estates::update_estate_price(&mut *tx, id, price_in_cents).await?;
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "price_in_cents",
+            estate.price_in_cents,
+            price_in_cents,
+        )
+        .await?;
+    }
+    if let Some(space_in_meters) = patch.space_in_meters {
+        crate::db::estates::update_estate_space(&mut *tx, id, space_in_meters).await?;
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "space_in_meters",
+            estate.space_in_meters,
+            space_in_meters,
+        )
+        .await?;
+    }
+    if let Some(attributes) = patch.attributes {
+        crate::db::estates::update_estate_attributes(&mut *tx, id, &attributes).await?;
+        crate::db::audit::record(
+            &mut *tx,
+            user_id,
+            "estate.update_attributes",
+            entity,
+            Some(id),
+            Some(serde_json::json!(before_attributes)),
+            Some(serde_json::json!(attributes)),
+        )
+        .await?;
+    }
+    if let Some(terms) = patch.terms {
+        crate::db::estates::update_estate_payment_terms(&mut *tx, id, &terms).await?;
+        crate::db::audit::record(
+            &mut *tx,
+            user_id,
+            "estate.update_payment_terms",
+            entity,
+            Some(id),
+            Some(serde_json::json!(before_terms)),
+            Some(serde_json::json!(terms)),
+        )
+        .await?;
+    }
+    if let Some(schedule) = patch.schedule {
+        crate::db::estates::update_estate_schedule(&mut *tx, id, &schedule).await?;
+        crate::db::audit::record(
+            &mut *tx,
+            user_id,
+            "estate.update_schedule",
+            entity,
+            Some(id),
+            Some(serde_json::json!(before_schedule)),
+            Some(serde_json::json!(schedule)),
+        )
+        .await?;
+    }
+    if let Some(assigned_agent) = patch.assigned_agent {
+        crate::db::estates::update_estate_assigned_agent(&mut *tx, id, assigned_agent).await?;
+        crate::db::audit::record_change(
+            &mut *tx,
+            user_id,
+            entity,
+            id,
+            "assigned_agent",
+            estate.assigned_agent,
+            Some(assigned_agent),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    let estate = crate::db::estates::get_estate_by_id(pool, id).await?;
+    Ok(Json(estate))
+}
+
+/// Moves the estate to the trash, where it can be restored from the
+/// dashboard until it is purged along with its images and videos.
+#[utoipa::path(
+    delete,
+    path = "/estates/{id}",
+    tag = "estates",
+    params(("id" = Uuid, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub async fn delete_estate(
+    State(app_state): State<AppState>,
+    caller: ApiCaller,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, id).await?;
+    let user_id = caller.require_estate(&estate, Permission::EstatesDelete)?;
+
+    crate::db::estates::delete_estate(&app_state.pool, id).await?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.delete",
+        AuditEntity::Estate,
+        Some(id),
+        Some(serde_json::json!(estate)),
+        None,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_image_objects(
+    app_state: &AppState,
+    images: &[EstateImage],
+) -> Result<(), ApiError> {
+    for key in images.iter().flat_map(|x| x.keys.stored()) {
+        app_state
+            .storage
+            .delete(key)
+            .await
+            .map_err(|e| ApiError::Internal(format!("image delete failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// The estate's gallery in display order.
+#[utoipa::path(
+    get,
+    path = "/estates/{id}/images",
+    tag = "estates",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Vec<EstateImage>),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub async fn list_images(
+    State(app_state): State<AppState>,
+    caller: Option<ApiCaller>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EstateImage>>, ApiError> {
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, id).await?;
+    require_visible(&estate, caller.as_ref())?;
+    let images = crate::db::estate_images::get_estate_images(&app_state.pool, id).await?;
+    Ok(Json(images))
+}
+
+/// Appends every `image` part of a multipart body to the estate's gallery.
+/// JPEG, PNG, WebP and HEIC are accepted; each is stored without its EXIF
+/// metadata together with thumbnail, medium and large WebP variants.
+#[utoipa::path(
+    post,
+    path = "/estates/{id}/images",
+    tag = "estates",
+    params(("id" = Uuid, Path)),
+    request_body(content = ImageUpload, content_type = "multipart/form-data"),
+    security(("bearer" = [])),
+    responses(
+        (status = 201, body = Vec<EstateImage>),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub async fn upload_images(
+    State(app_state): State<AppState>,
+    caller: ApiCaller,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<Vec<EstateImage>>), ApiError> {
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, id).await?;
+    let user_id = caller.require_estate(&estate, Permission::EstatesEdit)?;
+
+    let mut uploads = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?
+    {
+        if field.name() != Some("image") {
+            continue;
+        }
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if !data.is_empty() {
+            uploads.push(data.to_vec());
+        }
+    }
+    if uploads.is_empty() {
+        return Err(ApiError::BadRequest(
+            "no data was recieved for the image".to_string(),
+        ));
+    }
+
+    let mut images = Vec::with_capacity(uploads.len());
+    for data in uploads {
+        images.push(crate::images::store_processed(app_state.storage.as_ref(), data).await?);
+    }
+    crate::db::estate_images::add_estate_images(&app_state.pool, id, &images).await?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.add_images",
+        AuditEntity::Estate,
+        Some(id),
+        None,
+        Some(serde_json::json!({ "images": images })),
+    )
+    .await?;
+
+    let images = crate::db::estate_images::get_estate_images(&app_state.pool, id).await?;
+    Ok((StatusCode::CREATED, Json(images)))
+}
+
+/// Removes an image from the gallery; the next image becomes the cover when
+/// the cover is removed.
+#[utoipa::path(
+    delete,
+    path = "/estates/{id}/images/{image_id}",
+    tag = "estates",
+    params(("id" = Uuid, Path), ("image_id" = Uuid, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody)
+    )
+)]
+pub async fn delete_image(
+    State(app_state): State<AppState>,
+    caller: ApiCaller,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, id).await?;
+    let user_id = caller.require_estate(&estate, Permission::EstatesEdit)?;
+    let image = crate::db::estate_images::get_estate_image(&app_state.pool, image_id).await?;
+    if image.estate_id != id {
+        return Err(ApiError::NotFound);
+    }
+
+    crate::db::estate_images::delete_estate_image(&app_state.pool, id, image_id).await?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.delete_image",
+        AuditEntity::Estate,
+        Some(id),
+        Some(serde_json::json!(image)),
+        None,
+    )
+    .await?;
+
+    delete_image_objects(&app_state, &[image]).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,192 @@
+//! Versioned JSON API for scripts and the mobile apps, mounted at `/api/v1`.
+//!
+//! Writes authenticate with personal API tokens only (`Authorization: Bearer`),
+//! never with the browser session, so the API needs no CSRF protection.
+
+use axum::{
+    Json, Router,
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use utoipa::{
+    Modify, OpenApi, ToSchema,
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    auth::{AuthError, Permission, api_token::TokenCaller, authorize_scoped},
+    images::ImageError,
+    models::Estate,
+};
+
+pub mod estates;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .merge(estates::router())
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Cryptos API", version = "1"),
+    paths(
+        estates::list_estates,
+        estates::get_estate,
+        estates::create_estate,
+        estates::update_estate,
+        estates::delete_estate,
+        estates::list_images,
+        estates::upload_images,
+        estates::delete_image,
+    ),
+    components(schemas(ErrorBody)),
+    modifiers(&BearerAuth),
+    servers((url = "/api/v1"))
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    Unauthorized,
+    Forbidden(String),
+    NotFound,
+    BadRequest(String),
+    /// The resource changed since it was read.
+    Conflict(String),
+    Internal(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            ApiError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                AuthError::Unauthenticated.to_string(),
+            ),
+            ApiError::Forbidden(e) => (StatusCode::FORBIDDEN, e),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Not found".to_string()),
+            ApiError::BadRequest(e) => (StatusCode::BAD_REQUEST, e),
+            ApiError::Conflict(e) => (StatusCode::CONFLICT, e),
+            ApiError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+        };
+        (status, Json(ErrorBody { error })).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => ApiError::NotFound,
+            e => ApiError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ImageError> for ApiError {
+    fn from(e: ImageError) -> Self {
+        match e {
+            ImageError::Encode(_) | ImageError::Storage(_) => ApiError::Internal(e.to_string()),
+            e => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::Unauthenticated => ApiError::Unauthorized,
+            e => ApiError::Forbidden(e.to_string()),
+        }
+    }
+}
+
+/// The token holder behind an API request together with the permissions
+/// their level currently grants.
+pub struct ApiCaller {
+    pub token: TokenCaller,
+    pub granted: Vec<Permission>,
+}
+
+impl FromRequestParts<AppState> for ApiCaller {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .extensions
+            .get::<TokenCaller>()
+            .cloned()
+            .ok_or(ApiError::Unauthorized)?;
+        let granted =
+            crate::db::permissions::get_level_permissions(&state.pool, &token.level).await?;
+        Ok(Self { token, granted })
+    }
+}
+
+/// Lets public endpoints take an `Option<ApiCaller>`, which is `None` for
+/// requests without a token.
+impl OptionalFromRequestParts<AppState> for ApiCaller {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match <Self as FromRequestParts<AppState>>::from_request_parts(parts, state).await {
+            Ok(caller) => Ok(Some(caller)),
+            Err(ApiError::Unauthorized) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl ApiCaller {
+    pub fn require(&self, required: Permission) -> Result<Uuid, ApiError> {
+        Ok(authorize_scoped(
+            Some((self.token.user_id, self.token.level.clone())),
+            &self.granted,
+            &self.token.scopes,
+            required,
+        )?)
+    }
+
+    /// API counterpart of [`crate::auth::require_estate_permission`].
+    pub fn require_estate(&self, estate: &Estate, required: Permission) -> Result<Uuid, ApiError> {
+        let user_id = self.require(required)?;
+        if self.require(Permission::EstatesManageAll).is_ok() || estate.is_managed_by(user_id) {
+            Ok(user_id)
+        } else {
+            Err(AuthError::NotOwner.into())
+        }
+    }
+}
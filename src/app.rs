@@ -1,13 +1,18 @@
 use crate::app::{
     dashboard::{
         Dashboard,
+        api_tokens::ApiTokens,
+        audit_log::AuditLog,
         manage_estates::{
             ManageEstates, add_estate::AddEstate, estate_details::EstateDetails,
-            public_estates::PublicEstates, update_estate::UpdateEstate,
+            estate_quote::EstateQuote, public_estates::PublicEstates, update_estate::UpdateEstate,
         },
+        manage_permissions::ManagePermissions,
         manage_user::{ManageUser, add_user::AddUser, update_user::UpdateUser},
+        my_account::MyAccount,
+        trash::Trash,
     },
-    login::Login,
+    login::{Login, LoginVerify},
     navbar::{About, Footer},
 };
 pub use crate::models::{Estate, SecureUser};
@@ -62,15 +67,22 @@ pub fn App() -> impl IntoView {
                 <Routes fallback=|| "Page not found.".into_view()>
                     <Route path=StaticSegment("/") view=HomePage/>
                     <Route path=StaticSegment("/login") view=Login/>
+                    <Route path=path!("/login/verify") view=LoginVerify/>
                     <Route path=StaticSegment("/estates") view=PublicEstates/>
                     <Route path=StaticSegment("/about") view=About/>
                     <Route path=path!("/dashboard/updateUser/:targetId") view=UpdateUser/>
                     <Route path=path!("/dashboard/updateEstate/:targetId") view=UpdateEstate/>
                     <Route path=path!("/dashboard/estateDetails/:targetId") view=EstateDetails/>
+                    <Route path=path!("/dashboard/estateQuote/:targetId") view=EstateQuote/>
                     <Route path=path!("/dashboard/addUser") view=AddUser/>
                     <Route path=path!("/dashboard/manageUser") view=ManageUser/>
                     <Route path=path!("/dashboard/manageEstates") view=ManageEstates/>
                     <Route path=path!("/dashboard/addEstate") view=AddEstate/>
+                    <Route path=path!("/dashboard/managePermissions") view=ManagePermissions/>
+                    <Route path=path!("/dashboard/auditLog") view=AuditLog/>
+                    <Route path=path!("/dashboard/myAccount") view=MyAccount/>
+                    <Route path=path!("/dashboard/apiTokens") view=ApiTokens/>
+                    <Route path=path!("/dashboard/trash") view=Trash/>
                     <Route path=path!("/dashboard") view=Dashboard/>
                 </Routes>
                 <Footer/>
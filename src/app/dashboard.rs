@@ -1,13 +1,26 @@
 use leptos::prelude::*;
 use uuid::Uuid;
 
-use crate::{LoadingSpinner, app::SecureUser, auth::AuthRequired};
+use crate::{
+    LoadingSpinner,
+    app::SecureUser,
+    auth::{AuthRequired, Permission, PermissionRequired},
+};
 
+pub mod api_tokens;
+pub mod audit_log;
 pub mod manage_estates;
+pub mod manage_permissions;
 pub mod manage_user;
+pub mod my_account;
+pub mod sessions;
+pub mod trash;
 
 #[server]
 async fn get_dashboard_stats() -> Result<(usize, usize), ServerFnError> {
+    use crate::auth::{Level, require_level};
+    require_level(Level::Viewer).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
@@ -74,6 +87,42 @@ fn CardsSection() -> impl IntoView {
                 icon="🏢"
                 gradient="from-purple-500 to-pink-500"
             />
+            <Card
+                name="حسابي"
+                href="/dashboard/myAccount"
+                icon="🔑"
+                gradient="from-emerald-500 to-teal-500"
+            />
+            <Card
+                name="مفاتيح الوصول البرمجي"
+                href="/dashboard/apiTokens"
+                icon="🗝️"
+                gradient="from-indigo-500 to-blue-500"
+            />
+            <PermissionRequired permission=Permission::RolesManage>
+                <Card
+                    name="ادارة الصلاحيات"
+                    href="/dashboard/managePermissions"
+                    icon="🔐"
+                    gradient="from-amber-500 to-orange-500"
+                />
+            </PermissionRequired>
+            <PermissionRequired permission=Permission::AuditView>
+                <Card
+                    name="سجل العمليات"
+                    href="/dashboard/auditLog"
+                    icon="📜"
+                    gradient="from-slate-500 to-gray-700"
+                />
+            </PermissionRequired>
+            <PermissionRequired permission=Permission::EstatesDelete>
+                <Card
+                    name="سلة المحذوفات"
+                    href="/dashboard/trash"
+                    icon="🗑️"
+                    gradient="from-red-500 to-rose-500"
+                />
+            </PermissionRequired>
         </div>
     }
 }
@@ -225,6 +274,9 @@ fn Card(
 
 #[server]
 async fn get_users_names() -> Result<Vec<(Uuid, String)>, ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    require_permission(Permission::UsersView).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
@@ -240,6 +292,9 @@ async fn get_users_names() -> Result<Vec<(Uuid, String)>, ServerFnError> {
 
 #[server]
 async fn get_user_by_id(id: uuid::Uuid) -> Result<SecureUser, ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    require_permission(Permission::UsersView).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
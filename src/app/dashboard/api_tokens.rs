@@ -0,0 +1,348 @@
+use leptos::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    LoadingSpinner,
+    auth::{AuthRequired, Permission},
+    models::ApiToken,
+};
+
+#[cfg(feature = "ssr")]
+const API_TOKENS_PATH: &str = "/dashboard/apiTokens";
+
+/// Scopes a token of the caller may carry: whatever the caller's level holds.
+#[cfg(feature = "ssr")]
+async fn grantable_scopes(
+    pool: &sqlx::PgPool,
+    user_id: Uuid,
+) -> Result<Vec<Permission>, ServerFnError> {
+    use crate::auth::Level;
+
+    let user = crate::db::users::get_user_by_id(pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if user.level == Level::SuperAdmin {
+        return Ok(Permission::ALL.to_vec());
+    }
+    crate::db::permissions::get_level_permissions(pool, &user.level)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    use crate::auth::{Level, require_session_level};
+    let user_id = require_session_level(Level::Viewer).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::api_tokens::get_user_api_tokens(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn get_grantable_scopes() -> Result<Vec<Permission>, ServerFnError> {
+    use crate::auth::{Level, require_session_level};
+    let user_id = require_session_level(Level::Viewer).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    grantable_scopes(&app_state.pool, user_id).await
+}
+
+/// Returns the token created by the previous request once, then forgets it.
+#[server]
+async fn take_new_api_token() -> Result<Option<String>, ServerFnError> {
+    use crate::auth::{
+        Level, api_token::NEW_API_TOKEN_KEY, require_session_level, session_from_context,
+    };
+    require_session_level(Level::Viewer).await?;
+
+    session_from_context()?
+        .remove::<String>(NEW_API_TOKEN_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn create_api_token(
+    name: String,
+    scopes: Option<Vec<Permission>>,
+    expires_in_days: i32,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{
+            Level,
+            api_token::{DISPLAY_PREFIX_LEN, NEW_API_TOKEN_KEY, generate_token, hash_token},
+            require_session_level, session_from_context,
+        },
+        models::AuditEntity,
+    };
+    let user_id = require_session_level(Level::Viewer).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ServerFnError::new("اسم المفتاح مطلوب"));
+    }
+    let scopes = scopes.unwrap_or_default();
+    if scopes.is_empty() {
+        return Err(ServerFnError::new("اختر صلاحية واحدة على الأقل"));
+    }
+    let grantable = grantable_scopes(&app_state.pool, user_id).await?;
+    if let Some(scope) = scopes.iter().find(|x| !grantable.contains(x)) {
+        return Err(ServerFnError::new(format!(
+            "لا تملك صلاحية {} لتمنحها للمفتاح",
+            scope.label()
+        )));
+    }
+
+    let token = generate_token();
+    let token_id = crate::db::api_tokens::create_api_token(
+        &app_state.pool,
+        user_id,
+        &name,
+        &token[..DISPLAY_PREFIX_LEN],
+        &hash_token(&token),
+        &scopes,
+        Some(expires_in_days).filter(|x| *x > 0),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    session_from_context()?
+        .insert(NEW_API_TOKEN_KEY, token)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.create_api_token",
+        AuditEntity::User,
+        Some(user_id),
+        None,
+        Some(serde_json::json!({ "token": token_id, "name": name, "scopes": scopes })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(API_TOKENS_PATH);
+    Ok(())
+}
+
+#[server]
+async fn revoke_api_token(id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Level, require_session_level},
+        models::AuditEntity,
+    };
+    let user_id = require_session_level(Level::Viewer).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::api_tokens::delete_api_token(&app_state.pool, user_id, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.revoke_api_token",
+        AuditEntity::User,
+        Some(user_id),
+        Some(serde_json::json!({ "token": id })),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(API_TOKENS_PATH);
+    Ok(())
+}
+
+#[component]
+pub fn ApiTokens() -> impl IntoView {
+    let tokens_res = Resource::new(|| (), |_| get_api_tokens());
+    let tokens = move || tokens_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let new_token_res = Resource::new(|| (), |_| take_new_api_token());
+    let new_token = move || new_token_res.get().and_then(|x| x.ok()).flatten();
+
+    view! {
+        <AuthRequired>
+            <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
+                <div class="max-w-4xl mx-auto">
+                    <Titles/>
+                    <Suspense fallback=LoadingSpinner>
+                        <ShowLet some=new_token let(token)>
+                            <div class="mb-8 bg-blue-50 border-l-4 border-blue-500 p-4 rounded-lg">
+                                <p class="text-sm text-blue-800 font-semibold mb-2">
+                                    "انسخ المفتاح الآن، لن يظهر مرة أخرى"
+                                </p>
+                                <p class="font-mono text-gray-800 break-all" dir="ltr">{token}</p>
+                            </div>
+                        </ShowLet>
+                        <div class="space-y-4 mb-8">
+                            <For
+                                each={tokens}
+                                key=|x| x.id
+                                let(token)
+                            >
+                                <TokenRow token/>
+                            </For>
+                        </div>
+                    </Suspense>
+                    <CreateToken/>
+                    <NavButton/>
+                </div>
+            </div>
+        </AuthRequired>
+    }
+}
+
+#[component]
+fn TokenRow(token: ApiToken) -> impl IntoView {
+    let revoke = ServerAction::<RevokeApiToken>::new();
+    let ApiToken {
+        id,
+        name,
+        token_prefix,
+        scopes,
+        expires_at,
+        last_used_at,
+        created_at,
+    } = token;
+    let scopes = scopes
+        .iter()
+        .map(|x| x.label())
+        .collect::<Vec<_>>()
+        .join("، ");
+
+    view! {
+        <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 flex items-center justify-between gap-4">
+            <div class="text-sm text-gray-700 space-y-1">
+                <h3 class="text-xl font-bold text-gray-800">{name}</h3>
+                <p class="font-mono" dir="ltr">{format!("{token_prefix}…")}</p>
+                <p>{format!("الصلاحيات: {scopes}")}</p>
+                <p>{format!("أُنشئ: {created_at}")}</p>
+                <p>{format!("ينتهي: {}", expires_at.unwrap_or_else(|| "بدون انتهاء".to_string()))}</p>
+                <p>{format!("آخر استخدام: {}", last_used_at.unwrap_or_else(|| "لم يُستخدم".to_string()))}</p>
+            </div>
+            <ActionForm action={revoke}>
+                <input class="hidden" type="text" value={id.to_string()} name="id"/>
+                <button
+                    class="px-4 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 whitespace-nowrap"
+                    type="submit"
+                >
+                    "إلغاء المفتاح"
+                </button>
+            </ActionForm>
+        </div>
+    }
+}
+
+#[component]
+fn CreateToken() -> impl IntoView {
+    let create = ServerAction::<CreateApiToken>::new();
+    let error_msg = move || {
+        create
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+    let scopes_res = Resource::new(|| (), |_| get_grantable_scopes());
+    let scopes = move || scopes_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let input_class = "w-full px-4 py-3 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-800";
+
+    view! {
+        <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl p-8 border border-gray-100 mb-8">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">"مفتاح جديد"</h2>
+            <ShowLet some=error_msg let(msg)>
+                <div class="mb-6 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                </div>
+            </ShowLet>
+            <ActionForm action={create}>
+                <div class="space-y-6">
+                    <div>
+                        <label class="block text-gray-700 font-bold mb-3" for="name">"الاسم"</label>
+                        <input class=input_class type="text" name="name" id="name" required/>
+                    </div>
+                    <div>
+                        <p class="block text-gray-700 font-bold mb-3">"الصلاحيات"</p>
+                        <Suspense>
+                            <div class="grid grid-cols-1 md:grid-cols-2 gap-2">
+                                {move || {
+                                    scopes()
+                                        .into_iter()
+                                        .map(|scope| {
+                                            let index = Permission::ALL
+                                                .iter()
+                                                .position(|x| *x == scope)
+                                                .unwrap_or_default();
+                                            view! {
+                                                <label class="flex items-center gap-2 text-gray-700">
+                                                    <input
+                                                        type="checkbox"
+                                                        name=format!("scopes[{index}]")
+                                                        value=scope.code()
+                                                    />
+                                                    {scope.label()}
+                                                </label>
+                                            }
+                                        })
+                                        .collect_view()
+                                }}
+                            </div>
+                        </Suspense>
+                    </div>
+                    <div>
+                        <label class="block text-gray-700 font-bold mb-3" for="expires_in_days">"مدة الصلاحية"</label>
+                        <select class=input_class name="expires_in_days" id="expires_in_days">
+                            <option value="30">"30 يوماً"</option>
+                            <option value="90" selected>"90 يوماً"</option>
+                            <option value="365">"سنة"</option>
+                            <option value="0">"بدون انتهاء"</option>
+                        </select>
+                    </div>
+                    <button
+                        class="w-full px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl transition-all duration-300"
+                        type="submit"
+                    >
+                        "إنشاء المفتاح"
+                    </button>
+                </div>
+            </ActionForm>
+        </div>
+    }
+}
+
+#[component]
+fn NavButton() -> impl IntoView {
+    view! {
+        <div class="flex justify-center gap-4">
+            <a
+                href="/dashboard"
+                class="px-8 py-4 bg-white text-gray-700 font-semibold text-lg rounded-xl shadow-lg hover:shadow-xl hover:scale-105 transition-all duration-300 border-2 border-gray-200 hover:border-blue-300"
+            >
+                "← العودة إلى لوحة التحكم"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn Titles() -> impl IntoView {
+    view! {
+        <div class="text-center mb-12">
+            <h1 class="text-4xl md:text-5xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-4 p-4">
+                "مفاتيح الوصول البرمجي"
+            </h1>
+            <p class="text-gray-600 text-lg">"مفاتيح شخصية للسكربتات والتكاملات عبر Authorization: Bearer"</p>
+        </div>
+    }
+}
@@ -0,0 +1,275 @@
+use leptos::prelude::*;
+use leptos_router::{components::Form, hooks::use_query_map};
+use uuid::Uuid;
+
+use crate::{
+    LoadingSpinner,
+    app::dashboard::get_users_names,
+    auth::{AuthRequired, Permission, PermissionRequired},
+    models::{AuditEntity, AuditEntry, AuditFilter, AuditPage},
+};
+
+#[cfg(feature = "ssr")]
+const AUDIT_PAGE_SIZE: i64 = 200;
+
+#[server]
+async fn get_audit_log(
+    filter: AuditFilter,
+    after: Option<String>,
+) -> Result<AuditPage, ServerFnError> {
+    use crate::{auth::require_permission, models::AuditCursor};
+    require_permission(Permission::AuditView).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let after = after.as_deref().and_then(AuditCursor::parse);
+    crate::db::audit::get_audit_page(
+        &app_state.pool,
+        &filter.with_valid_dates(),
+        after,
+        AUDIT_PAGE_SIZE,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[component]
+pub fn AuditLog() -> impl IntoView {
+    let query = use_query_map();
+    let filter = move || {
+        query.with(|q| {
+            let get = |key: &str| q.get(key).filter(|x| !x.is_empty());
+            AuditFilter {
+                actor_id: get("actor_id").and_then(|x| Uuid::parse_str(&x).ok()),
+                entity_type: get("entity_type")
+                    .and_then(|x| AuditEntity::ALL.into_iter().find(|e| e.as_str() == x)),
+                entity_id: get("entity_id").and_then(|x| Uuid::parse_str(&x).ok()),
+                from: get("from"),
+                to: get("to"),
+            }
+            .with_valid_dates()
+        })
+    };
+    let after = move || query.with(|q| q.get("after"));
+    let page_res = Resource::new(
+        move || (filter(), after()),
+        |(filter, after)| get_audit_log(filter, after),
+    );
+    let page = move || page_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let entries = move || page().entries;
+
+    view! {
+        <AuthRequired>
+            <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
+                <div class="max-w-7xl mx-auto">
+                    <Titles/>
+                    <PermissionRequired permission=Permission::AuditView>
+                        <Filters filter=Signal::derive(filter)/>
+                        <Suspense fallback=LoadingSpinner>
+                            <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 overflow-x-auto mb-8">
+                                <table class="w-full text-right text-sm">
+                                    <thead>
+                                        <tr class="text-gray-600">
+                                            <th class="px-3 py-2">"الوقت"</th>
+                                            <th class="px-3 py-2">"المستخدم"</th>
+                                            <th class="px-3 py-2">"العملية"</th>
+                                            <th class="px-3 py-2">"الكيان"</th>
+                                            <th class="px-3 py-2">"قبل"</th>
+                                            <th class="px-3 py-2">"بعد"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        <For
+                                            each={entries}
+                                            key=|x| x.id
+                                            let(AuditEntry { actor_name, action, entity_type, entity_id, before, after, created_at, .. })
+                                        >
+                                            <tr class="border-t border-gray-100 align-top">
+                                                <td class="px-3 py-2 whitespace-nowrap text-gray-500">{created_at}</td>
+                                                <td class="px-3 py-2 font-semibold text-gray-800">
+                                                    {actor_name.unwrap_or_else(|| "—".to_string())}
+                                                </td>
+                                                <td class="px-3 py-2 font-mono text-blue-700">{action}</td>
+                                                <td class="px-3 py-2 text-gray-700">
+                                                    {entity_type}
+                                                    <span class="block text-xs text-gray-400 font-mono">
+                                                        {entity_id.map(|x| x.to_string()).unwrap_or_default()}
+                                                    </span>
+                                                </td>
+                                                <td class="px-3 py-2 font-mono text-xs text-red-700 break-all">{before.unwrap_or_default()}</td>
+                                                <td class="px-3 py-2 font-mono text-xs text-green-700 break-all">{after.unwrap_or_default()}</td>
+                                            </tr>
+                                        </For>
+                                    </tbody>
+                                </table>
+                            </div>
+                            <Pagination
+                                filter=Signal::derive(filter)
+                                paged=Signal::derive(move || after().is_some())
+                                next=Signal::derive(move || page().next)
+                            />
+                        </Suspense>
+                    </PermissionRequired>
+                    <NavButton/>
+                </div>
+            </div>
+        </AuthRequired>
+    }
+}
+
+#[component]
+fn Filters(filter: Signal<AuditFilter>) -> impl IntoView {
+    let users_res = Resource::new(|| (), |_| get_users_names());
+    let users = move || users_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let input_class = "w-full px-4 py-2 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-800";
+
+    view! {
+        <Form method="GET" action="">
+            <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 mb-8 grid grid-cols-1 md:grid-cols-3 lg:grid-cols-6 gap-4 items-end">
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="actor_id">"المستخدم"</label>
+                    <select class=input_class name="actor_id" id="actor_id">
+                        <option value="">"الكل"</option>
+                        <Suspense>
+                            <For
+                                each={users}
+                                key=|x| x.0
+                                let((id, name))
+                            >
+                                <option
+                                    value={id.to_string()}
+                                    selected=move || filter.get().actor_id == Some(id)
+                                >{name}</option>
+                            </For>
+                        </Suspense>
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="entity_type">"الكيان"</label>
+                    <select class=input_class name="entity_type" id="entity_type">
+                        <option value="">"الكل"</option>
+                        {AuditEntity::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || filter.get().entity_type == Some(x)
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="entity_id">"المعرف"</label>
+                    <input
+                        class=input_class
+                        type="text"
+                        name="entity_id"
+                        id="entity_id"
+                        prop:value=move || filter.get().entity_id.map(|x| x.to_string()).unwrap_or_default()
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="from">"من"</label>
+                    <input
+                        class=input_class
+                        type="date"
+                        name="from"
+                        id="from"
+                        prop:value=move || filter.get().from.unwrap_or_default()
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="to">"إلى"</label>
+                    <input
+                        class=input_class
+                        type="date"
+                        name="to"
+                        id="to"
+                        prop:value=move || filter.get().to.unwrap_or_default()
+                    />
+                </div>
+                <input
+                    class="px-4 py-2.5 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-semibold rounded-lg shadow-md hover:shadow-lg hover:scale-105 transition-all duration-300 text-center"
+                    type="submit"
+                    value="تصفية"
+                />
+            </div>
+        </Form>
+    }
+}
+
+/// The filter as hidden form fields, so moving between pages keeps it.
+#[component]
+fn FilterFields(filter: AuditFilter) -> impl IntoView {
+    let fields = [
+        ("actor_id", filter.actor_id.map(|x| x.to_string())),
+        (
+            "entity_type",
+            filter.entity_type.map(|x| x.as_str().to_string()),
+        ),
+        ("entity_id", filter.entity_id.map(|x| x.to_string())),
+        ("from", filter.from),
+        ("to", filter.to),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .map(|(name, value)| view! { <input class="hidden" type="text" name=name value=value/> })
+        .collect_view()
+}
+
+#[component]
+fn Pagination(
+    filter: Signal<AuditFilter>,
+    /// Whether the current page was reached through a cursor.
+    paged: Signal<bool>,
+    next: Signal<Option<String>>,
+) -> impl IntoView {
+    let button_class = "px-6 py-2.5 bg-white text-gray-700 font-semibold rounded-lg shadow-md border-2 border-gray-200 hover:border-blue-300";
+
+    view! {
+        <div class="flex justify-center gap-4 mb-8">
+            <Show when=move || paged.get()>
+                <Form method="GET" action="">
+                    <FilterFields filter=filter.get()/>
+                    <input class=button_class type="submit" value="الصفحة الأولى"/>
+                </Form>
+            </Show>
+            <ShowLet some=move || next.get() let(after)>
+                <Form method="GET" action="">
+                    <FilterFields filter=filter.get()/>
+                    <input class="hidden" type="text" name="after" value=after/>
+                    <input class=button_class type="submit" value="الصفحة التالية"/>
+                </Form>
+            </ShowLet>
+        </div>
+    }
+}
+
+#[component]
+fn NavButton() -> impl IntoView {
+    view! {
+        <div class="flex justify-center gap-4">
+            <a
+                href="/dashboard"
+                class="px-8 py-4 bg-white text-gray-700 font-semibold text-lg rounded-xl shadow-lg hover:shadow-xl hover:scale-105 transition-all duration-300 border-2 border-gray-200 hover:border-blue-300"
+            >
+                "← العودة إلى لوحة التحكم"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn Titles() -> impl IntoView {
+    view! {
+        <div class="text-center mb-12">
+            <h1 class="text-4xl md:text-5xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-4 p-4">
+                "سجل العمليات"
+            </h1>
+            <p class="text-gray-600 text-lg">"من قام بماذا ومتى"</p>
+        </div>
+    }
+}
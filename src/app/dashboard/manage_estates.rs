@@ -1,86 +1,158 @@
 use leptos::prelude::*;
+use leptos_router::hooks::use_query_map;
 use uuid::Uuid;
 
 use crate::LoadingSpinner;
 use crate::app::Estate;
-use crate::auth::{AdminOnly, AuthRequired};
+use crate::auth::{AuthRequired, Permission, PermissionRequired};
+use crate::models::{EstateAttributes, EstateFilter, EstateListing, EstatePage};
+use estate_attributes::EstateFacts;
+use estate_filters::{EstateFilters, Pagination, Snippet, filter_from_query};
+use estate_status::{ScheduleNote, StatusBadge};
+use expiry_notices::ExpiryNotices;
 
 pub mod add_estate;
+pub mod estate_attributes;
 pub mod estate_details;
+pub mod estate_filters;
+pub mod estate_quote;
+pub mod estate_status;
+pub mod expiry_notices;
+pub mod gallery;
+pub mod payment_terms;
 pub mod public_estates;
 pub mod update_estate;
+pub mod uploads;
+pub mod videos;
 
 #[server]
 async fn remove_estate(target_id: uuid::Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesDelete).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    let image_url = crate::db::estates::get_image_url_by_id(&app_state.pool, target_id)
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
-    let image_url = std::path::PathBuf::from(image_url);
-    let name = image_url.file_name().unwrap().to_str().unwrap().to_string();
 
     crate::db::estates::delete_estate(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-    app_state
-        .s3
-        .client
-        .delete_object()
-        .bucket(app_state.s3.bucket)
-        .key(&name)
-        .send()
-        .await
-        .map_err(|e| ServerFnError::new(format!("S3 Delete failed: {e:?}")))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.delete",
+        AuditEntity::Estate,
+        Some(target_id),
+        Some(serde_json::json!(estate)),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect("/dashboard/manageEstates");
     Ok(())
 }
 
 #[server]
-async fn get_estates() -> Result<Vec<Estate>, ServerFnError> {
+async fn get_estates(
+    mine: bool,
+    filter: EstateFilter,
+    after: Option<String>,
+) -> Result<EstatePage, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_permission},
+        models::EstateCursor,
+    };
+    let user_id = require_permission(Permission::EstatesView).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    let res = crate::db::estates::get_all_estates(&app_state.pool)
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let filter = EstateFilter {
+        managed_by: mine.then_some(user_id),
+        ..filter
+    };
+    let after = after.as_deref().and_then(EstateCursor::parse);
+    let res = crate::db::estates::get_estates_page(
+        &app_state.pool,
+        &filter,
+        after,
+        estate_filters::ESTATES_PAGE_SIZE,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
     Ok(res)
 }
 
+/// The caller's id and whether they may manage listings owned by others.
+#[server]
+async fn get_estate_scope() -> Result<(Uuid, bool), ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    let user_id = require_permission(Permission::EstatesView).await?;
+    let manage_all = require_permission(Permission::EstatesManageAll)
+        .await
+        .is_ok();
+    Ok((user_id, manage_all))
+}
+
 #[component]
 pub fn ManageEstates() -> impl IntoView {
-    let estates_res = Resource::new(|| (), move |_| get_estates());
-    let estates = move || estates_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let query = use_query_map();
+    let mine = move || query.with(|q| q.get("mine")).is_some_and(|x| x == "true");
+    let filter = move || query.with(filter_from_query);
+    let after = move || query.with(|q| q.get("after"));
+    let estates_res = Resource::new(
+        move || (mine(), filter(), after()),
+        |(mine, filter, after)| get_estates(mine, filter, after),
+    );
+    let page = move || estates_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let estates = move || page().items;
+    let scope = Resource::new(|| (), |_| get_estate_scope());
 
     view! {
         <AuthRequired>
         <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
             <div class="max-w-7xl mx-auto">
                 <Titles/>
+                <OwnershipTabs mine=Signal::derive(mine)/>
+                <ExpiryNotices/>
+                <EstateFilters filter=Signal::derive(filter) mine=Signal::derive(mine) with_status=true/>
                 <Suspense fallback=LoadingSpinner>
                     <div class="grid grid-cols-1 lg:grid-cols-2 gap-8 mb-8">
                         <For
                             each={estates}
-                            key=|x| x.id
-                            let(Estate { id, name, address, image_url, price_in_cents, space_in_meters, .. })
+                            key=|x| x.estate.id
+                            let(EstateListing { estate: Estate { id, name, address, image_url, thumbnail_url, price_in_cents, space_in_meters, created_by, assigned_agent, property_type, bedrooms, bathrooms, floor, finishing, furnished, purpose, status, publish_at, expire_at, .. }, snippet })
                         >
                             <div class="group bg-white/90 backdrop-blur-sm rounded-2xl shadow-lg hover:shadow-2xl transition-all duration-500 overflow-hidden border border-gray-100 hover:scale-[1.02]">
                                 <div class="relative h-64 overflow-hidden">
                                     <img
                                         class="w-full h-full object-cover transform group-hover:scale-110 transition-transform duration-700"
-                                        src={image_url}
+                                        src={thumbnail_url.unwrap_or(image_url)}
                                         alt={name.clone()}
                                     />
                                     <div class="absolute inset-0 bg-gradient-to-t from-black/60 to-transparent opacity-0 group-hover:opacity-100 transition-opacity duration-300"></div>
+                                    <span class="absolute top-4 right-4 px-3 py-1 bg-white/90 text-blue-700 text-sm font-semibold rounded-full shadow">
+                                        {purpose.label()}
+                                    </span>
+                                    <div class="absolute top-4 left-4">
+                                        <StatusBadge status/>
+                                    </div>
                                 </div>
 
                                 <div class="p-6">
                                     <h2 class="text-2xl font-bold text-gray-800 mb-3 group-hover:text-blue-600 transition-colors duration-300">
                                         {name}
                                     </h2>
+                                    <ShowLet some=move || snippet.clone() let(snippet)>
+                                        <Snippet snippet/>
+                                    </ShowLet>
 
                                     <div class="space-y-3 mb-6">
                                         <div class="flex items-center text-gray-600 gap-2">
@@ -103,15 +175,23 @@ pub fn ManageEstates() -> impl IntoView {
                                                 <svg class="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 8c-1.657 0-3 .895-3 2s1.343 2 3 2 3 .895 3 2-1.343 2-3 2m0-8c1.11 0 2.08.402 2.599 1M12 8V7m0 1v8m0 0v1m0-1c-1.11 0-2.08-.402-2.599-1M21 12a9 9 0 11-18 0 9 9 0 0118 0z"></path>
                                                 </svg>
-                                                <span class="font-bold">{format!("{:.2}", price_in_cents as f32 / 100.0)}" ج"</span>
+                                                <span class="font-bold">{format!("{:.2}", price_in_cents as f32 / 100.0)}" ج"{purpose.price_suffix()}</span>
                                             </div>
                                         </div>
+                                        <EstateFacts attributes={EstateAttributes { property_type, bedrooms, bathrooms, floor, finishing, furnished }}/>
+                                        <ScheduleNote status publish_at expire_at/>
                                     </div>
-                                    <ActionsButtons id/>
+                                    <ActionsButtons id created_by assigned_agent scope/>
                                 </div>
                             </div>
                         </For>
                     </div>
+                    <Pagination
+                        filter=Signal::derive(filter)
+                        paged=Signal::derive(move || after().is_some())
+                        next=Signal::derive(move || page().next)
+                        mine=Signal::derive(mine)
+                    />
                 </Suspense>
                 <NavButtons/>
             </div>
@@ -121,12 +201,48 @@ pub fn ManageEstates() -> impl IntoView {
 }
 
 #[component]
-fn ActionsButtons(id: Uuid) -> impl IntoView {
+fn ActionsButtons(
+    id: Uuid,
+    created_by: Option<Uuid>,
+    assigned_agent: Option<Uuid>,
+    scope: Resource<Result<(Uuid, bool), ServerFnError>>,
+) -> impl IntoView {
+    let managed = move || {
+        scope
+            .get()
+            .and_then(|x| x.ok())
+            .is_some_and(|(user_id, manage_all)| {
+                manage_all || created_by == Some(user_id) || assigned_agent == Some(user_id)
+            })
+    };
     view! {
         <div class="flex flex-wrap gap-3">
             <DetailsButton id/>
-            <UpdateButton id/>
-            <DeleteButton id/>
+            <Show when=managed>
+                <UpdateButton id/>
+                <DeleteButton id/>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn OwnershipTabs(mine: Signal<bool>) -> impl IntoView {
+    let class = move |active: bool| {
+        if active {
+            "px-6 py-2.5 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-semibold rounded-lg shadow-md"
+        } else {
+            "px-6 py-2.5 bg-white text-gray-700 font-semibold rounded-lg shadow-md border-2 border-gray-200 hover:border-blue-300"
+        }
+    };
+    view! {
+        <div class="flex justify-center gap-4 mb-8">
+            <a href="/dashboard/manageEstates" class=move || class(!mine.get())>
+                "كل العقارات"
+            </a>
+            <a href="/dashboard/manageEstates?mine=true" class=move || class(mine.get())>
+                "عقاراتي"
+            </a>
         </div>
     }
 }
@@ -146,14 +262,14 @@ fn DetailsButton(id: Uuid) -> impl IntoView {
 #[component]
 fn UpdateButton(id: Uuid) -> impl IntoView {
     view! {
-        <AdminOnly>
+        <PermissionRequired permission=Permission::EstatesEdit>
             <a
                 href={format!("/dashboard/updateEstate/{}",id)}
                 class="flex-1 px-4 py-2.5 bg-gradient-to-r from-green-500 to-emerald-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg hover:scale-105 transition-all duration-300 text-center"
             >
                 "تحديث"
             </a>
-        </AdminOnly>
+        </PermissionRequired>
     }
 }
 
@@ -161,7 +277,7 @@ fn UpdateButton(id: Uuid) -> impl IntoView {
 fn DeleteButton(id: Uuid) -> impl IntoView {
     let remove_estate = ServerAction::<RemoveEstate>::new();
     view! {
-        <AdminOnly>
+        <PermissionRequired permission=Permission::EstatesDelete>
             <div class="flex-1">
                 <ActionForm action={remove_estate}>
                     <input class="hidden" name="target_id" value={id.to_string()}/>
@@ -173,7 +289,7 @@ fn DeleteButton(id: Uuid) -> impl IntoView {
                     </button>
                 </ActionForm>
             </div>
-        </AdminOnly>
+        </PermissionRequired>
     }
 }
 
@@ -181,7 +297,7 @@ fn DeleteButton(id: Uuid) -> impl IntoView {
 fn NavButtons() -> impl IntoView {
     view! {
         <div class="flex justify-center gap-4 mt-12">
-            <AdminOnly>
+            <PermissionRequired permission=Permission::EstatesCreate>
                 <a
                     href="/dashboard/addEstate"
                     class="group px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl hover:scale-105 transition-all duration-300 flex items-center gap-3"
@@ -191,7 +307,7 @@ fn NavButtons() -> impl IntoView {
                     </svg>
                     "إضافة عقار جديد"
                 </a>
-            </AdminOnly>
+            </PermissionRequired>
 
             <a
                 href="/dashboard"
@@ -1,31 +1,125 @@
+// `add_estate` takes every field of the add form; `#[server]` does not pass
+// an `allow` on the function through to the code it generates.
+#![allow(clippy::too_many_arguments)]
+
 use leptos::prelude::*;
-use web_sys::{FormData, HtmlFormElement, HtmlInputElement, SubmitEvent, wasm_bindgen::JsCast};
+use web_sys::{
+    File, HtmlFormElement, HtmlInputElement, SubmitEvent,
+    wasm_bindgen::{JsCast, JsValue},
+};
 
+use super::estate_attributes::AttributeFields;
+use super::estate_status::ScheduleFields;
+use super::payment_terms::PaymentTermsFields;
+use super::uploads::put_blob;
 use crate::auth::AuthRequired;
+use crate::models::{
+    EstateAttributes, ImageUrls, ListingSchedule, PaymentTerms, PresignedUpload, UploadRequest,
+};
 
 #[server]
 async fn add_estate(
     name: String,
     address: String,
-    image_url: String,
+    images: Option<Vec<uuid::Uuid>>,
     price_in_cents: i64,
     space_in_meters: i32,
     description: String,
+    attributes: EstateAttributes,
+    terms: PaymentTerms,
+    publish: bool,
+    schedule: ListingSchedule,
 ) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_permission},
+        models::{AuditEntity, validate_price, validate_space},
+    };
+    let user_id = require_permission(Permission::EstatesCreate).await?;
+    let schedule = schedule.trimmed();
+    if publish || schedule.publish_at.is_some() {
+        require_permission(Permission::EstatesPublish).await?;
+    }
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::create_estate(
-        &app_state.pool,
+    validate_price(price_in_cents).map_err(ServerFnError::new)?;
+    validate_space(space_in_meters).map_err(ServerFnError::new)?;
+    attributes.validate().map_err(ServerFnError::new)?;
+    terms.validate().map_err(ServerFnError::new)?;
+    schedule.validate().map_err(ServerFnError::new)?;
+    // The form only names pending images; their keys never leave the server.
+    let image_ids = images.unwrap_or_default();
+    let distinct = image_ids
+        .iter()
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    let images = crate::db::image_uploads::get_pending_images(&app_state.pool, user_id, &image_ids)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if images.len() != image_ids.len() || distinct != image_ids.len() {
+        return Err(ServerFnError::new(
+            crate::images::ImageError::UploadNotFound.to_string(),
+        ));
+    }
+    let image_url = images
+        .first()
+        .map(|x| x.original.clone())
+        .ok_or_else(|| ServerFnError::new("at least one image is required"))?;
+
+    let estate = crate::models::NewEstate {
         name,
         address,
         image_url,
         price_in_cents,
         space_in_meters,
         description,
+        attributes,
+        terms,
+        schedule,
+    };
+    let estate_id = crate::db::estates::create_estate(&app_state.pool, &estate, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::estate_images::add_estate_images(&app_state.pool, estate_id, &images)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::image_uploads::delete_pending_images(&app_state.pool, &image_ids)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.create",
+        AuditEntity::Estate,
+        Some(estate_id),
+        None,
+        Some(serde_json::json!({ "estate": estate, "images": images })),
     )
     .await
     .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if publish {
+        use crate::models::EstateStatus;
+        crate::db::estates::update_estate_status(
+            &app_state.pool,
+            estate_id,
+            EstateStatus::Draft,
+            EstateStatus::Published,
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+        crate::db::audit::record_change(
+            &app_state.pool,
+            user_id,
+            AuditEntity::Estate,
+            estate_id,
+            "status",
+            EstateStatus::Draft,
+            EstateStatus::Published,
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
     leptos_axum::redirect("/dashboard/manageEstates");
     Ok(())
 }
@@ -138,6 +232,16 @@ pub fn AddEstate() -> impl IntoView {
                                         </p>
                                     </div>
 
+                                    <AttributeFields
+                                        input_class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800 placeholder-gray-400"
+                                        label_class="block text-gray-700 font-bold mb-3 text-lg"
+                                    />
+
+                                    <PaymentTermsFields
+                                        input_class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-green-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800 placeholder-gray-400"
+                                        label_class="block text-gray-700 font-bold mb-3 text-lg"
+                                    />
+
                                     <div class="group">
                                         <label
                                             class="block text-gray-700 font-bold mb-3 text-lg flex items-center gap-2"
@@ -159,6 +263,24 @@ pub fn AddEstate() -> impl IntoView {
                                             minlength="15"
                                         ></textarea>
                                     </div>
+
+                                    <div class="group">
+                                        <label class="block text-gray-700 font-bold mb-3 text-lg" for="publish">"حالة الإعلان"</label>
+                                        <select
+                                            class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800"
+                                            name="publish"
+                                            id="publish"
+                                        >
+                                            <option value="false" selected>"حفظ كمسودة"</option>
+                                            <option value="true">"نشر فوراً"</option>
+                                        </select>
+                                        <p class="mt-2 text-sm text-gray-500">"المسودات لا تظهر للزوار حتى يتم نشرها."</p>
+                                    </div>
+
+                                    <ScheduleFields
+                                        input_class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800"
+                                        label_class="block text-gray-700 font-bold mb-3 text-lg"
+                                    />
                                 </div>
 
                                     <UploadImage/>
@@ -171,88 +293,298 @@ pub fn AddEstate() -> impl IntoView {
     }
 }
 
-#[server(input = server_fn::codec::MultipartFormData)]
-async fn upload_image(data: server_fn::codec::MultipartData) -> Result<String, ServerFnError> {
+/// Issues one upload url per announced file. The browser puts the files
+/// straight into storage and then hands the keys to [`finalize_uploads`].
+#[server]
+async fn presign_uploads(files: Vec<UploadRequest>) -> Result<Vec<PresignedUpload>, ServerFnError> {
+    use crate::{
+        auth::{Permission, api_token::hash_token, require_permission},
+        images::{ImageError, ImageKind, max_image_bytes},
+        storage::{PUBLIC_PATH, UPLOAD_URL_TTL},
+    };
+    let user_id = require_permission(Permission::EstatesCreate).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    let mut data = data.into_inner().unwrap();
-
-    let mut image_data = Vec::new();
-    let mut image_name = String::new();
-    let mut image_kind = String::new();
-
-    while let Ok(Some(mut field)) = data.next_field().await {
-        let name = field.name().unwrap_or_default().to_string();
-        match name.as_str() {
-            "data" => {
-                while let Ok(Some(chunk)) = field.chunk().await {
-                    image_data.extend(chunk.to_vec());
-                }
-            }
-            "image_name" => {
-                if let Ok(imgn) = field.text().await {
-                    image_name = imgn;
-                }
-            }
-            "image_kind" => {
-                if let Ok(imgk) = field.text().await {
-                    image_kind = imgk;
-                }
-            }
-            _ => (),
-        };
-    }
-    if image_data.is_empty() {
+    if files.is_empty() {
         return Err(ServerFnError::new("no data was recieved for the image"));
     }
-    if image_name.is_empty() {
-        return Err(ServerFnError::new("no name was recieved for the image"));
+    let limit = max_image_bytes();
+    let mut uploads = Vec::with_capacity(files.len());
+    for file in files {
+        if file.size <= 0 {
+            return Err(ServerFnError::new("no data was recieved for the image"));
+        }
+        if file.size as usize > limit {
+            return Err(ServerFnError::new(ImageError::TooLarge(limit).to_string()));
+        }
+        if ImageKind::from_content_type(&file.content_type).is_none() {
+            return Err(ServerFnError::new(ImageError::Unsupported.to_string()));
+        }
+
+        let key = format!("upload-{}", uuid::Uuid::new_v4());
+        let token = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        crate::db::image_uploads::create_image_upload(
+            &app_state.pool,
+            &key,
+            user_id,
+            &hash_token(&token),
+            &file.content_type,
+            file.size,
+            UPLOAD_URL_TTL.as_secs_f64(),
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let url = app_state
+            .storage
+            .presign_put(&key, &file.content_type, file.size, UPLOAD_URL_TTL)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?
+            .unwrap_or_else(|| format!("{PUBLIC_PATH}/{key}?token={token}"));
+        uploads.push(PresignedUpload {
+            key,
+            url,
+            content_type: file.content_type,
+        });
     }
-    if image_kind.is_empty() {
-        return Err(ServerFnError::new("no kind was recieved for the image"));
+    Ok(uploads)
+}
+
+/// Checks the direct uploads and turns them into pending gallery images
+/// before the estate exists. Only their ids travel back with the estate
+/// form; the urls are for the preview.
+#[server]
+async fn finalize_uploads(
+    keys: Vec<String>,
+) -> Result<Vec<(uuid::Uuid, ImageUrls)>, ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    let user_id = require_permission(Permission::EstatesCreate).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let base = crate::storage::public_base();
+    let mut images = Vec::with_capacity(keys.len());
+    for key in keys {
+        let image = crate::images::finalize_upload(
+            &app_state.pool,
+            app_state.storage.as_ref(),
+            user_id,
+            &key,
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let id = crate::db::image_uploads::create_pending_image(&app_state.pool, user_id, &image)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        images.push((id, image.public_urls(base)));
     }
-    let prefix = uuid::Uuid::new_v4().to_string();
-    image_name = prefix + &image_name;
+    Ok(images)
+}
 
-    let url = app_state
-        .s3
-        .store_image(&image_name, Some(image_kind), image_data.into())
-        .await?;
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UploadState {
+    Uploading,
+    Done,
+    Failed,
+}
+
+/// One file on its way to storage.
+#[derive(Debug, Clone)]
+struct FileUpload {
+    name: String,
+    key: Option<String>,
+    loaded: f64,
+    total: f64,
+    state: UploadState,
+}
+
+impl FileUpload {
+    fn percent(&self) -> u32 {
+        if self.total > 0.0 {
+            (self.loaded / self.total * 100.0).min(100.0) as u32
+        } else {
+            0
+        }
+    }
+}
 
-    Ok(url)
+/// Puts `file` to its presigned url, reporting progress into `uploads[i]`.
+fn put_file(
+    file: File,
+    upload: &PresignedUpload,
+    i: usize,
+    uploads: RwSignal<Vec<FileUpload>>,
+) -> Result<(), JsValue> {
+    let update = move |f: &dyn Fn(&mut FileUpload)| {
+        uploads.update(|xs| {
+            if let Some(x) = xs.get_mut(i) {
+                f(x);
+            }
+        })
+    };
+    put_blob(
+        &upload.url,
+        Some(&upload.content_type),
+        &file,
+        move |loaded| update(&|x| x.loaded = loaded),
+        move |ok| {
+            let state = if ok {
+                UploadState::Done
+            } else {
+                UploadState::Failed
+            };
+            update(&|x| x.state = state);
+        },
+    )
 }
 
 #[island]
 fn UploadImage() -> impl IntoView {
-    let file_input = NodeRef::new();
-
-    let upload_action =
-        Action::new_local(|data: &web_sys::FormData| upload_image(data.clone().into()));
+    let uploads = RwSignal::new(Vec::<FileUpload>::new());
+    let error = RwSignal::new(None::<String>);
+    let finalized = RwSignal::new(false);
+    let finalize_action = Action::new_local(|keys: &Vec<String>| finalize_uploads(keys.clone()));
 
     let on_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
-        let input: HtmlInputElement = file_input.get().unwrap();
-        let files = input.files().unwrap();
-        let file = files.get(0).unwrap();
-        let name = file.name();
-        let kind = file.type_();
-
         let target = ev.target().unwrap().unchecked_into::<HtmlFormElement>();
-        let form_data = FormData::new_with_form(&target).unwrap();
-        let _ = form_data.append_with_str("image_name", &name);
-        let _ = form_data.append_with_str("image_kind", &kind);
-        upload_action.dispatch_local(form_data);
+        let Some(files) = target
+            .query_selector("input[type=file]")
+            .ok()
+            .flatten()
+            .and_then(|x| x.unchecked_into::<HtmlInputElement>().files())
+        else {
+            return;
+        };
+        let files = (0..files.length())
+            .filter_map(|i| files.get(i))
+            .collect::<Vec<File>>();
+        if files.is_empty() {
+            return;
+        }
+
+        error.set(None);
+        finalized.set(false);
+        uploads.set(
+            files
+                .iter()
+                .map(|x| FileUpload {
+                    name: x.name(),
+                    key: None,
+                    loaded: 0.0,
+                    total: x.size(),
+                    state: UploadState::Uploading,
+                })
+                .collect(),
+        );
+        let requests = files
+            .iter()
+            .map(|x| UploadRequest {
+                content_type: x.type_(),
+                size: x.size() as i64,
+            })
+            .collect();
+        leptos::task::spawn_local(async move {
+            let presigned = match presign_uploads(requests).await {
+                Ok(x) => x,
+                Err(e) => {
+                    uploads.set(Vec::new());
+                    error.set(Some(e.to_string()));
+                    return;
+                }
+            };
+            for (i, (file, upload)) in files.into_iter().zip(presigned).enumerate() {
+                uploads.update(|xs| {
+                    if let Some(x) = xs.get_mut(i) {
+                        x.key = Some(upload.key.clone());
+                    }
+                });
+                if put_file(file, &upload, i, uploads).is_err() {
+                    uploads.update(|xs| {
+                        if let Some(x) = xs.get_mut(i) {
+                            x.state = UploadState::Failed;
+                        }
+                    });
+                }
+            }
+        });
     };
 
-    let image = move || upload_action.value().get().transpose().ok().flatten();
+    // Once every file is in storage the keys go to the server in one go; a
+    // failed file sends the user back to the picker.
+    Effect::new(move || {
+        let xs = uploads.get();
+        if let Some(x) = xs.iter().find(|x| x.state == UploadState::Failed) {
+            error.set(Some(format!("تعذر رفع {}", x.name)));
+            uploads.set(Vec::new());
+        } else if !xs.is_empty()
+            && xs.iter().all(|x| x.state == UploadState::Done)
+            && !finalized.get_untracked()
+        {
+            finalized.set(true);
+            finalize_action.dispatch_local(xs.iter().filter_map(|x| x.key.clone()).collect());
+        }
+    });
+    Effect::new(move || {
+        if let Some(Err(e)) = finalize_action.value().get() {
+            error.set(Some(e.to_string()));
+            uploads.set(Vec::new());
+        }
+    });
+
+    let images = move || {
+        finalize_action
+            .value()
+            .get()
+            .transpose()
+            .ok()
+            .flatten()
+            .filter(|x| !x.is_empty())
+    };
+    let uploading = move || uploads.with(|xs| !xs.is_empty());
 
     view! {
     <div class="flex flex-wrap gap-4 justify-center mt-10">
         <ShowLet
-            some=image
-            let(image_url)
+            some=images
+            let(images)
             fallback=move || view!{
+                <Show
+                    when=move || !uploading()
+                    fallback=move || view! {
+                        <div class="w-full space-y-3">
+                            {move || {
+                                uploads
+                                    .get()
+                                    .into_iter()
+                                    .map(|upload| view! {
+                                        <div>
+                                            <div class="flex justify-between text-sm text-gray-600 mb-1">
+                                                <span class="truncate">{upload.name.clone()}</span>
+                                                <span>{format!("{}%", upload.percent())}</span>
+                                            </div>
+                                            <div class="w-full h-2 bg-gray-200 rounded-full overflow-hidden">
+                                                <div
+                                                    class="h-full bg-gradient-to-r from-blue-500 to-violet-500 transition-all duration-300"
+                                                    style=format!("width: {}%", upload.percent())
+                                                ></div>
+                                            </div>
+                                        </div>
+                                    })
+                                    .collect_view()
+                            }}
+                            <Show when=move || finalize_action.pending().get()>
+                                <p class="text-center text-sm text-gray-600">"جاري معالجة الصور..."</p>
+                            </Show>
+                        </div>
+                    }
+                >
                 <form
                     id="INNER_FORM"
                     on:submit=on_submit
@@ -265,17 +597,20 @@ fn UploadImage() -> impl IntoView {
                         <svg class="w-5 h-5 text-pink-600" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M4 16l4.586-4.586a2 2 0 012.828 0L16 16m-2-2l1.586-1.586a2 2 0 012.828 0L20 14m-6-6h.01M6 20h12a2 2 0 002-2V6a2 2 0 00-2-2H6a2 2 0 00-2 2v12a2 2 0 002 2z"></path>
                         </svg>
-                        "الصورة"
+                        "الصور"
                     </label>
                     <input
-                        node_ref={file_input}
                         class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-pink-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800 placeholder-gray-400"
                         type="file"
-                        accept=".png, .jpg, .jpeg, .webp"
+                        accept=".png, .jpg, .jpeg, .webp, .heic, .heif"
                         name="data"
                         id="data"
+                        multiple
                         required
                     />
+                    {move || error.get().map(|e| view! {
+                        <p class="col-span-2 text-sm text-red-600">{e}</p>
+                    })}
                     <button
                         class="group px-8 py-4 bg-gradient-to-r from-blue-500 to-violet-500 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl hover:scale-105 active:scale-95 transition-all duration-300 flex items-center gap-3"
                         type="submit"
@@ -284,22 +619,33 @@ fn UploadImage() -> impl IntoView {
                         <svg class="w-6 h-6 group-hover:rotate-12 transition-transform duration-300" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M5 13l4 4L19 7"></path>
                         </svg>
-                        "تأكيد الصورة"
+                        "تأكيد الصور"
                     </button>
                     <CancelButton/>
                 </form>
+                </Show>
             }
         >
-            <input
-                class="hidden"
-                value={image_url.clone()}
-                name="image_url"
-                type="text"
-            />
-            <img
-                src={image_url}
-                alt="estate image"
-            />
+            <p class="w-full text-center text-sm text-gray-600">"الصورة الأولى هي صورة الغلاف"</p>
+            {images
+                .into_iter()
+                .enumerate()
+                .map(|(i, (id, urls))| {
+                    view! {
+                        <input
+                            class="hidden"
+                            value=id.to_string()
+                            name=format!("images[{i}]")
+                            type="text"
+                        />
+                        <img
+                            class="h-32 w-44 object-cover rounded-lg"
+                            src={urls.smallest().to_string()}
+                            alt="estate image"
+                        />
+                    }
+                })
+                .collect_view()}
             <button
                 class="group px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl hover:scale-105 active:scale-95 transition-all duration-300 flex items-center gap-3"
                 type="submit"
@@ -0,0 +1,129 @@
+use leptos::prelude::*;
+
+use crate::models::{EstateAttributes, Finishing, PropertyType};
+
+fn number(value: Option<i32>) -> String {
+    value.map(|x| x.to_string()).unwrap_or_default()
+}
+
+/// Inputs for every typed attribute, named `attributes[<field>]` so a form
+/// hands them to a server function as one [`EstateAttributes`].
+#[component]
+pub fn AttributeFields(
+    #[prop(optional)] attributes: EstateAttributes,
+    input_class: &'static str,
+    label_class: &'static str,
+) -> impl IntoView {
+    let EstateAttributes {
+        property_type,
+        bedrooms,
+        bathrooms,
+        floor,
+        finishing,
+        furnished,
+    } = attributes;
+
+    view! {
+        <div class="grid grid-cols-1 md:grid-cols-3 gap-6">
+            <div>
+                <label class=label_class for="property_type">"نوع العقار"</label>
+                <select class=input_class name="attributes[property_type]" id="property_type">
+                    {PropertyType::ALL
+                        .into_iter()
+                        .map(|x| view! {
+                            <option value={x.as_str()} selected={x == property_type}>{x.label()}</option>
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div>
+                <label class=label_class for="finishing">"التشطيب"</label>
+                <select class=input_class name="attributes[finishing]" id="finishing">
+                    <option value="" selected={finishing.is_none()}>"غير محدد"</option>
+                    {Finishing::ALL
+                        .into_iter()
+                        .map(|x| view! {
+                            <option value={x.as_str()} selected={Some(x) == finishing}>{x.label()}</option>
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div>
+                <label class=label_class for="furnished">"الفرش"</label>
+                <select class=input_class name="attributes[furnished]" id="furnished">
+                    <option value="false" selected={!furnished}>"غير مفروش"</option>
+                    <option value="true" selected={furnished}>"مفروش"</option>
+                </select>
+            </div>
+            <div>
+                <label class=label_class for="bedrooms">"غرف النوم"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    min="0"
+                    name="attributes[bedrooms]"
+                    id="bedrooms"
+                    value={number(bedrooms)}
+                />
+            </div>
+            <div>
+                <label class=label_class for="bathrooms">"الحمامات"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    min="0"
+                    name="attributes[bathrooms]"
+                    id="bathrooms"
+                    value={number(bathrooms)}
+                />
+            </div>
+            <div>
+                <label class=label_class for="floor">"الطابق (0 للأرضي)"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    name="attributes[floor]"
+                    id="floor"
+                    value={number(floor)}
+                />
+            </div>
+        </div>
+    }
+}
+
+/// The attributes of a listing card as small badges.
+#[component]
+pub fn EstateFacts(attributes: EstateAttributes) -> impl IntoView {
+    view! {
+        <div class="flex flex-wrap gap-2">
+            {attributes
+                .facts()
+                .into_iter()
+                .map(|(label, value)| view! {
+                    <span class="px-3 py-1 bg-blue-50 text-blue-700 text-sm rounded-full border border-blue-100">
+                        {label}": "{value}
+                    </span>
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+/// The attributes on the details page, one tile each.
+#[component]
+pub fn EstateSpecs(attributes: EstateAttributes) -> impl IntoView {
+    view! {
+        <div class="grid grid-cols-2 md:grid-cols-3 gap-4 mb-8">
+            {attributes
+                .facts()
+                .into_iter()
+                .map(|(label, value)| view! {
+                    <div class="bg-gray-50 p-4 rounded-xl border border-gray-100 text-center">
+                        <p class="text-sm text-gray-500 mb-1">{label}</p>
+                        <p class="text-lg font-bold text-gray-800">{value}</p>
+                    </div>
+                })
+                .collect_view()}
+        </div>
+    }
+}
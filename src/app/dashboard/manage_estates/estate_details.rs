@@ -2,7 +2,15 @@ use leptos::prelude::*;
 use leptos_router::hooks::use_params_map;
 use uuid::Uuid;
 
+use super::estate_attributes::EstateSpecs;
+use super::estate_quote::QuoteCalculator;
+use super::estate_status::StatusBadge;
+use super::gallery::{Gallery, get_estate_images};
+use super::payment_terms::PaymentPlanDetails;
+use super::videos::{VideoPlayer, get_estate_videos};
 use crate::app::Estate;
+use crate::financing::QuoteRequest;
+use crate::models::{EstateStatus, ListingPurpose};
 
 #[server]
 async fn get_estate_by_id(id: uuid::Uuid) -> Result<Estate, ServerFnError> {
@@ -17,6 +25,7 @@ async fn get_estate_by_id(id: uuid::Uuid) -> Result<Estate, ServerFnError> {
             "could not find estate with id".to_string(),
         ));
     };
+    crate::auth::require_estate_visible(&estate).await?;
     Ok(estate)
 }
 
@@ -25,17 +34,36 @@ pub fn EstateDetails() -> impl IntoView {
     let params = use_params_map();
     let target_id = move || params.with(|p| p.get("targetId"));
 
-    let estate_res = Resource::new(
-        move || {
-            target_id()
-                .as_ref()
-                .and_then(|x| Uuid::parse_str(x).ok())
-                .unwrap_or(Uuid::nil())
-        },
-        get_estate_by_id,
-    );
+    let estate_id = move || {
+        target_id()
+            .as_ref()
+            .and_then(|x| Uuid::parse_str(x).ok())
+            .unwrap_or(Uuid::nil())
+    };
+    let estate_res = Resource::new(estate_id, get_estate_by_id);
+    let images_res = Resource::new(estate_id, get_estate_images);
+    let videos_res = Resource::new(estate_id, get_estate_videos);
 
     let estate = move || estate_res.get().and_then(|x| x.ok());
+    let images = move || {
+        images_res
+            .get()
+            .and_then(|x| x.ok())
+            .filter(|x| x.len() > 1)
+    };
+    let videos = move || {
+        videos_res
+            .get()
+            .and_then(|x| x.ok())
+            .filter(|x| !x.is_empty())
+    };
+    let cover_srcset = move || {
+        images_res
+            .get()
+            .and_then(|x| x.ok())
+            .and_then(|xs| xs.into_iter().find(|x| x.is_cover))
+            .and_then(|x| x.urls.srcset())
+    };
 
     view! {
         <Suspense fallback=|| view! {
@@ -54,13 +82,21 @@ pub fn EstateDetails() -> impl IntoView {
                 </div>
             }>
                 {move || estate().map(|estate| {
-                    let Estate {  name, address, image_url,description, price_in_cents, space_in_meters, .. } = estate;
+                    let attributes = estate.attributes();
+                    let terms = estate.terms();
+                    let plan = terms.plan();
+                    let purpose = terms.purpose;
+                    let estate_id = estate.id;
+                    let quote_request = QuoteRequest::for_estate(estate.price_in_cents, plan);
+                    let Estate {  name, address, image_url,description, price_in_cents, space_in_meters, status, .. } = estate;
                     view! {
                         <div class="max-w-5xl mx-auto">
                             <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl overflow-hidden border border-gray-100">
                                 <div class="relative h-96 overflow-hidden">
                                     <img
                                         class="w-full h-full object-cover transform hover:scale-110 transition-transform duration-700"
+                                        srcset=cover_srcset
+                                        sizes="(min-width: 1024px) 1024px, 100vw"
                                         src={image_url.clone()}
                                         alt={name.clone()}
                                     />
@@ -78,6 +114,13 @@ pub fn EstateDetails() -> impl IntoView {
                                 </div>
 
                                 <div class="p-8">
+                                    <p class="mb-4 flex justify-center gap-3">
+                                        <span class="px-4 py-1.5 bg-blue-100 text-blue-700 font-semibold rounded-full">{purpose.label()}</span>
+                                        <Show when=move || status != EstateStatus::Published>
+                                            <StatusBadge status/>
+                                        </Show>
+                                    </p>
+                                    <EstateSpecs attributes/>
                                     <div class="grid grid-cols-1 md:grid-cols-2 gap-6 mb-8">
                                         <div class="bg-gradient-to-br from-blue-50 to-cyan-50 p-6 rounded-2xl shadow-md hover:shadow-lg transition-shadow duration-300 border border-blue-100">
                                             <div class="flex items-center gap-3 mb-3">
@@ -100,10 +143,10 @@ pub fn EstateDetails() -> impl IntoView {
                                                         <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 8c-1.657 0-3 .895-3 2s1.343 2 3 2 3 .895 3 2-1.343 2-3 2m0-8c1.11 0 2.08.402 2.599 1M12 8V7m0 1v8m0 0v1m0-1c-1.11 0-2.08-.402-2.599-1M21 12a9 9 0 11-18 0 9 9 0 0118 0z"></path>
                                                     </svg>
                                                 </div>
-                                                <h3 class="text-xl font-bold text-gray-800">"السعر"</h3>
+                                                <h3 class="text-xl font-bold text-gray-800">{purpose.price_label()}</h3>
                                             </div>
                                             <p class="text-3xl font-bold bg-gradient-to-r from-green-600 to-emerald-600 bg-clip-text text-transparent">
-                                                {format!("{:.2}", price_in_cents as f32 / 100.0)}" جنيه"
+                                                {format!("{:.2}", price_in_cents as f32 / 100.0)}" جنيه"{purpose.price_suffix()}
                                             </p>
                                         </div>
 
@@ -113,6 +156,28 @@ pub fn EstateDetails() -> impl IntoView {
                                             >{description}</p>
                                         </div>
                                     </div>
+
+                                    <ShowLet some=move || plan let(plan)>
+                                        <PaymentPlanDetails plan/>
+                                    </ShowLet>
+                                    <Show when=move || purpose == ListingPurpose::Sale && status != EstateStatus::Sold>
+                                        <QuoteCalculator estate_id request=quote_request/>
+                                    </Show>
+
+                                    <ShowLet some=images let(images)>
+                                        <h2 class="text-2xl font-bold text-gray-800 mb-4">"معرض الصور"</h2>
+                                        <Gallery images/>
+                                    </ShowLet>
+
+                                    <ShowLet some=videos let(videos)>
+                                        <h2 class="text-2xl font-bold text-gray-800 mb-4">"جولات الفيديو"</h2>
+                                        <div class="grid grid-cols-1 gap-6">
+                                            {videos
+                                                .into_iter()
+                                                .map(|video| view! { <VideoPlayer video/> })
+                                                .collect_view()}
+                                        </div>
+                                    </ShowLet>
                                 </div>
                             </div>
                         </div>
@@ -0,0 +1,402 @@
+use leptos::prelude::*;
+use leptos_router::{components::Form, params::ParamsMap};
+
+use crate::models::{
+    EstateFilter, EstateSort, EstateStatus, Finishing, ListingPurpose, PropertyType,
+    highlight_segments,
+};
+
+/// Estates shown per listing page.
+#[cfg(feature = "ssr")]
+pub const ESTATES_PAGE_SIZE: i64 = 20;
+
+/// Reads the listing filter from the query string. Prices travel in pounds
+/// there, as typed by visitors, and are stored in cents; a price too large
+/// to hold in cents drops its bound.
+pub fn filter_from_query(q: &ParamsMap) -> EstateFilter {
+    let get = |key: &str| q.get(key).filter(|x| !x.is_empty());
+    let cents = |key: &str| {
+        get(key)
+            .and_then(|x| x.parse::<i64>().ok())
+            .and_then(|x| x.checked_mul(100))
+    };
+    EstateFilter {
+        min_price: cents("min_price"),
+        max_price: cents("max_price"),
+        min_space: get("min_space").and_then(|x| x.parse().ok()),
+        max_space: get("max_space").and_then(|x| x.parse().ok()),
+        property_type: get("property_type").and_then(|x| PropertyType::parse(&x)),
+        min_bedrooms: get("min_bedrooms").and_then(|x| x.parse().ok()),
+        min_bathrooms: get("min_bathrooms").and_then(|x| x.parse().ok()),
+        min_floor: get("min_floor").and_then(|x| x.parse().ok()),
+        max_floor: get("max_floor").and_then(|x| x.parse().ok()),
+        finishing: get("finishing").and_then(|x| Finishing::parse(&x)),
+        furnished: get("furnished").and_then(|x| x.parse().ok()),
+        purpose: get("purpose").and_then(|x| ListingPurpose::parse(&x)),
+        installments: get("installments").and_then(|x| x.parse().ok()),
+        max_down_payment: cents("max_down_payment"),
+        status: get("status").and_then(|x| EstateStatus::parse(&x)),
+        sort: get("sort")
+            .and_then(|x| EstateSort::parse(&x))
+            .unwrap_or_else(|| EstateSort::default_for(get("text").as_deref())),
+        text: get("text"),
+        managed_by: None,
+        listed_only: false,
+    }
+}
+
+fn pounds(cents: Option<i64>) -> String {
+    cents.map(|x| (x / 100).to_string()).unwrap_or_default()
+}
+
+fn number(value: Option<i32>) -> String {
+    value.map(|x| x.to_string()).unwrap_or_default()
+}
+
+#[component]
+pub fn EstateFilters(
+    filter: Signal<EstateFilter>,
+    /// Keeps the dashboard's "my estates" tab across searches.
+    #[prop(optional, into)]
+    mine: MaybeProp<bool>,
+    /// Offers the status filter; public listings only show published and
+    /// reserved estates anyway.
+    #[prop(optional)]
+    with_status: bool,
+) -> impl IntoView {
+    let input_class = "w-full px-4 py-2 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-800";
+
+    view! {
+        <Form method="GET" action="">
+            <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 mb-8 grid grid-cols-1 md:grid-cols-4 lg:grid-cols-7 gap-4 items-end">
+                <Show when=move || mine.get().unwrap_or_default()>
+                    <input class="hidden" type="text" name="mine" value="true"/>
+                </Show>
+                <div class="md:col-span-2">
+                    <label class="block text-gray-700 font-semibold mb-2" for="text">"بحث"</label>
+                    <input
+                        class=input_class
+                        type="text"
+                        name="text"
+                        id="text"
+                        placeholder="الاسم أو العنوان أو الوصف"
+                        prop:value=move || filter.get().text.unwrap_or_default()
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="min_price">"السعر من (ج)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="min_price"
+                        id="min_price"
+                        prop:value=move || pounds(filter.get().min_price)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="max_price">"إلى (ج)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="max_price"
+                        id="max_price"
+                        prop:value=move || pounds(filter.get().max_price)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="min_space">"المساحة من (م²)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="min_space"
+                        id="min_space"
+                        prop:value=move || number(filter.get().min_space)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="max_space">"إلى (م²)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="max_space"
+                        id="max_space"
+                        prop:value=move || number(filter.get().max_space)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="sort">"الترتيب"</label>
+                    <select class=input_class name="sort" id="sort">
+                        {EstateSort::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || filter.get().sort == x
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="property_type">"نوع العقار"</label>
+                    <select class=input_class name="property_type" id="property_type">
+                        <option value="" selected=move || filter.get().property_type.is_none()>"الكل"</option>
+                        {PropertyType::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || filter.get().property_type == Some(x)
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="min_bedrooms">"غرف النوم من"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="min_bedrooms"
+                        id="min_bedrooms"
+                        prop:value=move || number(filter.get().min_bedrooms)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="min_bathrooms">"الحمامات من"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="min_bathrooms"
+                        id="min_bathrooms"
+                        prop:value=move || number(filter.get().min_bathrooms)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="min_floor">"الطابق من"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        name="min_floor"
+                        id="min_floor"
+                        prop:value=move || number(filter.get().min_floor)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="max_floor">"إلى"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        name="max_floor"
+                        id="max_floor"
+                        prop:value=move || number(filter.get().max_floor)
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="finishing">"التشطيب"</label>
+                    <select class=input_class name="finishing" id="finishing">
+                        <option value="" selected=move || filter.get().finishing.is_none()>"الكل"</option>
+                        {Finishing::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || filter.get().finishing == Some(x)
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="furnished">"الفرش"</label>
+                    <select class=input_class name="furnished" id="furnished">
+                        <option value="" selected=move || filter.get().furnished.is_none()>"الكل"</option>
+                        <option value="true" selected=move || filter.get().furnished == Some(true)>"مفروش"</option>
+                        <option value="false" selected=move || filter.get().furnished == Some(false)>"غير مفروش"</option>
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="purpose">"الغرض"</label>
+                    <select class=input_class name="purpose" id="purpose">
+                        <option value="" selected=move || filter.get().purpose.is_none()>"الكل"</option>
+                        {ListingPurpose::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || filter.get().purpose == Some(x)
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="installments">"السداد"</label>
+                    <select class=input_class name="installments" id="installments">
+                        <option value="" selected=move || filter.get().installments.is_none()>"الكل"</option>
+                        <option value="true" selected=move || filter.get().installments == Some(true)>"بالتقسيط"</option>
+                        <option value="false" selected=move || filter.get().installments == Some(false)>"كاش فقط"</option>
+                    </select>
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="max_down_payment">"المقدم حتى (ج)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        name="max_down_payment"
+                        id="max_down_payment"
+                        prop:value=move || pounds(filter.get().max_down_payment)
+                    />
+                </div>
+                <Show when=move || with_status>
+                    <div>
+                        <label class="block text-gray-700 font-semibold mb-2" for="status">"الحالة"</label>
+                        <select class=input_class name="status" id="status">
+                            <option value="" selected=move || filter.get().status.is_none()>"الكل"</option>
+                            {EstateStatus::ALL
+                                .into_iter()
+                                .map(|x| view! {
+                                    <option
+                                        value={x.as_str()}
+                                        selected=move || filter.get().status == Some(x)
+                                    >{x.label()}</option>
+                                })
+                                .collect_view()}
+                        </select>
+                    </div>
+                </Show>
+                <input
+                    class="px-4 py-2.5 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-semibold rounded-lg shadow-md hover:shadow-lg hover:scale-105 transition-all duration-300 text-center md:col-span-4 lg:col-span-7"
+                    type="submit"
+                    value="تصفية"
+                />
+            </div>
+        </Form>
+    }
+}
+
+/// The current filter as hidden fields, so paging keeps it in the URL.
+#[component]
+fn FilterFields(filter: EstateFilter, mine: bool) -> impl IntoView {
+    let fields = [
+        (
+            "mine",
+            if mine {
+                "true".to_string()
+            } else {
+                String::new()
+            },
+        ),
+        ("text", filter.text.unwrap_or_default()),
+        ("min_price", pounds(filter.min_price)),
+        ("max_price", pounds(filter.max_price)),
+        ("min_space", number(filter.min_space)),
+        ("max_space", number(filter.max_space)),
+        (
+            "property_type",
+            filter
+                .property_type
+                .map(|x| x.as_str().to_string())
+                .unwrap_or_default(),
+        ),
+        ("min_bedrooms", number(filter.min_bedrooms)),
+        ("min_bathrooms", number(filter.min_bathrooms)),
+        ("min_floor", number(filter.min_floor)),
+        ("max_floor", number(filter.max_floor)),
+        (
+            "finishing",
+            filter
+                .finishing
+                .map(|x| x.as_str().to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "furnished",
+            filter.furnished.map(|x| x.to_string()).unwrap_or_default(),
+        ),
+        (
+            "purpose",
+            filter
+                .purpose
+                .map(|x| x.as_str().to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "installments",
+            filter
+                .installments
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+        ),
+        ("max_down_payment", pounds(filter.max_down_payment)),
+        (
+            "status",
+            filter
+                .status
+                .map(|x| x.as_str().to_string())
+                .unwrap_or_default(),
+        ),
+        ("sort", filter.sort.as_str().to_string()),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| view! { <input class="hidden" type="text" name=name value=value/> })
+        .collect_view()
+}
+
+#[component]
+pub fn Pagination(
+    filter: Signal<EstateFilter>,
+    /// Whether the current page was reached through a cursor.
+    paged: Signal<bool>,
+    next: Signal<Option<String>>,
+    #[prop(optional, into)] mine: MaybeProp<bool>,
+) -> impl IntoView {
+    let button_class = "px-6 py-2.5 bg-white text-gray-700 font-semibold rounded-lg shadow-md border-2 border-gray-200 hover:border-blue-300";
+    let mine = move || mine.get().unwrap_or_default();
+
+    view! {
+        <div class="flex justify-center gap-4 mb-8">
+            <Show when=move || paged.get()>
+                <Form method="GET" action="">
+                    <FilterFields filter=filter.get() mine=mine()/>
+                    <input class=button_class type="submit" value="الصفحة الأولى"/>
+                </Form>
+            </Show>
+            <ShowLet some=move || next.get() let(after)>
+                <Form method="GET" action="">
+                    <FilterFields filter=filter.get() mine=mine()/>
+                    <input class="hidden" type="text" name="after" value=after/>
+                    <input class=button_class type="submit" value="الصفحة التالية"/>
+                </Form>
+            </ShowLet>
+        </div>
+    }
+}
+
+/// A search snippet with its matches highlighted.
+#[component]
+pub fn Snippet(snippet: String) -> impl IntoView {
+    view! {
+        <p class="text-sm text-gray-600 mb-4 leading-relaxed">
+            {highlight_segments(&snippet)
+                .into_iter()
+                .map(|(text, highlighted)| {
+                    if highlighted {
+                        view! { <mark class="bg-yellow-200 rounded px-0.5">{text}</mark> }.into_any()
+                    } else {
+                        text.into_any()
+                    }
+                })
+                .collect_view()}
+        </p>
+    }
+}
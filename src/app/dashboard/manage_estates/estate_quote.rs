@@ -0,0 +1,301 @@
+use leptos::prelude::*;
+use leptos_router::hooks::{use_params_map, use_query_map};
+use uuid::Uuid;
+
+use crate::app::Estate;
+use crate::financing::{Installment, MAX_YEARS, Quote, QuoteRequest, quote};
+use crate::models::InstallmentFrequency;
+
+fn pounds(cents: i64) -> String {
+    format!("{:.2}", cents as f64 / 100.0)
+}
+
+/// Prices a quote for the estate's stored price, so a printed quote cannot
+/// be made for another amount than the one listed.
+#[server]
+async fn get_estate_quote(
+    estate_id: Uuid,
+    down_payment_percent: f64,
+    years: i32,
+    annual_rate_percent: f64,
+    frequency: InstallmentFrequency,
+) -> Result<(Estate, Quote), ServerFnError> {
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::auth::require_estate_visible(&estate).await?;
+    let quote = quote(QuoteRequest {
+        price_in_cents: estate.price_in_cents,
+        down_payment_percent,
+        years,
+        annual_rate_percent,
+        frequency,
+    })
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok((estate, quote))
+}
+
+/// Down payment, installment and totals of a quote.
+#[component]
+fn QuoteSummary(quote: Quote) -> impl IntoView {
+    let rows = [
+        ("المقدم", pounds(quote.down_payment_in_cents)),
+        ("المبلغ الممول", pounds(quote.financed_in_cents)),
+        (
+            match quote.request.frequency {
+                InstallmentFrequency::Monthly => "القسط الشهري",
+                InstallmentFrequency::Quarterly => "القسط الربع سنوي",
+            },
+            pounds(quote.installment_in_cents),
+        ),
+        ("إجمالي الفوائد", pounds(quote.total_interest_in_cents)),
+        ("إجمالي المدفوع", pounds(quote.total_paid_in_cents)),
+    ];
+
+    view! {
+        <dl class="grid grid-cols-2 md:grid-cols-5 gap-4 text-center mb-6">
+            {rows
+                .into_iter()
+                .map(|(label, value)| view! {
+                    <div class="bg-white p-4 rounded-xl border border-gray-100 shadow-sm">
+                        <dt class="text-sm text-gray-500 mb-1">{label}</dt>
+                        <dd class="text-lg font-bold text-gray-800">{value}" ج"</dd>
+                    </div>
+                })
+                .collect_view()}
+        </dl>
+    }
+}
+
+/// The amortization table of a quote.
+#[component]
+fn ScheduleTable(schedule: Vec<Installment>) -> impl IntoView {
+    view! {
+        <table class="w-full text-sm text-center">
+            <thead class="bg-gray-100 text-gray-700">
+                <tr>
+                    <th class="px-3 py-2">"#"</th>
+                    <th class="px-3 py-2">"القسط"</th>
+                    <th class="px-3 py-2">"أصل الدين"</th>
+                    <th class="px-3 py-2">"الفائدة"</th>
+                    <th class="px-3 py-2">"المتبقي"</th>
+                </tr>
+            </thead>
+            <tbody>
+                {schedule
+                    .into_iter()
+                    .map(|x| view! {
+                        <tr class="border-b border-gray-100">
+                            <td class="px-3 py-1">{x.number}</td>
+                            <td class="px-3 py-1">{pounds(x.payment_in_cents)}</td>
+                            <td class="px-3 py-1">{pounds(x.principal_in_cents)}</td>
+                            <td class="px-3 py-1">{pounds(x.interest_in_cents)}</td>
+                            <td class="px-3 py-1">{pounds(x.balance_in_cents)}</td>
+                        </tr>
+                    })
+                    .collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+#[island]
+pub fn QuoteCalculator(estate_id: Uuid, request: QuoteRequest) -> impl IntoView {
+    let down_payment_percent = RwSignal::new(request.down_payment_percent);
+    let years = RwSignal::new(request.years);
+    let annual_rate_percent = RwSignal::new(request.annual_rate_percent);
+    let frequency = RwSignal::new(request.frequency);
+
+    let result = Memo::new(move |_| {
+        quote(QuoteRequest {
+            price_in_cents: request.price_in_cents,
+            down_payment_percent: down_payment_percent.get(),
+            years: years.get(),
+            annual_rate_percent: annual_rate_percent.get(),
+            frequency: frequency.get(),
+        })
+        .map_err(|e| e.to_string())
+    });
+    let print_url = move || {
+        format!(
+            "/dashboard/estateQuote/{estate_id}?down_payment_percent={}&years={}&rate={}&frequency={}",
+            down_payment_percent.get(),
+            years.get(),
+            annual_rate_percent.get(),
+            frequency.get().as_str()
+        )
+    };
+    let input_class = "w-full px-4 py-2 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent text-gray-800";
+
+    view! {
+        <div class="mb-8 bg-gradient-to-br from-blue-50 to-cyan-50 p-6 rounded-2xl shadow-md border border-blue-100">
+            <h2 class="text-2xl font-bold text-gray-800 mb-4">"حاسبة التقسيط والتمويل"</h2>
+            <div class="grid grid-cols-1 md:grid-cols-4 gap-4 mb-6">
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="calc_down_payment">
+                        "المقدم: "{move || format!("{:.0}%", down_payment_percent.get())}
+                    </label>
+                    <input
+                        class="w-full"
+                        type="range"
+                        min="0"
+                        max="100"
+                        step="1"
+                        id="calc_down_payment"
+                        prop:value=move || down_payment_percent.get().to_string()
+                        on:input=move |ev| {
+                            down_payment_percent.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                        }
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="calc_years">"المدة (سنوات)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="1"
+                        max=MAX_YEARS.to_string()
+                        id="calc_years"
+                        prop:value=move || years.get().to_string()
+                        on:input=move |ev| years.set(event_target_value(&ev).parse().unwrap_or(0))
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="calc_rate">"الفائدة السنوية (%)"</label>
+                    <input
+                        class=input_class
+                        type="number"
+                        min="0"
+                        max="100"
+                        step="0.25"
+                        id="calc_rate"
+                        prop:value=move || annual_rate_percent.get().to_string()
+                        on:input=move |ev| {
+                            annual_rate_percent.set(event_target_value(&ev).parse().unwrap_or(0.0))
+                        }
+                    />
+                </div>
+                <div>
+                    <label class="block text-gray-700 font-semibold mb-2" for="calc_frequency">"نظام الأقساط"</label>
+                    <select
+                        class=input_class
+                        id="calc_frequency"
+                        on:change=move |ev| {
+                            if let Some(x) = InstallmentFrequency::parse(&event_target_value(&ev)) {
+                                frequency.set(x);
+                            }
+                        }
+                    >
+                        {InstallmentFrequency::ALL
+                            .into_iter()
+                            .map(|x| view! {
+                                <option
+                                    value={x.as_str()}
+                                    selected=move || frequency.get() == x
+                                >{x.label()}</option>
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+            </div>
+            {move || match result.get() {
+                Ok(quote) => {
+                    let schedule = quote.schedule.clone();
+                    view! {
+                        <QuoteSummary quote/>
+                        <div class="max-h-96 overflow-y-auto bg-white rounded-xl border border-gray-100 mb-4">
+                            <ScheduleTable schedule/>
+                        </div>
+                    }
+                        .into_any()
+                }
+                Err(e) => view! { <p class="text-red-700 font-semibold mb-4">{e}</p> }.into_any(),
+            }}
+            <a
+                href=print_url
+                target="_blank"
+                class="inline-block px-6 py-2.5 bg-white text-gray-700 font-semibold rounded-lg shadow-md border-2 border-gray-200 hover:border-blue-300"
+            >"عرض سعر للطباعة"</a>
+        </div>
+    }
+}
+
+/// A printable quote for the terms in the query string.
+#[component]
+pub fn EstateQuote() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let estate_id = move || {
+        params
+            .with(|p| p.get("targetId"))
+            .and_then(|x| Uuid::parse_str(&x).ok())
+            .unwrap_or(Uuid::nil())
+    };
+    let terms = move || {
+        query.with(|q| {
+            let get = |key: &str| q.get(key).filter(|x| !x.is_empty());
+            (
+                get("down_payment_percent")
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(20.0),
+                get("years").and_then(|x| x.parse().ok()).unwrap_or(10),
+                get("rate").and_then(|x| x.parse().ok()).unwrap_or(0.0),
+                get("frequency")
+                    .and_then(|x| InstallmentFrequency::parse(&x))
+                    .unwrap_or(InstallmentFrequency::Monthly),
+            )
+        })
+    };
+    let quote_res = Resource::new(
+        move || (estate_id(), terms()),
+        |(id, (down_payment_percent, years, rate, frequency))| {
+            get_estate_quote(id, down_payment_percent, years, rate, frequency)
+        },
+    );
+
+    view! {
+        <div class="max-w-5xl mx-auto py-12 px-4">
+            <Suspense fallback=crate::LoadingSpinner>
+                {move || quote_res.get().map(|res| match res {
+                    Ok((estate, quote)) => {
+                        let request = quote.request;
+                        let schedule = quote.schedule.clone();
+                        view! {
+                            <div class="flex justify-between items-start mb-6">
+                                <div>
+                                    <h1 class="text-3xl font-bold text-gray-800 mb-2">"عرض سعر: "{estate.name}</h1>
+                                    <p class="text-gray-600">{estate.address}</p>
+                                </div>
+                                <button
+                                    class="print:hidden px-6 py-2.5 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-semibold rounded-lg shadow-md"
+                                    onclick="window.print()"
+                                >"طباعة"</button>
+                            </div>
+                            <p class="mb-6 text-gray-700">
+                                {format!(
+                                    "السعر {} ج، مقدم {:.0}%، على {} سنوات بفائدة سنوية {}% وأقساط {}",
+                                    pounds(request.price_in_cents),
+                                    request.down_payment_percent,
+                                    request.years,
+                                    request.annual_rate_percent,
+                                    request.frequency.label(),
+                                )}
+                            </p>
+                            <QuoteSummary quote/>
+                            <ScheduleTable schedule/>
+                            <p class="mt-6 text-sm text-gray-500">
+                                "هذا العرض استرشادي ولا يمثل التزاماً بالتمويل."
+                            </p>
+                        }
+                            .into_any()
+                    }
+                    Err(e) => view! { <p class="text-red-700 font-semibold">{e.to_string()}</p> }
+                        .into_any(),
+                })}
+            </Suspense>
+        </div>
+    }
+}
@@ -0,0 +1,96 @@
+use leptos::prelude::*;
+
+use crate::models::{EstateStatus, ListingSchedule};
+
+/// The status of a listing as a colored badge.
+#[component]
+pub fn StatusBadge(status: EstateStatus) -> impl IntoView {
+    let color = match status {
+        EstateStatus::Draft => "bg-gray-100 text-gray-700",
+        EstateStatus::Published => "bg-green-100 text-green-700",
+        EstateStatus::Reserved => "bg-amber-100 text-amber-700",
+        EstateStatus::Sold => "bg-red-100 text-red-700",
+        EstateStatus::Archived => "bg-slate-200 text-slate-700",
+    };
+
+    view! {
+        <span class=format!("px-3 py-1 text-sm font-semibold rounded-full shadow {color}")>
+            {status.label()}
+        </span>
+    }
+}
+
+/// When the estate last entered each status it has been in.
+#[component]
+pub fn StatusHistory(history: Vec<(EstateStatus, String)>) -> impl IntoView {
+    view! {
+        <ul class="flex flex-wrap justify-center gap-4 text-sm text-gray-600">
+            {history
+                .into_iter()
+                .map(|(status, at)| view! {
+                    <li>
+                        <span class="font-semibold">{status.label()}": "</span>
+                        {at}
+                    </li>
+                })
+                .collect_view()}
+        </ul>
+    }
+}
+
+/// Inputs for the publishing schedule, named `schedule[<field>]` so a form
+/// hands them to a server function as one [`ListingSchedule`].
+#[component]
+pub fn ScheduleFields(
+    #[prop(optional)] schedule: ListingSchedule,
+    input_class: &'static str,
+    label_class: &'static str,
+) -> impl IntoView {
+    let ListingSchedule {
+        publish_at,
+        expire_at,
+    } = schedule;
+
+    view! {
+        <div class="grid grid-cols-1 md:grid-cols-2 gap-6">
+            <div>
+                <label class=label_class for="publish_at">"موعد النشر"</label>
+                <input
+                    class=input_class
+                    type="datetime-local"
+                    name="schedule[publish_at]"
+                    id="publish_at"
+                    value={publish_at.unwrap_or_default()}
+                />
+            </div>
+            <div>
+                <label class=label_class for="expire_at">"موعد انتهاء الإعلان"</label>
+                <input
+                    class=input_class
+                    type="datetime-local"
+                    name="schedule[expire_at]"
+                    id="expire_at"
+                    value={expire_at.unwrap_or_default()}
+                />
+            </div>
+            <p class="md:col-span-2 text-sm text-gray-500">
+                "تُنشر المسودة تلقائياً عند موعد النشر، ويُؤرشف الإعلان المنشور عند موعد انتهائه. اترك الحقل فارغاً لإلغائه."
+            </p>
+        </div>
+    }
+}
+
+/// A pending scheduled publication or expiry, for listing cards.
+#[component]
+pub fn ScheduleNote(
+    status: EstateStatus,
+    publish_at: Option<String>,
+    expire_at: Option<String>,
+) -> impl IntoView {
+    let note = match status {
+        EstateStatus::Draft => publish_at.map(|x| format!("يُنشر في {}", x.replace('T', " "))),
+        EstateStatus::Published => expire_at.map(|x| format!("ينتهي في {}", x.replace('T', " "))),
+        _ => None,
+    };
+    note.map(|note| view! { <p class="text-sm text-amber-700 font-semibold">{note}</p> })
+}
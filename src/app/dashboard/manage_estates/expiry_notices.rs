@@ -0,0 +1,118 @@
+use leptos::prelude::*;
+use uuid::Uuid;
+
+use crate::app::Estate;
+
+/// Published listings the caller manages that expire soon, with how many
+/// days a renewal adds.
+#[server]
+async fn get_expiring_estates() -> Result<(Vec<Estate>, i32), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_permission},
+        listing_schedule::{notice_days, renewal_days},
+    };
+    let user_id = require_permission(Permission::EstatesView).await?;
+    let manage_all = require_permission(Permission::EstatesManageAll)
+        .await
+        .is_ok();
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let estates = crate::db::estates::get_expiring_estates(
+        &app_state.pool,
+        notice_days(),
+        (!manage_all).then_some(user_id),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok((estates, renewal_days()))
+}
+
+#[server]
+async fn renew_estate(target_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        listing_schedule::renewal_days,
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let renewed = crate::db::estates::renew_estate(&app_state.pool, target_id, renewal_days())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if !renewed {
+        return Err(ServerFnError::new(
+            "only published listings with an expiry can be renewed",
+        ));
+    }
+    let after = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "expire_at",
+        before.expire_at,
+        after.expire_at,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect("/dashboard/manageEstates");
+    Ok(())
+}
+
+/// Reminders for listings about to expire, each renewable with one click.
+#[component]
+pub fn ExpiryNotices() -> impl IntoView {
+    let renew = ServerAction::<RenewEstate>::new();
+    let expiring_res = Resource::new(move || renew.version().get(), |_| get_expiring_estates());
+    let expiring = move || {
+        expiring_res
+            .get()
+            .and_then(|x| x.ok())
+            .filter(|(estates, _)| !estates.is_empty())
+    };
+
+    view! {
+        <Suspense>
+            <ShowLet some=expiring let((estates, renewal_days))>
+                <div class="mb-8 bg-amber-50 border-2 border-amber-200 rounded-xl p-6">
+                    <h2 class="text-xl font-bold text-amber-800 mb-4">"إعلانات تنتهي قريباً"</h2>
+                    <ul class="space-y-3">
+                        {estates
+                            .into_iter()
+                            .map(|Estate { id, name, expire_at, .. }| view! {
+                                <li class="flex flex-wrap items-center justify-between gap-3">
+                                    <a class="font-semibold text-gray-800 hover:text-blue-600" href={format!("/dashboard/estateDetails/{id}")}>
+                                        {name}
+                                    </a>
+                                    <span class="text-sm text-gray-600">
+                                        "ينتهي في "{expire_at.unwrap_or_default().replace('T', " ")}
+                                    </span>
+                                    <ActionForm action={renew}>
+                                        <input class="hidden" type="text" name="target_id" value={id.to_string()}/>
+                                        <input
+                                            class="px-4 py-2 bg-gradient-to-r from-amber-500 to-orange-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg cursor-pointer"
+                                            type="submit"
+                                            value={format!("تجديد {renewal_days} يوماً")}
+                                        />
+                                    </ActionForm>
+                                </li>
+                            })
+                            .collect_view()}
+                    </ul>
+                </div>
+            </ShowLet>
+        </Suspense>
+    }
+}
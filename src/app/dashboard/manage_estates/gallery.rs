@@ -0,0 +1,561 @@
+use leptos::prelude::*;
+use uuid::Uuid;
+use web_sys::{FormData, HtmlFormElement, wasm_bindgen::JsCast};
+
+use crate::models::EstateImage;
+
+/// Collects every `data` file of an upload form, and its `target_id` when
+/// the form has one. Files over the size limit are refused while streaming.
+#[cfg(feature = "ssr")]
+pub async fn read_upload(
+    data: server_fn::codec::MultipartData,
+) -> Result<(Option<Uuid>, Vec<Vec<u8>>), ServerFnError> {
+    use crate::images::{ImageError, max_image_bytes};
+
+    let mut data = data
+        .into_inner()
+        .ok_or_else(|| ServerFnError::new("no data was recieved for the image"))?;
+
+    let limit = max_image_bytes();
+    let mut target_id = None;
+    let mut files = Vec::new();
+    while let Ok(Some(mut field)) = data.next_field().await {
+        match field.name().unwrap_or_default() {
+            "data" => {
+                let mut bytes = Vec::new();
+                while let Ok(Some(chunk)) = field.chunk().await {
+                    if bytes.len() + chunk.len() > limit {
+                        return Err(ServerFnError::new(ImageError::TooLarge(limit).to_string()));
+                    }
+                    bytes.extend(chunk.to_vec());
+                }
+                if !bytes.is_empty() {
+                    files.push(bytes);
+                }
+            }
+            "target_id" => {
+                if let Ok(ti) = field.text().await {
+                    target_id = Some(Uuid::parse_str(&ti)?);
+                }
+            }
+            _ => (),
+        };
+    }
+    if files.is_empty() {
+        return Err(ServerFnError::new("no data was recieved for the image"));
+    }
+    Ok((target_id, files))
+}
+
+/// Runs every upload through [`crate::images`] and stores the results.
+#[cfg(feature = "ssr")]
+pub async fn store_uploads(
+    storage: &dyn crate::storage::ImageStorage,
+    files: Vec<Vec<u8>>,
+) -> Result<Vec<crate::models::ImageKeys>, ServerFnError> {
+    let mut images = Vec::with_capacity(files.len());
+    for file in files {
+        let image = crate::images::store_processed(storage, file)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        images.push(image);
+    }
+    Ok(images)
+}
+
+/// Removes the stored objects behind `images`, variants included, skipping
+/// images hosted elsewhere.
+#[cfg(feature = "ssr")]
+pub async fn delete_image_objects(
+    storage: &dyn crate::storage::ImageStorage,
+    images: &[EstateImage],
+) -> Result<(), ServerFnError> {
+    for key in images.iter().flat_map(|x| x.keys.stored()) {
+        storage
+            .delete(key)
+            .await
+            .map_err(|e| ServerFnError::new(format!("image delete failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// The gallery of an estate the caller may see.
+#[server]
+pub async fn get_estate_images(estate_id: Uuid) -> Result<Vec<EstateImage>, ServerFnError> {
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::auth::require_estate_visible(&estate).await?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(input = server_fn::codec::MultipartFormData)]
+async fn upload_estate_images(
+    data: server_fn::codec::MultipartData,
+) -> Result<Vec<EstateImage>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission, require_permission},
+        models::AuditEntity,
+    };
+    require_permission(Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let (target_id, files) = read_upload(data).await?;
+    let target_id =
+        target_id.ok_or_else(|| ServerFnError::new("no id was recieved for the image"))?;
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
+    let images = store_uploads(app_state.storage.as_ref(), files).await?;
+    crate::db::estate_images::add_estate_images(&app_state.pool, target_id, &images)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.add_images",
+        AuditEntity::Estate,
+        Some(target_id),
+        None,
+        Some(serde_json::json!({ "images": images })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn delete_estate_image(estate_id: Uuid, id: Uuid) -> Result<Vec<EstateImage>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let image = crate::db::estate_images::get_estate_image(&app_state.pool, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if image.estate_id != estate_id {
+        return Err(ServerFnError::new(
+            "the image does not belong to this estate",
+        ));
+    }
+    crate::db::estate_images::delete_estate_image(&app_state.pool, estate_id, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.delete_image",
+        AuditEntity::Estate,
+        Some(estate_id),
+        Some(serde_json::json!(image)),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    delete_image_objects(app_state.storage.as_ref(), &[image]).await?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn set_cover_image(estate_id: Uuid, id: Uuid) -> Result<Vec<EstateImage>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::estate_images::set_cover_image(&app_state.pool, estate_id, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.set_cover",
+        AuditEntity::Estate,
+        Some(estate_id),
+        None,
+        Some(serde_json::json!({ "image": id })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn reorder_estate_images(
+    estate_id: Uuid,
+    ids: Vec<Uuid>,
+) -> Result<Vec<EstateImage>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::estate_images::reorder_estate_images(&app_state.pool, estate_id, &ids)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.reorder_images",
+        AuditEntity::Estate,
+        Some(estate_id),
+        None,
+        Some(serde_json::json!({ "order": ids })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn update_image_details(
+    estate_id: Uuid,
+    id: Uuid,
+    caption: String,
+    alt_text: String,
+) -> Result<Vec<EstateImage>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let before = crate::db::estate_images::get_estate_image(&app_state.pool, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::estate_images::update_image_details(
+        &app_state.pool,
+        estate_id,
+        id,
+        caption.trim(),
+        alt_text.trim(),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.update_image_details",
+        AuditEntity::Estate,
+        Some(estate_id),
+        Some(serde_json::json!({ "image": id, "caption": before.caption, "alt_text": before.alt_text })),
+        Some(serde_json::json!({ "image": id, "caption": caption, "alt_text": alt_text })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estate_images::get_estate_images(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Moves the image `dragged` to where `target` is.
+fn move_image(images: &mut Vec<EstateImage>, dragged: Uuid, target: Uuid) {
+    let from = images.iter().position(|x| x.id == dragged);
+    let to = images.iter().position(|x| x.id == target);
+    if let (Some(from), Some(to)) = (from, to) {
+        let image = images.remove(from);
+        images.insert(to, image);
+    }
+}
+
+/// Gallery editor on the update page: multi-file upload, drag-to-reorder,
+/// cover selection, captions and per-image delete.
+#[island]
+pub fn ManageGallery(estate_id: Uuid, images: Vec<EstateImage>) -> impl IntoView {
+    let images = RwSignal::new(images);
+    let dragged = RwSignal::new(None::<Uuid>);
+
+    let upload = Action::new_local(|data: &FormData| upload_estate_images(data.clone().into()));
+    let delete = ServerAction::<DeleteEstateImage>::new();
+    let cover = ServerAction::<SetCoverImage>::new();
+    let reorder = ServerAction::<ReorderEstateImages>::new();
+    let details = ServerAction::<UpdateImageDetails>::new();
+
+    let error = RwSignal::new(None::<String>);
+    let apply = move |res: Option<Result<Vec<EstateImage>, ServerFnError>>| match res {
+        Some(Ok(xs)) => {
+            images.set(xs);
+            error.set(None);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => (),
+    };
+    Effect::new(move || apply(upload.value().get()));
+    Effect::new(move || apply(delete.value().get()));
+    Effect::new(move || apply(cover.value().get()));
+    Effect::new(move || apply(reorder.value().get()));
+    Effect::new(move || apply(details.value().get()));
+
+    let on_upload = move |ev: web_sys::SubmitEvent| {
+        ev.prevent_default();
+        let target = ev.target().unwrap().unchecked_into::<HtmlFormElement>();
+        let form_data = FormData::new_with_form(&target).unwrap();
+        upload.dispatch_local(form_data);
+        target.reset();
+    };
+    let on_drop = move |target: Uuid| {
+        let Some(from) = dragged.get_untracked() else {
+            return;
+        };
+        dragged.set(None);
+        if from == target {
+            return;
+        }
+        images.update(|xs| move_image(xs, from, target));
+        let ids = images.with_untracked(|xs| xs.iter().map(|x| x.id).collect());
+        reorder.dispatch(ReorderEstateImages { estate_id, ids });
+    };
+
+    view! {
+        <div class="my-5">
+            <h2 class="text-xl font-bold mb-2">"معرض الصور"</h2>
+            <p class="text-sm text-gray-500 mb-4">"اسحب الصور لتغيير ترتيبها"</p>
+            <ShowLet some=move || error.get() let(msg)>
+                <div class="mb-4 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                </div>
+            </ShowLet>
+            <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-4 mb-6">
+                <For
+                    each=move || images.get()
+                    key=|x| (x.id, x.position, x.is_cover, x.caption.clone(), x.alt_text.clone())
+                    let(image)
+                >
+                    {
+                        let id = image.id;
+                        let caption = RwSignal::new(image.caption.clone());
+                        let alt_text = RwSignal::new(image.alt_text.clone());
+                        view! {
+                            <div
+                                class="bg-white rounded-xl shadow-md border-2 p-3 cursor-move"
+                                class=("border-blue-500", image.is_cover)
+                                class=("border-gray-100", !image.is_cover)
+                                class=("opacity-50", move || dragged.get() == Some(id))
+                                draggable="true"
+                                on:dragstart=move |ev: web_sys::DragEvent| {
+                                    if let Some(dt) = ev.data_transfer() {
+                                        let _ = dt.set_data("text/plain", &id.to_string());
+                                    }
+                                    dragged.set(Some(id));
+                                }
+                                on:dragend=move |_| dragged.set(None)
+                                on:dragover=move |ev: web_sys::DragEvent| ev.prevent_default()
+                                on:drop=move |ev: web_sys::DragEvent| {
+                                    ev.prevent_default();
+                                    on_drop(id);
+                                }
+                            >
+                                <img
+                                    class="w-full h-40 object-cover rounded-lg mb-3 pointer-events-none"
+                                    src={image.urls.smallest().to_string()}
+                                    alt={image.alt_text.clone()}
+                                />
+                                <Show when=move || image.is_cover>
+                                    <p class="text-blue-700 font-semibold text-sm mb-2">"صورة الغلاف"</p>
+                                </Show>
+                                <input
+                                    class="w-full px-3 py-2 mb-2 bg-gray-50 border-2 border-gray-200 rounded-lg text-sm"
+                                    type="text"
+                                    placeholder="التعليق"
+                                    bind:value=caption
+                                />
+                                <input
+                                    class="w-full px-3 py-2 mb-2 bg-gray-50 border-2 border-gray-200 rounded-lg text-sm"
+                                    type="text"
+                                    placeholder="النص البديل"
+                                    bind:value=alt_text
+                                />
+                                <div class="flex flex-wrap gap-2">
+                                    <button
+                                        class="flex-1 px-3 py-2 text-white bg-green-600 rounded-md hover:bg-green-700 text-sm"
+                                        on:click=move |_| {
+                                            details.dispatch(UpdateImageDetails {
+                                                estate_id,
+                                                id,
+                                                caption: caption.get_untracked(),
+                                                alt_text: alt_text.get_untracked(),
+                                            });
+                                        }
+                                    >"حفظ"</button>
+                                    <Show when=move || !image.is_cover>
+                                        <button
+                                            class="flex-1 px-3 py-2 text-white bg-blue-600 rounded-md hover:bg-blue-700 text-sm"
+                                            on:click=move |_| {
+                                                cover.dispatch(SetCoverImage { estate_id, id });
+                                            }
+                                        >"اجعلها الغلاف"</button>
+                                    </Show>
+                                    <button
+                                        class="flex-1 px-3 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 text-sm"
+                                        on:click=move |_| {
+                                            delete.dispatch(DeleteEstateImage { estate_id, id });
+                                        }
+                                    >"حذف"</button>
+                                </div>
+                            </div>
+                        }
+                    }
+                </For>
+            </div>
+            <form on:submit=on_upload class="grid grid-cols-1 md:grid-cols-2 gap-3 place-items-center">
+                <input class="hidden" type="text" value={estate_id.to_string()} name="target_id"/>
+                <input
+                    class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl text-gray-800"
+                    type="file"
+                    accept=".png, .jpg, .jpeg, .webp"
+                    name="data"
+                    multiple
+                    required
+                />
+                <input
+                    class="w-full px-4 py-2.5 bg-gradient-to-r from-green-500 to-emerald-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg transition-all duration-300 text-center"
+                    type="submit"
+                    value=move || if upload.pending().get() { "جاري الرفع..." } else { "إضافة الصور" }
+                />
+            </form>
+        </div>
+    }
+}
+
+/// Carousel with thumbnails and a full-screen lightbox for the details page.
+#[island]
+pub fn Gallery(images: Vec<EstateImage>) -> impl IntoView {
+    let count = images.len();
+    let images = StoredValue::new(images);
+    let current = RwSignal::new(0usize);
+    let lightbox = RwSignal::new(false);
+
+    let next = move || current.update(|x| *x = (*x + 1) % count.max(1));
+    let previous = move || current.update(|x| *x = (*x + count.max(1) - 1) % count.max(1));
+    let image = move || images.with_value(|xs| xs.get(current.get()).cloned());
+
+    let handle = window_event_listener(leptos::ev::keydown, move |ev| {
+        if !lightbox.get_untracked() {
+            return;
+        }
+        match ev.key().as_str() {
+            "Escape" => lightbox.set(false),
+            "ArrowLeft" => next(),
+            "ArrowRight" => previous(),
+            _ => (),
+        }
+    });
+    on_cleanup(move || handle.remove());
+
+    let nav_class = "absolute top-1/2 -translate-y-1/2 bg-black/50 text-white text-2xl w-12 h-12 rounded-full hover:bg-black/70";
+
+    view! {
+        <Show when=move || count != 0>
+            <div class="mb-8">
+                <div class="relative h-96 rounded-2xl overflow-hidden bg-gray-100">
+                    <ShowLet some=image let(image)>
+                        <img
+                            class="w-full h-full object-cover cursor-zoom-in"
+                            srcset={image.urls.srcset()}
+                            sizes="(min-width: 1024px) 1024px, 100vw"
+                            src={image.urls.original}
+                            alt={image.alt_text}
+                            on:click=move |_| lightbox.set(true)
+                        />
+                    </ShowLet>
+                    <Show when=move || { count > 1 }>
+                        <button class=format!("{nav_class} right-4") on:click=move |_| previous()>"›"</button>
+                        <button class=format!("{nav_class} left-4") on:click=move |_| next()>"‹"</button>
+                    </Show>
+                </div>
+                <p class="text-center text-gray-600 mt-2">
+                    {move || image().map(|x| x.caption).unwrap_or_default()}
+                </p>
+                <div class="flex gap-2 overflow-x-auto mt-4 pb-2">
+                    {move || images.with_value(|xs| {
+                        xs.iter()
+                            .enumerate()
+                            .map(|(i, x)| view! {
+                                <img
+                                    class="h-20 w-28 object-cover rounded-lg cursor-pointer border-2 flex-none"
+                                    class=("border-blue-500", move || current.get() == i)
+                                    class=("border-transparent", move || current.get() != i)
+                                    src={x.urls.smallest().to_string()}
+                                    alt={x.alt_text.clone()}
+                                    on:click=move |_| current.set(i)
+                                />
+                            })
+                            .collect_view()
+                    })}
+                </div>
+            </div>
+            <Show when=move || lightbox.get()>
+                <div
+                    class="fixed inset-0 z-50 bg-black/90 flex items-center justify-center"
+                    on:click=move |_| lightbox.set(false)
+                >
+                    <ShowLet some=image let(image)>
+                        <figure class="max-w-6xl max-h-screen p-4" on:click=|ev| ev.stop_propagation()>
+                            <img
+                                class="max-h-[85vh] mx-auto object-contain"
+                                srcset={image.urls.srcset()}
+                                sizes="100vw"
+                                src={image.urls.original}
+                                alt={image.alt_text}
+                            />
+                            <figcaption class="text-center text-white mt-3">{image.caption}</figcaption>
+                        </figure>
+                    </ShowLet>
+                    <button class="absolute top-4 left-4 text-white text-3xl" on:click=move |_| lightbox.set(false)>"×"</button>
+                    <Show when=move || { count > 1 }>
+                        <button
+                            class=format!("{nav_class} right-4")
+                            on:click=move |ev| {
+                                ev.stop_propagation();
+                                previous();
+                            }
+                        >"›"</button>
+                        <button
+                            class=format!("{nav_class} left-4")
+                            on:click=move |ev| {
+                                ev.stop_propagation();
+                                next();
+                            }
+                        >"‹"</button>
+                    </Show>
+                </div>
+            </Show>
+        </Show>
+    }
+}
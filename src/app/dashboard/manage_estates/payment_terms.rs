@@ -0,0 +1,136 @@
+use leptos::prelude::*;
+
+use crate::models::{InstallmentFrequency, ListingPurpose, PaymentPlan, PaymentTerms};
+
+fn number<T: ToString>(value: Option<T>) -> String {
+    value.map(|x| x.to_string()).unwrap_or_default()
+}
+
+fn pounds(cents: i64) -> String {
+    format!("{:.2}", cents as f64 / 100.0)
+}
+
+/// Inputs for the purpose and installment plan, named `terms[<field>]` so a
+/// form hands them to a server function as one [`PaymentTerms`]. Leaving
+/// the plan fields empty means cash only.
+#[component]
+pub fn PaymentTermsFields(
+    #[prop(optional)] terms: PaymentTerms,
+    input_class: &'static str,
+    label_class: &'static str,
+) -> impl IntoView {
+    let PaymentTerms {
+        purpose,
+        down_payment_in_cents,
+        installment_years,
+        installment_frequency,
+        installment_in_cents,
+    } = terms;
+
+    view! {
+        <div class="grid grid-cols-1 md:grid-cols-2 gap-6">
+            <div class="md:col-span-2">
+                <label class=label_class for="purpose">"الغرض"</label>
+                <select class=input_class name="terms[purpose]" id="purpose">
+                    {ListingPurpose::ALL
+                        .into_iter()
+                        .map(|x| view! {
+                            <option value={x.as_str()} selected={x == purpose}>{x.label()}</option>
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div>
+                <label class=label_class for="down_payment_in_cents">"المقدم (بالقرش)"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    min="0"
+                    name="terms[down_payment_in_cents]"
+                    id="down_payment_in_cents"
+                    value={number(down_payment_in_cents)}
+                />
+            </div>
+            <div>
+                <label class=label_class for="installment_years">"سنوات التقسيط"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    min="1"
+                    max="30"
+                    name="terms[installment_years]"
+                    id="installment_years"
+                    value={number(installment_years)}
+                />
+            </div>
+            <div>
+                <label class=label_class for="installment_frequency">"نظام الأقساط"</label>
+                <select class=input_class name="terms[installment_frequency]" id="installment_frequency">
+                    <option value="" selected={installment_frequency.is_none()}>"بدون تقسيط"</option>
+                    {InstallmentFrequency::ALL
+                        .into_iter()
+                        .map(|x| view! {
+                            <option value={x.as_str()} selected={Some(x) == installment_frequency}>{x.label()}</option>
+                        })
+                        .collect_view()}
+                </select>
+            </div>
+            <div>
+                <label class=label_class for="installment_in_cents">"قيمة القسط (بالقرش)"</label>
+                <input
+                    class=input_class
+                    type="number"
+                    min="1"
+                    name="terms[installment_in_cents]"
+                    id="installment_in_cents"
+                    value={number(installment_in_cents)}
+                />
+            </div>
+            <p class="md:col-span-2 text-sm text-gray-500">
+                "اترك حقول التقسيط فارغة إذا كان السداد كاش فقط. التقسيط متاح لعقارات البيع فقط."
+            </p>
+        </div>
+    }
+}
+
+/// An installment plan on the details page.
+#[component]
+pub fn PaymentPlanDetails(plan: PaymentPlan) -> impl IntoView {
+    let rows = [
+        (
+            "المقدم",
+            format!("{} جنيه", pounds(plan.down_payment_in_cents)),
+        ),
+        ("مدة التقسيط", format!("{} سنوات", plan.years)),
+        (
+            "القسط",
+            format!(
+                "{} جنيه ({})",
+                pounds(plan.installment_in_cents),
+                plan.frequency.label()
+            ),
+        ),
+        ("عدد الأقساط", plan.installments().to_string()),
+    ];
+    let total = plan
+        .total_in_cents()
+        .map(|x| ("الإجمالي", format!("{} جنيه", pounds(x))));
+    let rows = rows.into_iter().chain(total);
+
+    view! {
+        <div class="mb-8 bg-gradient-to-br from-purple-50 to-pink-50 p-6 rounded-2xl shadow-md border border-purple-100">
+            <h3 class="text-xl font-bold text-gray-800 mb-4">"خطة التقسيط"</h3>
+            <dl class="grid grid-cols-2 md:grid-cols-5 gap-4 text-center">
+                {rows
+                    .into_iter()
+                    .map(|(label, value)| view! {
+                        <div>
+                            <dt class="text-sm text-gray-500 mb-1">{label}</dt>
+                            <dd class="text-lg font-bold text-gray-800">{value}</dd>
+                        </div>
+                    })
+                    .collect_view()}
+            </dl>
+        </div>
+    }
+}
@@ -1,21 +1,50 @@
 use crate::app::Estate;
+use crate::models::{EstateAttributes, EstateFilter, EstateListing, EstatePage, EstateStatus};
 use leptos::prelude::*;
+use leptos_router::hooks::use_query_map;
+
+use super::estate_attributes::EstateFacts;
+use super::estate_filters::{EstateFilters, Pagination, Snippet, filter_from_query};
+use super::estate_status::StatusBadge;
 
 #[server]
-async fn get_public_estates() -> Result<Vec<Estate>, ServerFnError> {
+async fn get_public_estates(
+    filter: EstateFilter,
+    after: Option<String>,
+) -> Result<EstatePage, ServerFnError> {
+    use super::estate_filters::ESTATES_PAGE_SIZE;
+    use crate::models::EstateCursor;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    let res = crate::db::estates::get_all_estates(&app_state.pool)
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let after = after.as_deref().and_then(EstateCursor::parse);
+    let res = crate::db::estates::get_estates_page(
+        &app_state.pool,
+        &EstateFilter {
+            managed_by: None,
+            listed_only: true,
+            ..filter
+        },
+        after,
+        ESTATES_PAGE_SIZE,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
     Ok(res)
 }
 
 #[component]
 pub fn PublicEstates() -> impl IntoView {
-    let estates_res = Resource::new(|| (), move |_| get_public_estates());
-    let estates = move || estates_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let query = use_query_map();
+    let filter = move || query.with(filter_from_query);
+    let after = move || query.with(|q| q.get("after"));
+    let estates_res = Resource::new(
+        move || (filter(), after()),
+        |(filter, after)| get_public_estates(filter, after),
+    );
+    let page = move || estates_res.get().and_then(|x| x.ok()).unwrap_or_default();
+    let estates = move || page().items;
 
     view! {
         <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
@@ -26,6 +55,7 @@ pub fn PublicEstates() -> impl IntoView {
                     </h1>
                     <p class="text-gray-600 text-lg">"تصفح العقارات المتاحة"</p>
                 </div>
+                <EstateFilters filter=Signal::derive(filter)/>
 
                 <Suspense fallback=|| view! {
                     <div class="text-center py-12">
@@ -36,23 +66,34 @@ pub fn PublicEstates() -> impl IntoView {
                     <div class="grid grid-cols-1 lg:grid-cols-2 gap-8 mb-8">
                         <For
                             each={estates}
-                            key=|x| x.id
-                            let(Estate { id, name, address, image_url, price_in_cents, space_in_meters, .. })
+                            key=|x| x.estate.id
+                            let(EstateListing { estate: Estate { id, name, address, image_url, thumbnail_url, price_in_cents, space_in_meters, property_type, bedrooms, bathrooms, floor, finishing, furnished, purpose, status, .. }, snippet })
                         >
                             <div class="group bg-white/90 backdrop-blur-sm rounded-2xl shadow-lg hover:shadow-2xl transition-all duration-500 overflow-hidden border border-gray-100 hover:scale-[1.02]">
                                 <div class="relative h-64 overflow-hidden">
                                     <img
                                         class="w-full h-full object-cover transform group-hover:scale-110 transition-transform duration-700"
-                                        src={image_url}
+                                        src={thumbnail_url.unwrap_or(image_url)}
                                         alt={name.clone()}
                                     />
                                     <div class="absolute inset-0 bg-gradient-to-t from-black/60 to-transparent opacity-0 group-hover:opacity-100 transition-opacity duration-300"></div>
+                                    <span class="absolute top-4 right-4 px-3 py-1 bg-white/90 text-blue-700 text-sm font-semibold rounded-full shadow">
+                                        {purpose.label()}
+                                    </span>
+                                    <Show when=move || status != EstateStatus::Published>
+                                        <div class="absolute top-4 left-4">
+                                            <StatusBadge status/>
+                                        </div>
+                                    </Show>
                                 </div>
 
                                 <div class="p-6">
                                     <h2 class="text-2xl font-bold text-gray-800 mb-3 group-hover:text-blue-600 transition-colors duration-300">
                                         {name}
                                     </h2>
+                                    <ShowLet some=move || snippet.clone() let(snippet)>
+                                        <Snippet snippet/>
+                                    </ShowLet>
 
                                     <div class="space-y-3 mb-6">
                                         <div class="flex items-center text-gray-600 gap-2">
@@ -75,9 +116,10 @@ pub fn PublicEstates() -> impl IntoView {
                                                 <svg class="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                                                     <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 8c-1.657 0-3 .895-3 2s1.343 2 3 2 3 .895 3 2-1.343 2-3 2"></path>
                                                 </svg>
-                                                <span class="font-bold">{format!("{:.2}", price_in_cents as f32 / 100.0)}" ج"</span>
+                                                <span class="font-bold">{format!("{:.2}", price_in_cents as f32 / 100.0)}" ج"{purpose.price_suffix()}</span>
                                             </div>
                                         </div>
+                                        <EstateFacts attributes={EstateAttributes { property_type, bedrooms, bathrooms, floor, finishing, furnished }}/>
                                     </div>
 
                                     <div class="flex flex-wrap gap-3">
@@ -92,6 +134,11 @@ pub fn PublicEstates() -> impl IntoView {
                             </div>
                         </For>
                     </div>
+                    <Pagination
+                        filter=Signal::derive(filter)
+                        paged=Signal::derive(move || after().is_some())
+                        next=Signal::derive(move || page().next)
+                    />
                 </Suspense>
             </div>
         </div>
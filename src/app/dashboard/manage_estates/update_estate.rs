@@ -1,14 +1,21 @@
 use leptos::prelude::*;
 use leptos_router::hooks::use_params_map;
 use uuid::Uuid;
-use web_sys::wasm_bindgen::JsCast;
-use web_sys::{FormData, HtmlFormElement, HtmlInputElement};
 
+use super::estate_attributes::AttributeFields;
+use super::estate_status::{ScheduleFields, StatusBadge, StatusHistory};
+use super::gallery::{ManageGallery, get_estate_images};
+use super::payment_terms::PaymentTermsFields;
+use super::videos::{ManageVideos, get_estate_videos};
 use crate::app::Estate;
-use crate::auth::AuthRequired;
+use crate::auth::{AuthRequired, Permission, PermissionRequired};
+use crate::models::{EstateAttributes, EstateStatus, ListingSchedule, PaymentTerms};
 
 #[server]
 async fn get_estate_by_id(id: uuid::Uuid) -> Result<Estate, ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    require_permission(Permission::EstatesView).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
@@ -23,125 +30,385 @@ async fn get_estate_by_id(id: uuid::Uuid) -> Result<Estate, ServerFnError> {
 
 #[server]
 async fn update_name(target_id: uuid::Uuid, name: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::update_estate_name(&app_state.pool, target_id, name)
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
+    crate::db::estates::update_estate_name(&app_state.pool, target_id, name.clone())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "name",
+        before.name,
+        name,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
     leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
     Ok(())
 }
 
 #[server]
 async fn update_address(target_id: uuid::Uuid, address: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::update_estate_address(&app_state.pool, target_id, address)
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_address(&app_state.pool, target_id, address.clone())
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "address",
+        before.address,
+        address,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
     Ok(())
 }
 
-#[server(input = server_fn::codec::MultipartFormData)]
-async fn update_image_url(data: server_fn::codec::MultipartData) -> Result<String, ServerFnError> {
-    use std::path::PathBuf;
+#[server]
+async fn update_description(
+    target_id: uuid::Uuid,
+    description: String,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    let mut data = data.into_inner().unwrap();
-
-    let mut image_data = Vec::new();
-    let mut target_id = Uuid::nil();
-    let mut image_url = String::new();
-
-    while let Ok(Some(mut field)) = data.next_field().await {
-        let name = field.name().unwrap_or_default().to_string();
-        match name.as_str() {
-            "data" => {
-                while let Ok(Some(data)) = field.chunk().await {
-                    image_data.extend(data.to_vec());
-                }
-            }
-            "image_url" => {
-                if let Ok(iu) = field.text().await {
-                    image_url = iu;
-                }
-            }
-            "target_id" => {
-                if let Ok(ti) = field.text().await {
-                    target_id = Uuid::parse_str(&ti)?;
-                }
-            }
-            _ => (),
-        };
-    }
-    if image_data.is_empty() {
-        return Err(ServerFnError::new("no data was recieved for the image"));
-    }
-    if image_url.is_empty() {
-        return Err(ServerFnError::new("no url was recieved for the image"));
-    }
-    if target_id.is_nil() {
-        return Err(ServerFnError::new("no id was recieved for the image"));
-    }
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_description(&app_state.pool, target_id, description.clone())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "description",
+        before.description,
+        description,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
-    let image_name = PathBuf::from(image_url)
-        .file_name()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_string();
+    leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
+    Ok(())
+}
+
+#[server]
+async fn update_price(target_id: uuid::Uuid, price_in_cents: i64) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::{AuditEntity, validate_price},
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+    validate_price(price_in_cents).map_err(ServerFnError::new)?;
 
-    let url = app_state
-        .s3
-        .store_image(&image_name, None, image_data)
-        .await?;
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    Ok(format!("{}?vv={}", url, Uuid::new_v4()))
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_price(&app_state.pool, target_id, price_in_cents)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "price_in_cents",
+        before.price_in_cents,
+        price_in_cents,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
+    Ok(())
 }
 
 #[server]
-async fn update_description(
+async fn update_space(target_id: uuid::Uuid, space_in_meters: i32) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::{AuditEntity, validate_space},
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+    validate_space(space_in_meters).map_err(ServerFnError::new)?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_space(&app_state.pool, target_id, space_in_meters)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "space_in_meters",
+        before.space_in_meters,
+        space_in_meters,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
+    Ok(())
+}
+
+#[server]
+async fn update_attributes(
     target_id: uuid::Uuid,
-    description: String,
+    attributes: EstateAttributes,
 ) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+    attributes.validate().map_err(ServerFnError::new)?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::update_description(&app_state.pool, target_id, description)
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
 
+    crate::db::estates::update_estate_attributes(&app_state.pool, target_id, &attributes)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.update_attributes",
+        AuditEntity::Estate,
+        Some(target_id),
+        Some(serde_json::json!(before.attributes())),
+        Some(serde_json::json!(attributes)),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
     leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
     Ok(())
 }
 
 #[server]
-async fn update_price(target_id: uuid::Uuid, price_in_cents: i64) -> Result<(), ServerFnError> {
+async fn update_payment_terms(
+    target_id: uuid::Uuid,
+    terms: PaymentTerms,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+    terms.validate().map_err(ServerFnError::new)?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::update_estate_price(&app_state.pool, target_id, price_in_cents)
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_payment_terms(&app_state.pool, target_id, &terms)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.update_payment_terms",
+        AuditEntity::Estate,
+        Some(target_id),
+        Some(serde_json::json!(before.terms())),
+        Some(serde_json::json!(terms)),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
     Ok(())
 }
 
 #[server]
-async fn update_space(target_id: uuid::Uuid, space_in_meters: i32) -> Result<(), ServerFnError> {
+async fn update_status(target_id: uuid::Uuid, status: EstateStatus) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::estates::update_estate_space(&app_state.pool, target_id, space_in_meters)
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    before
+        .status
+        .transition(status)
+        .map_err(ServerFnError::new)?;
+    if let Some(permission) = before.status.transition_permission(status) {
+        require_estate_permission(target_id, permission).await?;
+    }
+
+    let changed =
+        crate::db::estates::update_estate_status(&app_state.pool, target_id, before.status, status)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if !changed {
+        return Err(ServerFnError::new(
+            "the estate's status was changed meanwhile",
+        ));
+    }
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "status",
+        before.status,
+        status,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
+    Ok(())
+}
+
+#[server]
+async fn update_schedule(
+    target_id: uuid::Uuid,
+    schedule: ListingSchedule,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(target_id, Permission::EstatesEdit).await?;
+    let schedule = schedule.trimmed();
+    schedule.validate().map_err(ServerFnError::new)?;
+    // The scheduler publishes on the caller's behalf.
+    if schedule.publish_at.is_some() {
+        require_estate_permission(target_id, Permission::EstatesPublish).await?;
+    }
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_schedule(&app_state.pool, target_id, &schedule)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.update_schedule",
+        AuditEntity::Estate,
+        Some(target_id),
+        Some(serde_json::json!(before.schedule())),
+        Some(serde_json::json!(schedule)),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
+    Ok(())
+}
+
+#[server]
+async fn get_agents() -> Result<Vec<(Uuid, String)>, ServerFnError> {
+    use crate::auth::require_permission;
+    require_permission(Permission::EstatesManageAll).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let users = crate::db::users::get_all_users(&app_state.pool)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(users.into_iter().map(|x| (x.id, x.name)).collect())
+}
+
+#[server]
+async fn update_agent(target_id: uuid::Uuid, assigned_agent: Uuid) -> Result<(), ServerFnError> {
+    use crate::{auth::require_permission, models::AuditEntity};
+    let user_id = require_permission(Permission::EstatesManageAll).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let before = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estates::update_estate_assigned_agent(&app_state.pool, target_id, assigned_agent)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::Estate,
+        target_id,
+        "assigned_agent",
+        before.assigned_agent,
+        Some(assigned_agent),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateEstate/{}", target_id));
     Ok(())
@@ -158,23 +425,36 @@ pub fn UpdateEstate() -> impl IntoView {
     };
     let target_res = Resource::new(target_id, get_estate_by_id);
 
-    let target = move || target_res.get().and_then(|x| x.ok());
+    let target = move || {
+        target_res
+            .get()
+            .and_then(|x| x.ok())
+            .map(|x| (x.status_history(), x))
+    };
 
     view! {
         <AuthRequired>
             <Suspense>
                 <ShowLet
                     some=target
-                    let(Estate{id,image_url,address,name,price_in_cents,space_in_meters,description})
+                    let((history, Estate{id,status,address,name,price_in_cents,space_in_meters,description,assigned_agent,property_type,bedrooms,bathrooms,floor,finishing,furnished,purpose,down_payment_in_cents,installment_years,installment_frequency,installment_in_cents,publish_at,expire_at,..}))
                 >
                 <div class="grid grid-cols-1 gap-5 text-center border-5 rounded-lg my-10 mx-5 p-1 md:p-3 lg:p-5">
                     <h1 class="text-2xl font-bold mb-5">"تحديث بيانات العقار"</h1>
+                    <UpdateStatus id status history/>
+                    <UpdateSchedule id schedule={ListingSchedule { publish_at, expire_at }}/>
                     <UpdateName id name/>
                     <UpdateAddress id address/>
-                    <UpdateImage id image_url/>
+                    <UpdateGallery id/>
+                    <UpdateVideos id/>
                     <UpdateDescription id description/>
                     <UpdatePrice id price_in_cents/>
                     <UpdateSpace id space_in_meters/>
+                    <UpdatePaymentTerms id terms={PaymentTerms { purpose, down_payment_in_cents, installment_years, installment_frequency, installment_in_cents }}/>
+                    <UpdateAttributes id attributes={EstateAttributes { property_type, bedrooms, bathrooms, floor, finishing, furnished }}/>
+                    <PermissionRequired permission=Permission::EstatesManageAll>
+                        <UpdateAgent id assigned_agent/>
+                    </PermissionRequired>
                 </div>
                 </ShowLet>
             </Suspense>
@@ -195,6 +475,166 @@ fn GoBack() -> impl IntoView {
     }
 }
 
+#[component]
+fn UpdateGallery(id: uuid::Uuid) -> impl IntoView {
+    let images_res = Resource::new(move || id, get_estate_images);
+    let images = move || images_res.get().and_then(|x| x.ok());
+
+    view! {
+        <Suspense>
+            <ShowLet some=images let(images)>
+                <ManageGallery estate_id=id images/>
+            </ShowLet>
+        </Suspense>
+    }
+}
+
+#[component]
+fn UpdateVideos(id: uuid::Uuid) -> impl IntoView {
+    let videos_res = Resource::new(move || id, get_estate_videos);
+    let videos = move || videos_res.get().and_then(|x| x.ok());
+
+    view! {
+        <Suspense>
+            <ShowLet some=videos let(videos)>
+                <ManageVideos estate_id=id videos/>
+            </ShowLet>
+        </Suspense>
+    }
+}
+
+#[component]
+fn UpdateAgent(id: uuid::Uuid, assigned_agent: Option<Uuid>) -> impl IntoView {
+    let action = ServerAction::<UpdateAgent>::new();
+    let agents_res = Resource::new(|| (), |_| get_agents());
+    let agents = move || agents_res.get().and_then(|x| x.ok()).unwrap_or_default();
+
+    view! {
+        <ActionForm action={action}>
+            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+            <div class="grid grid-cols-1 gap-2 my-5">
+                <label
+                    class="block text-sm font-bold mb-2 sm:text-base lg:text-xl"
+                    for="assigned_agent"
+                >"الوكيل المسؤول"</label>
+                <select
+                    class="text-center w-full px-4 py-2 border-2 border-gray-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500 md:border-green-400"
+                    name="assigned_agent"
+                    id="assigned_agent"
+                >
+                    <For
+                        each={agents}
+                        key=|x| x.0
+                        let((agent_id, name))
+                    >
+                        <option
+                            value={agent_id.to_string()}
+                            selected={assigned_agent == Some(agent_id)}
+                        >{name}</option>
+                    </For>
+                </select>
+                <SubmitButton content="تحديث الوكيل"/>
+            </div>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn UpdateStatus(
+    id: uuid::Uuid,
+    status: EstateStatus,
+    history: Vec<(EstateStatus, String)>,
+) -> impl IntoView {
+    let action = ServerAction::<UpdateStatus>::new();
+    view! {
+        <div class="grid grid-cols-1 gap-3 my-5">
+            <p class="flex justify-center items-center gap-3 text-sm font-bold sm:text-base lg:text-xl">
+                "حالة الإعلان:"
+                <StatusBadge status/>
+            </p>
+            <StatusHistory history/>
+            <div class="flex flex-wrap justify-center gap-3">
+                {status
+                    .next()
+                    .iter()
+                    .map(|next| {
+                        let form = move || view! {
+                            <ActionForm action={action}>
+                                <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+                                <input class="hidden" type="text" value={next.as_str()} name="status"/>
+                                <SubmitButton content={next.action_label()}/>
+                            </ActionForm>
+                        };
+                        match status.transition_permission(*next) {
+                            Some(permission) => view! {
+                                <PermissionRequired permission>
+                                    {form()}
+                                </PermissionRequired>
+                            }
+                            .into_any(),
+                            None => form().into_any(),
+                        }
+                    })
+                    .collect_view()}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn UpdateSchedule(id: uuid::Uuid, schedule: ListingSchedule) -> impl IntoView {
+    let action = ServerAction::<UpdateSchedule>::new();
+    view! {
+        <ActionForm action={action}>
+            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+            <div class="grid grid-cols-1 gap-2 my-5">
+                <ScheduleFields
+                    schedule
+                    input_class="text-center w-full px-4 py-2 border-2 border-gray-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500 md:border-green-400"
+                    label_class="block text-sm font-bold mb-2 sm:text-base lg:text-xl"
+                />
+                <SubmitButton content="تحديث مواعيد النشر"/>
+            </div>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn UpdateAttributes(id: uuid::Uuid, attributes: EstateAttributes) -> impl IntoView {
+    let action = ServerAction::<UpdateAttributes>::new();
+    view! {
+        <ActionForm action={action}>
+            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+            <div class="grid grid-cols-1 gap-2 my-5">
+                <AttributeFields
+                    attributes
+                    input_class="text-center w-full px-4 py-2 border-2 border-gray-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500 md:border-green-400"
+                    label_class="block text-sm font-bold mb-2 sm:text-base lg:text-xl"
+                />
+                <SubmitButton content="تحديث المواصفات"/>
+            </div>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn UpdatePaymentTerms(id: uuid::Uuid, terms: PaymentTerms) -> impl IntoView {
+    let action = ServerAction::<UpdatePaymentTerms>::new();
+    view! {
+        <ActionForm action={action}>
+            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+            <div class="grid grid-cols-1 gap-2 my-5">
+                <PaymentTermsFields
+                    terms
+                    input_class="text-center w-full px-4 py-2 border-2 border-gray-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500 md:border-green-400"
+                    label_class="block text-sm font-bold mb-2 sm:text-base lg:text-xl"
+                />
+                <SubmitButton content="تحديث طريقة السداد"/>
+            </div>
+        </ActionForm>
+    }
+}
+
 #[component]
 fn UpdateSpace(id: uuid::Uuid, space_in_meters: i32) -> impl IntoView {
     let action = ServerAction::<UpdateSpace>::new();
@@ -317,74 +757,6 @@ fn UpdateName(id: uuid::Uuid, name: String) -> impl IntoView {
     }
 }
 
-#[island]
-fn UpdateImage(id: uuid::Uuid, image_url: String) -> impl IntoView {
-    let url = RwSignal::new(image_url.clone());
-
-    let action =
-        Action::new_local(|data: &web_sys::FormData| update_image_url(data.clone().into()));
-
-    Effect::new(move || {
-        let nv = action.value().get().transpose().ok().flatten();
-        if let Some(nv) = nv {
-            url.set(nv);
-        }
-    });
-
-    let on_submit = move |ev: web_sys::SubmitEvent| {
-        ev.prevent_default();
-
-        let target = ev.target().unwrap().unchecked_into::<HtmlFormElement>();
-        let form_data = FormData::new_with_form(&target).unwrap();
-        action.dispatch_local(form_data);
-    };
-    let default_label = "اختار الصورة البديلة";
-
-    let label_content = RwSignal::new(default_label.to_string());
-    let on_input = move |ev: web_sys::Event| {
-        let target = ev.target().unwrap().unchecked_into::<HtmlInputElement>();
-        let res = target
-            .files()
-            .and_then(|xs| xs.get(0).map(|x| x.name()))
-            .map(|x| format!("تم اختيار الصورة : {x}"))
-            .unwrap_or(default_label.to_string());
-        label_content.set(res);
-    };
-
-    view! {
-        <form on:submit={on_submit}>
-            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
-            <input class="hidden" type="text" value={image_url.to_string()} name="image_url"/>
-            <div class="grid grid-cols-1 gap-2 my-5 place-items-center gap-5">
-                <label
-                    class="block text-sm font-bold mb-2 sm:text-base lg:text-xl"
-                    for="image_url"
-                >"الصورة"</label>
-                <div class="grid grid-cols-2 gap-5">
-                    <img src={move || url.get()}/>
-                    <div class="grid grid-cols-1 gap-5">
-                        <label
-                            for="data"
-                            class="px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-pink-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800 placeholder-gray-400"
-                        >{move || label_content.get()}</label>
-                        <input
-                            on:input={on_input}
-                            class="hidden"
-                            type="file"
-                            accept=".png, .jpg, .jpeg, .webp"
-                            name="data"
-                            id="data"
-                            placeholder="hello"
-                            required
-                        />
-                        <SubmitButton content="تحديث الصورة"/>
-                    </div>
-                </div>
-            </div>
-        </form>
-    }
-}
-
 #[component]
 fn SubmitButton(content: &'static str) -> impl IntoView {
     view! {
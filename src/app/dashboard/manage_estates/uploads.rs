@@ -0,0 +1,59 @@
+//! Browser side of uploads that go straight to storage.
+
+use std::{cell::Cell, rc::Rc};
+
+use web_sys::{
+    Blob, ProgressEvent, XmlHttpRequest,
+    wasm_bindgen::{JsCast, JsValue, closure::Closure},
+};
+
+type ProgressHandler = Closure<dyn FnMut(ProgressEvent)>;
+
+/// Puts `body` to `url` with `XMLHttpRequest`, the only browser api that
+/// reports upload progress. `on_progress` gets the bytes sent so far and
+/// `on_done` whether storage took the body.
+pub fn put_blob(
+    url: &str,
+    content_type: Option<&str>,
+    body: &Blob,
+    mut on_progress: impl FnMut(f64) + 'static,
+    on_done: impl FnOnce(bool) + 'static,
+) -> Result<(), JsValue> {
+    let xhr = XmlHttpRequest::new()?;
+    xhr.open("PUT", url)?;
+    if let Some(content_type) = content_type {
+        xhr.set_request_header("Content-Type", content_type)?;
+    }
+
+    let progress = ProgressHandler::new(move |ev: ProgressEvent| on_progress(ev.loaded()));
+    xhr.upload()?
+        .set_onprogress(Some(progress.as_ref().unchecked_ref()));
+
+    // Load or error, whichever comes, reports back once and releases the
+    // progress handler.
+    let done = Rc::new(Cell::new(Some((on_done, progress))));
+    let finish = move |xhr: &XmlHttpRequest, ok: bool| {
+        if let Some((on_done, _progress)) = done.take() {
+            if let Ok(upload) = xhr.upload() {
+                upload.set_onprogress(None);
+            }
+            on_done(ok);
+        }
+    };
+    let finish = Rc::new(finish);
+
+    let on_load = {
+        let (xhr, finish) = (xhr.clone(), finish.clone());
+        Closure::once_into_js(move || {
+            finish(&xhr, xhr.status().is_ok_and(|x| (200..300).contains(&x)))
+        })
+    };
+    xhr.set_onload(Some(on_load.unchecked_ref()));
+    let on_error = {
+        let xhr = xhr.clone();
+        Closure::once_into_js(move || finish(&xhr, false))
+    };
+    xhr.set_onerror(Some(on_error.unchecked_ref()));
+
+    xhr.send_with_opt_blob(Some(body))
+}
@@ -0,0 +1,491 @@
+use std::{cell::Cell, rc::Rc};
+
+use leptos::prelude::*;
+use uuid::Uuid;
+use web_sys::{
+    Blob, CanvasRenderingContext2d, File, FormData, HtmlCanvasElement, HtmlFormElement,
+    HtmlInputElement, HtmlVideoElement, SubmitEvent,
+    wasm_bindgen::{JsCast, JsValue, closure::Closure},
+};
+
+use super::uploads::put_blob;
+use crate::models::{EstateVideo, VideoPart, VideoUploadPlan};
+use crate::videos::PART_SIZE;
+
+/// Removes the stored files and posters behind `videos`.
+#[cfg(feature = "ssr")]
+pub async fn delete_video_objects(
+    storage: &dyn crate::storage::ImageStorage,
+    videos: &[EstateVideo],
+) -> Result<(), ServerFnError> {
+    for key in videos.iter().flat_map(|x| x.stored()) {
+        storage
+            .delete(key)
+            .await
+            .map_err(|e| ServerFnError::new(format!("video delete failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// The videos of an estate the caller may see.
+#[server]
+pub async fn get_estate_videos(estate_id: Uuid) -> Result<Vec<EstateVideo>, ServerFnError> {
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::auth::require_estate_visible(&estate).await?;
+
+    crate::db::estate_videos::get_estate_videos(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Starts, or resumes, the upload of a video and hands out the urls of the
+/// parts still missing.
+#[server]
+async fn start_video_upload(
+    estate_id: Uuid,
+    file_name: String,
+    content_type: String,
+    size: i64,
+) -> Result<VideoUploadPlan, ServerFnError> {
+    use crate::auth::{Permission, require_estate_permission};
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::videos::start_upload(
+        &app_state.pool,
+        app_state.storage.as_ref(),
+        estate_id,
+        user_id,
+        &file_name,
+        &content_type,
+        size,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Checks a fully uploaded video and adds it to the estate.
+#[server]
+async fn finish_video_upload(
+    estate_id: Uuid,
+    upload_id: String,
+) -> Result<Vec<EstateVideo>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+        videos::VideoError,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let upload = crate::db::video_uploads::get_video_upload(&app_state.pool, &upload_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .filter(|x| x.estate_id == estate_id && x.user_id == user_id)
+        .ok_or_else(|| ServerFnError::new(VideoError::UploadNotFound.to_string()))?;
+    let id = crate::videos::finish_upload(&app_state.pool, app_state.storage.as_ref(), &upload)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.add_video",
+        AuditEntity::Estate,
+        Some(estate_id),
+        None,
+        Some(serde_json::json!({ "video": id, "key": upload.object_key, "size": upload.size })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    crate::db::estate_videos::get_estate_videos(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Stores the frame the agent picked as the video's poster, replacing the
+/// previous one.
+#[server(input = server_fn::codec::MultipartFormData)]
+async fn set_video_poster(
+    data: server_fn::codec::MultipartData,
+) -> Result<Vec<EstateVideo>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission, require_permission},
+        models::AuditEntity,
+    };
+    require_permission(Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let (target_id, files) = super::gallery::read_upload(data).await?;
+    let target_id =
+        target_id.ok_or_else(|| ServerFnError::new("no id was recieved for the video"))?;
+    let video = crate::db::estate_videos::get_estate_video(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let user_id = require_estate_permission(video.estate_id, Permission::EstatesEdit).await?;
+
+    let frame = files
+        .into_iter()
+        .next()
+        .ok_or_else(|| ServerFnError::new("no data was recieved for the image"))?;
+    let poster_key = crate::images::store_original(app_state.storage.as_ref(), frame)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::estate_videos::set_video_poster(
+        &app_state.pool,
+        video.estate_id,
+        video.id,
+        &poster_key,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.set_video_poster",
+        AuditEntity::Estate,
+        Some(video.estate_id),
+        Some(serde_json::json!({ "video": video.id, "poster": video.poster_key })),
+        Some(serde_json::json!({ "video": video.id, "poster": poster_key })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if let Some(old) = &video.poster_key {
+        app_state
+            .storage
+            .delete(old)
+            .await
+            .map_err(|e| ServerFnError::new(format!("image delete failed: {e}")))?;
+    }
+
+    crate::db::estate_videos::get_estate_videos(&app_state.pool, video.estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn delete_estate_video(estate_id: Uuid, id: Uuid) -> Result<Vec<EstateVideo>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_estate_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_estate_permission(estate_id, Permission::EstatesEdit).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let video = crate::db::estate_videos::get_estate_video(&app_state.pool, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if video.estate_id != estate_id {
+        return Err(ServerFnError::new(
+            "the video does not belong to this estate",
+        ));
+    }
+    crate::db::estate_videos::delete_estate_video(&app_state.pool, estate_id, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.delete_video",
+        AuditEntity::Estate,
+        Some(estate_id),
+        Some(serde_json::json!(video)),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    delete_video_objects(app_state.storage.as_ref(), &[video]).await?;
+
+    crate::db::estate_videos::get_estate_videos(&app_state.pool, estate_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Puts the parts one after the other, so an interrupted upload keeps every
+/// part before the failed one for when it is resumed.
+fn put_parts<F: FnOnce(bool) + 'static>(
+    file: File,
+    mut parts: std::vec::IntoIter<VideoPart>,
+    sent: f64,
+    progress: RwSignal<Option<(f64, f64)>>,
+    on_done: F,
+) {
+    let Some(part) = parts.next() else {
+        return on_done(true);
+    };
+    let total = file.size();
+    let start = (part.number - 1) as f64 * PART_SIZE as f64;
+    let end = (start + PART_SIZE as f64).min(total);
+    let Ok(chunk) = file.slice_with_f64_and_f64(start, end) else {
+        return on_done(false);
+    };
+
+    let on_done = Rc::new(Cell::new(Some(on_done)));
+    let result = put_blob(
+        &part.url,
+        None,
+        &chunk,
+        move |loaded| progress.set(Some((sent + loaded, total))),
+        {
+            let on_done = on_done.clone();
+            move |ok| {
+                if let Some(on_done) = on_done.take() {
+                    if ok {
+                        put_parts(file, parts, sent + end - start, progress, on_done);
+                    } else {
+                        on_done(false);
+                    }
+                }
+            }
+        },
+    );
+    if result.is_err()
+        && let Some(on_done) = on_done.take()
+    {
+        on_done(false);
+    }
+}
+
+/// Draws the frame `video` is showing and hands it over as a JPEG.
+fn capture_frame(
+    video: &HtmlVideoElement,
+    on_frame: impl FnOnce(Blob) + 'static,
+) -> Result<(), JsValue> {
+    let canvas = document()
+        .create_element("canvas")?
+        .unchecked_into::<HtmlCanvasElement>();
+    canvas.set_width(video.video_width());
+    canvas.set_height(video.video_height());
+    canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("no 2d context"))?
+        .unchecked_into::<CanvasRenderingContext2d>()
+        .draw_image_with_html_video_element(video, 0.0, 0.0)?;
+    let callback = Closure::once_into_js(move |blob: Option<Blob>| {
+        if let Some(blob) = blob {
+            on_frame(blob);
+        }
+    });
+    canvas.to_blob_with_type(callback.unchecked_ref(), "image/jpeg")
+}
+
+fn poster_form(id: Uuid, frame: &Blob) -> Result<FormData, JsValue> {
+    let form = FormData::new()?;
+    form.append_with_str("target_id", &id.to_string())?;
+    form.append_with_blob_and_filename("data", frame, "poster.jpg")?;
+    Ok(form)
+}
+
+/// Video editor on the update page: resumable upload with progress, poster
+/// frame selection and delete.
+#[island]
+pub fn ManageVideos(estate_id: Uuid, videos: Vec<EstateVideo>) -> impl IntoView {
+    let videos = RwSignal::new(videos);
+    let progress = RwSignal::new(None::<(f64, f64)>);
+
+    let finish = ServerAction::<FinishVideoUpload>::new();
+    let delete = ServerAction::<DeleteEstateVideo>::new();
+    let poster = Action::new_local(|data: &FormData| set_video_poster(data.clone().into()));
+
+    let error = RwSignal::new(None::<String>);
+    let apply = move |res: Option<Result<Vec<EstateVideo>, ServerFnError>>| match res {
+        Some(Ok(xs)) => {
+            videos.set(xs);
+            error.set(None);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => (),
+    };
+    Effect::new(move || {
+        let res = finish.value().get();
+        if res.is_some() {
+            progress.set(None);
+        }
+        apply(res);
+    });
+    Effect::new(move || apply(delete.value().get()));
+    Effect::new(move || apply(poster.value().get()));
+
+    let on_upload = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        let target = ev.target().unwrap().unchecked_into::<HtmlFormElement>();
+        let Some(file) = target
+            .query_selector("input[type=file]")
+            .ok()
+            .flatten()
+            .and_then(|x| x.unchecked_into::<HtmlInputElement>().files())
+            .and_then(|x| x.get(0))
+        else {
+            return;
+        };
+
+        error.set(None);
+        progress.set(Some((0.0, file.size())));
+        leptos::task::spawn_local(async move {
+            let plan =
+                start_video_upload(estate_id, file.name(), file.type_(), file.size() as i64).await;
+            let plan = match plan {
+                Ok(x) => x,
+                Err(e) => {
+                    progress.set(None);
+                    error.set(Some(e.to_string()));
+                    return;
+                }
+            };
+            let VideoUploadPlan {
+                upload_id,
+                uploaded,
+                parts,
+            } = plan;
+            put_parts(
+                file,
+                parts.into_iter(),
+                uploaded as f64,
+                progress,
+                move |ok| {
+                    if ok {
+                        finish.dispatch(FinishVideoUpload {
+                            estate_id,
+                            upload_id,
+                        });
+                    } else {
+                        progress.set(None);
+                        error.set(Some(
+                            "انقطع رفع الفيديو، أعد اختيار الملف نفسه لاستكمال الرفع".to_string(),
+                        ));
+                    }
+                },
+            );
+        });
+    };
+    let percent = move || {
+        progress
+            .get()
+            .map(|(sent, total)| (sent / total.max(1.0) * 100.0).min(100.0) as u32)
+    };
+
+    view! {
+        <div class="my-5">
+            <h2 class="text-xl font-bold mb-2">"جولات الفيديو"</h2>
+            <p class="text-sm text-gray-500 mb-4">"شغّل الفيديو وأوقفه عند اللقطة المناسبة لجعلها صورة الغلاف"</p>
+            <ShowLet some=move || error.get() let(msg)>
+                <div class="mb-4 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                </div>
+            </ShowLet>
+            <div class="grid grid-cols-1 md:grid-cols-2 gap-4 mb-6">
+                <For
+                    each=move || videos.get()
+                    key=|x| (x.id, x.poster_key.clone())
+                    let(video)
+                >
+                    {
+                        let id = video.id;
+                        let video_ref = NodeRef::<leptos::html::Video>::new();
+                        let on_capture = move |_| {
+                            let Some(element) = video_ref.get() else {
+                                return;
+                            };
+                            let captured = capture_frame(&element, move |frame| {
+                                if let Ok(form) = poster_form(id, &frame) {
+                                    poster.dispatch_local(form);
+                                }
+                            });
+                            if captured.is_err() {
+                                error.set(Some("تعذر التقاط اللقطة الحالية".to_string()));
+                            }
+                        };
+                        view! {
+                            <div class="bg-white rounded-xl shadow-md border-2 border-gray-100 p-3">
+                                <video
+                                    node_ref=video_ref
+                                    class="w-full rounded-lg mb-3 bg-black"
+                                    controls
+                                    preload="metadata"
+                                    crossorigin="anonymous"
+                                    src={video.video_url.clone()}
+                                    poster={video.poster_url.clone()}
+                                ></video>
+                                <p class="text-sm text-gray-600 mb-2">
+                                    {video.duration()}" · "{video.width}"×"{video.height}
+                                </p>
+                                <div class="flex flex-wrap gap-2">
+                                    <button
+                                        class="flex-1 px-3 py-2 text-white bg-blue-600 rounded-md hover:bg-blue-700 text-sm"
+                                        on:click=on_capture
+                                    >"اجعل اللقطة الحالية الغلاف"</button>
+                                    <button
+                                        class="flex-1 px-3 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 text-sm"
+                                        on:click=move |_| {
+                                            delete.dispatch(DeleteEstateVideo { estate_id, id });
+                                        }
+                                    >"حذف"</button>
+                                </div>
+                            </div>
+                        }
+                    }
+                </For>
+            </div>
+            <form on:submit=on_upload class="grid grid-cols-1 md:grid-cols-2 gap-3 place-items-center">
+                <input
+                    class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl text-gray-800"
+                    type="file"
+                    accept="video/mp4, video/quicktime, .mp4, .mov"
+                    name="data"
+                    required
+                />
+                <input
+                    class="w-full px-4 py-2.5 bg-gradient-to-r from-green-500 to-emerald-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg transition-all duration-300 text-center disabled:opacity-50"
+                    type="submit"
+                    disabled=move || progress.get().is_some()
+                    value=move || match percent() {
+                        Some(x) if finish.pending().get() || x == 100 => "جاري فحص الفيديو...".to_string(),
+                        Some(x) => format!("جاري الرفع... {x}%"),
+                        None => "إضافة فيديو".to_string(),
+                    }
+                />
+                <ShowLet some=percent let(x)>
+                    <div class="md:col-span-2 w-full h-2 bg-gray-200 rounded-full overflow-hidden">
+                        <div
+                            class="h-full bg-gradient-to-r from-green-500 to-emerald-500 transition-all duration-300"
+                            style=format!("width: {x}%")
+                        ></div>
+                    </div>
+                </ShowLet>
+            </form>
+        </div>
+    }
+}
+
+/// HTML5 player for the details page.
+#[component]
+pub fn VideoPlayer(video: EstateVideo) -> impl IntoView {
+    view! {
+        <figure>
+            <video
+                class="w-full rounded-2xl bg-black"
+                controls
+                playsinline
+                preload="metadata"
+                src={video.video_url.clone()}
+                poster={video.poster_url.clone()}
+            >
+                "متصفحك لا يدعم تشغيل الفيديو"
+            </video>
+            <figcaption class="text-center text-gray-600 mt-2">{video.duration()}</figcaption>
+        </figure>
+    }
+}
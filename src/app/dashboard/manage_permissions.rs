@@ -0,0 +1,185 @@
+use leptos::prelude::*;
+
+use crate::{
+    LoadingSpinner,
+    auth::{AuthRequired, Level, Permission, PermissionRequired},
+};
+
+#[server]
+async fn get_permission_matrix() -> Result<Vec<(Level, Permission)>, ServerFnError> {
+    use crate::auth::require_permission;
+    require_permission(Permission::RolesManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::permissions::get_role_permissions(&app_state.pool)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn set_permission(
+    level: Level,
+    permission: Permission,
+    granted: bool,
+) -> Result<(), ServerFnError> {
+    use crate::{auth::require_permission, models::AuditEntity};
+    let user_id = require_permission(Permission::RolesManage).await?;
+
+    if level == Level::SuperAdmin {
+        return Err(ServerFnError::new(
+            "SuperAdmin permissions can not be changed".to_string(),
+        ));
+    }
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    if granted {
+        crate::db::permissions::grant_permission(&app_state.pool, &level, permission).await
+    } else {
+        crate::db::permissions::revoke_permission(&app_state.pool, &level, permission).await
+    }
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        if granted { "role.grant" } else { "role.revoke" },
+        AuditEntity::Role,
+        None,
+        None,
+        Some(serde_json::json!({ "level": level, "permission": permission })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect("/dashboard/managePermissions");
+    Ok(())
+}
+
+fn editable_levels() -> Vec<Level> {
+    Level::ALL
+        .into_iter()
+        .filter(|x| *x != Level::SuperAdmin)
+        .collect()
+}
+
+#[component]
+pub fn ManagePermissions() -> impl IntoView {
+    let matrix_res = Resource::new(|| (), move |_| get_permission_matrix());
+    let matrix = move || matrix_res.get().and_then(|x| x.ok());
+
+    view! {
+        <AuthRequired>
+            <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
+                <div class="max-w-6xl mx-auto">
+                    <Titles/>
+                    <PermissionRequired permission=Permission::RolesManage>
+                        <Suspense fallback=LoadingSpinner>
+                            <ShowLet some=matrix let(matrix)>
+                                <MatrixTable matrix/>
+                            </ShowLet>
+                        </Suspense>
+                    </PermissionRequired>
+                    <NavButton/>
+                </div>
+            </div>
+        </AuthRequired>
+    }
+}
+
+#[component]
+fn MatrixTable(matrix: Vec<(Level, Permission)>) -> impl IntoView {
+    let levels = editable_levels();
+    let header = levels
+        .iter()
+        .map(|x| view! { <th class="px-4 py-3 text-center">{x.label()}</th> })
+        .collect_view();
+
+    let rows = Permission::ALL
+        .into_iter()
+        .map(|permission| {
+            let cells = levels
+                .iter()
+                .map(|level| {
+                    let granted = matrix.contains(&(level.clone(), permission));
+                    view! {
+                        <td class="px-4 py-3 text-center">
+                            <ToggleButton level={level.clone()} permission granted/>
+                        </td>
+                    }
+                })
+                .collect_view();
+            view! {
+                <tr class="border-t border-gray-100">
+                    <td class="px-4 py-3 font-semibold text-gray-800">
+                        {permission.label()}
+                        <span class="block text-xs text-gray-400">{permission.code()}</span>
+                    </td>
+                    {cells}
+                </tr>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 overflow-x-auto mb-8">
+            <table class="w-full text-right">
+                <thead>
+                    <tr class="text-gray-600">
+                        <th class="px-4 py-3">"الصلاحية"</th>
+                        {header}
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+fn ToggleButton(level: Level, permission: Permission, granted: bool) -> impl IntoView {
+    let set_permission = ServerAction::<SetPermission>::new();
+    let class = if granted {
+        "w-10 h-10 rounded-lg bg-gradient-to-r from-green-500 to-emerald-500 text-white font-bold shadow-md hover:scale-105 transition-all duration-300"
+    } else {
+        "w-10 h-10 rounded-lg bg-gray-100 text-gray-400 font-bold border-2 border-gray-200 hover:scale-105 transition-all duration-300"
+    };
+    view! {
+        <ActionForm action={set_permission}>
+            <input class="hidden" name="level" value={level.to_string()}/>
+            <input class="hidden" name="permission" value={permission.code()}/>
+            <input class="hidden" name="granted" value={(!granted).to_string()}/>
+            <button type="submit" class={class}>
+                {if granted { "✔" } else { "✖" }}
+            </button>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn NavButton() -> impl IntoView {
+    view! {
+        <div class="flex justify-center gap-4">
+            <a
+                href="/dashboard"
+                class="px-8 py-4 bg-white text-gray-700 font-semibold text-lg rounded-xl shadow-lg hover:shadow-xl hover:scale-105 transition-all duration-300 border-2 border-gray-200 hover:border-blue-300"
+            >
+                "← العودة إلى لوحة التحكم"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn Titles() -> impl IntoView {
+    view! {
+        <div class="text-center mb-12">
+            <h1 class="text-4xl md:text-5xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-4 p-4">
+                "إدارة الصلاحيات"
+            </h1>
+            <p class="text-gray-600 text-lg">"تحديد ما يمكن لكل دور القيام به"</p>
+        </div>
+    }
+}
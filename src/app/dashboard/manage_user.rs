@@ -4,7 +4,7 @@ use uuid::Uuid;
 use crate::{
     LoadingSpinner,
     app::dashboard::get_users_names,
-    auth::{AdminOnly, AuthRequired},
+    auth::{AuthRequired, Permission, PermissionRequired},
 };
 
 pub mod add_user;
@@ -12,12 +12,39 @@ pub mod update_user;
 
 #[server]
 async fn remove_user(target_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Level, Permission, require_level, require_permission},
+        models::{AuditEntity, SecureUser},
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    crate::db::sessions::revoke_user_sessions(&app_state.pool, target_id, None)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
     crate::db::users::delete_user(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.delete",
+        AuditEntity::User,
+        Some(target_id),
+        Some(serde_json::json!(SecureUser::from(target))),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
     leptos_axum::redirect("/dashboard/manageUser");
     Ok(())
 }
@@ -67,7 +94,7 @@ pub fn ManageUser() -> impl IntoView {
 #[component]
 fn UpdateButton(target_id: Uuid) -> impl IntoView {
     view! {
-        <AdminOnly>
+        <PermissionRequired permission=Permission::UsersManage>
         <a
             href={format!("/dashboard/updateUser/{}",target_id)}
             class="px-5 py-2.5 bg-gradient-to-r from-green-500 to-emerald-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg hover:scale-105 transition-all duration-300 flex items-center gap-2"
@@ -77,7 +104,7 @@ fn UpdateButton(target_id: Uuid) -> impl IntoView {
             </svg>
             "تحديث"
         </a>
-        </AdminOnly>
+        </PermissionRequired>
     }
 }
 
@@ -85,7 +112,7 @@ fn UpdateButton(target_id: Uuid) -> impl IntoView {
 fn DeleteButton(target_id: Uuid) -> impl IntoView {
     let remove_user = ServerAction::<RemoveUser>::new();
     view! {
-        <AdminOnly>
+        <PermissionRequired permission=Permission::UsersManage>
         <ActionForm action={remove_user}>
             <input class="hidden" name="target_id" value={target_id.to_string()}/>
             <button
@@ -98,7 +125,7 @@ fn DeleteButton(target_id: Uuid) -> impl IntoView {
                 "حذف"
             </button>
         </ActionForm>
-        </AdminOnly>
+        </PermissionRequired>
     }
 }
 
@@ -106,7 +133,7 @@ fn DeleteButton(target_id: Uuid) -> impl IntoView {
 fn NavButton() -> impl IntoView {
     view! {
         <div class="flex justify-center gap-4">
-            <AdminOnly>
+            <PermissionRequired permission=Permission::UsersManage>
             <a
                 href="/dashboard/addUser"
                 class="group px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl hover:scale-105 transition-all duration-300 flex items-center gap-3"
@@ -116,7 +143,7 @@ fn NavButton() -> impl IntoView {
                 </svg>
                 "إضافة مستخدم جديد"
             </a>
-            </AdminOnly>
+            </PermissionRequired>
 
             <a
                 href="/dashboard"
@@ -1,16 +1,43 @@
 use leptos::prelude::*;
 
-use crate::auth::{AuthRequired, Level};
+use crate::auth::{AuthRequired, Level, password::MIN_PASSWORD_LENGTH};
 
 #[server]
 async fn add_user(name: String, level: Level, password: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, password::check_password_strength, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+    if level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+    check_password_strength(&password, &name).map_err(|e| ServerFnError::new(e.to_string()))?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
     let hashed = password_auth::generate_hash(password);
-    crate::db::users::create_user(&app_state.pool, name, hashed, level)
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let created_id =
+        crate::db::users::create_user(&app_state.pool, name.clone(), hashed, level.clone())
+            .await
+            .map_err(|e| match e {
+                e if crate::db::users::is_name_taken(&e) => {
+                    ServerFnError::new("a user with this name already exists")
+                }
+                e => ServerFnError::new(e.to_string()),
+            })?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.create",
+        AuditEntity::User,
+        Some(created_id),
+        None,
+        Some(serde_json::json!({ "name": name, "level": level })),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
     leptos_axum::redirect("/dashboard/manageUser");
     Ok(())
 }
@@ -18,6 +45,13 @@ async fn add_user(name: String, level: Level, password: String) -> Result<(), Se
 #[component]
 pub fn AddUser() -> impl IntoView {
     let add_user = ServerAction::<AddUser>::new();
+    let error_msg = move || {
+        add_user
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
 
     view! {
         <AuthRequired>
@@ -26,6 +60,11 @@ pub fn AddUser() -> impl IntoView {
                     <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl overflow-hidden border border-gray-100">
                         <Banner/>
                         <div class="p-8">
+                            <ShowLet some=error_msg let(msg)>
+                                <div class="mb-6 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                                </div>
+                            </ShowLet>
                             <ActionForm action={add_user}>
                                 <div class="space-y-6">
                                     <UserNameInput/>
@@ -61,7 +100,7 @@ pub fn UserPasswordInput() -> impl IntoView {
                 id="password"
                 placeholder="أدخل كلمة سر قوية"
                 required
-                minlength="8"
+                minlength=MIN_PASSWORD_LENGTH
             />
         </div>
     }
@@ -83,8 +122,14 @@ pub fn UserLevelInput() -> impl IntoView {
                 id="level"
                 class="w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-purple-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800 font-semibold cursor-pointer"
             >
-                <option value="Admin">"مدير (Admin)"</option>
-                <option value="User">"مستخدم (User)"</option>
+                {Level::ALL
+                    .into_iter()
+                    .map(|x| view! {
+                        <option value={x.to_string()} selected={x == Level::User}>
+                            {format!("{} ({})", x.label(), x)}
+                        </option>
+                    })
+                    .collect_view()}
             </select>
         </div>
     }
@@ -122,7 +167,9 @@ pub fn Warn() -> impl IntoView {
                 <WarnIcon/>
                 <p class="text-sm text-yellow-800">
                     <span class="font-semibold">"تنبيه:"</span>
-                    " تأكد من استخدام كلمة سر قوية وآمنة"
+                    {format!(
+                        " كلمة السر يجب أن تتكون من {MIN_PASSWORD_LENGTH} أحرف على الأقل وتحتوي على حروف وأرقام، وسيُطلب من المستخدم تغييرها عند أول دخول"
+                    )}
                 </p>
             </div>
         </div>
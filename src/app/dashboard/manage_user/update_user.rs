@@ -3,17 +3,46 @@ use leptos_router::hooks::use_params_map;
 use uuid::Uuid;
 
 use crate::app::SecureUser;
-use crate::app::dashboard::get_user_by_id;
+use crate::app::dashboard::{get_user_by_id, sessions::ActiveSessions};
 use crate::auth::{AuthRequired, Level};
 
 #[server]
 async fn update_name(target_id: uuid::Uuid, name: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::users::update_user_name(&app_state.pool, target_id, name)
+    let before = crate::db::users::get_user_by_id(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if before.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    crate::db::users::update_user_name(&app_state.pool, target_id, name.clone())
+        .await
+        .map_err(|e| match e {
+            e if crate::db::users::is_name_taken(&e) => {
+                ServerFnError::new("a user with this name already exists")
+            }
+            e => ServerFnError::new(e.to_string()),
+        })?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::User,
+        target_id,
+        "name",
+        before.name,
+        name,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
     Ok(())
@@ -21,13 +50,42 @@ async fn update_name(target_id: uuid::Uuid, name: String) -> Result<(), ServerFn
 
 #[server]
 async fn update_password(target_id: uuid::Uuid, password: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, password::check_password_strength, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+    check_password_strength(&password, &target.name)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
     let hashed = password_auth::generate_hash(password);
-    crate::db::users::update_user_password(&app_state.pool, target_id, hashed)
+    crate::db::users::update_user_password(&app_state.pool, target_id, hashed, true)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::sessions::revoke_user_sessions(&app_state.pool, target_id, None)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.update_password",
+        AuditEntity::User,
+        Some(target_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
     Ok(())
@@ -35,12 +93,189 @@ async fn update_password(target_id: uuid::Uuid, password: String) -> Result<(),
 
 #[server]
 async fn update_level(target_id: uuid::Uuid, level: Level) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
     let app_state = use_context::<crate::AppState>()
         .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    crate::db::users::update_user_level(&app_state.pool, target_id, level)
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if level == Level::SuperAdmin || target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    crate::db::users::update_user_level(&app_state.pool, target_id, level.clone())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::sessions::revoke_user_sessions(&app_state.pool, target_id, None)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::User,
+        target_id,
+        "level",
+        target.level,
+        level,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
+    Ok(())
+}
+
+/// Seconds left on the target's login lock, `None` when the account is not locked.
+#[server]
+async fn get_login_lock(target_id: uuid::Uuid) -> Result<Option<i64>, ServerFnError> {
+    use crate::{
+        auth::{Permission, require_permission},
+        db::login_attempts::{USERNAME_KIND, get_attempt_state},
+    };
+    require_permission(Permission::UsersView).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let state = get_attempt_state(&app_state.pool, USERNAME_KIND, &target.name)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(Some(state.lock_remaining_secs).filter(|x| *x > 0))
+}
+
+#[server]
+async fn unlock_user(target_id: uuid::Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_level, require_permission},
+        db::login_attempts::{USERNAME_KIND, clear_attempts},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+    clear_attempts(&app_state.pool, USERNAME_KIND, &target.name)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.unlock",
+        AuditEntity::User,
+        Some(target_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
+    Ok(())
+}
+
+/// Whether the target has 2FA enabled and whether it is required for them.
+#[server]
+async fn get_two_factor_state(target_id: uuid::Uuid) -> Result<(bool, bool), ServerFnError> {
+    use crate::auth::{Permission, require_permission};
+    require_permission(Permission::UsersView).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok((target.totp_enabled, target.totp_required))
+}
+
+#[server]
+async fn set_totp_required(target_id: uuid::Uuid, required: bool) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    crate::db::two_factor::set_totp_required(&app_state.pool, target_id, required)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record_change(
+        &app_state.pool,
+        user_id,
+        AuditEntity::User,
+        target_id,
+        "totp_required",
+        target.totp_required,
+        required,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
+    Ok(())
+}
+
+/// Removes the target's TOTP secret and recovery codes, e.g. after a lost phone.
+#[server]
+async fn reset_two_factor(target_id: uuid::Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, require_level, require_permission},
+        models::AuditEntity,
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    crate::db::two_factor::disable_totp(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.reset_totp",
+        AuditEntity::User,
+        Some(target_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
 
     leptos_axum::redirect(&format!("/dashboard/updateUser/{}", target_id));
     Ok(())
@@ -70,9 +305,12 @@ pub fn UpdateUser() -> impl IntoView {
                     let(SecureUser{id,name,level})
                 >
                     <div class="grid grid-cols-1 gap-5 text-center border-5 rounded-lg my-10 mx-5 p-1 md:p-3 lg:p-5">
+                        <UnlockUser id/>
                         <UpdateName id name/>
                         <UpdateLevel id level/>
                         <UpdatePassword id/>
+                        <TwoFactorAdmin id/>
+                        <ActiveSessions target_id=id/>
                     </div>
                 </ShowLet>
             </Suspense>
@@ -81,13 +319,92 @@ pub fn UpdateUser() -> impl IntoView {
     }
 }
 
+#[component]
+fn UnlockUser(id: uuid::Uuid) -> impl IntoView {
+    let unlock_user = ServerAction::<UnlockUser>::new();
+    let lock_res = Resource::new(move || id, get_login_lock);
+    let lock = move || lock_res.get().and_then(|x| x.ok()).flatten();
+    view! {
+        <Suspense>
+            <ShowLet some=lock let(remaining_secs)>
+                <ActionForm action={unlock_user}>
+                    <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+                    <div class="grid grid-cols-1 gap-2 my-10 bg-red-50 border-2 border-red-200 rounded-lg p-4">
+                        <p class="text-red-800 font-semibold">
+                            {format!(
+                                "الحساب مقفل بسبب تكرار محاولات الدخول الفاشلة لمدة {} دقيقة أخرى",
+                                (remaining_secs + 59) / 60,
+                            )}
+                        </p>
+                        <input
+                            class="w-auto px-4 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-2"
+                            type="submit"
+                            value="إلغاء قفل الحساب"
+                        />
+                    </div>
+                </ActionForm>
+            </ShowLet>
+        </Suspense>
+    }
+}
+
+#[component]
+fn TwoFactorAdmin(id: uuid::Uuid) -> impl IntoView {
+    let set_required = ServerAction::<SetTotpRequired>::new();
+    let reset = ServerAction::<ResetTwoFactor>::new();
+    let state_res = Resource::new(move || id, get_two_factor_state);
+    let state = move || state_res.get().and_then(|x| x.ok());
+    let button_class = "w-auto px-4 py-2 text-white bg-blue-600 rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2";
+
+    view! {
+        <Suspense>
+            <ShowLet some=state let((enabled, required))>
+                <div class="grid grid-cols-1 gap-2 my-10">
+                    <p class="font-semibold text-gray-700">
+                        {if enabled { "المصادقة الثنائية مفعّلة" } else { "المصادقة الثنائية غير مفعّلة" }}
+                    </p>
+                    <ActionForm action={set_required}>
+                        <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+                        <input class="hidden" type="text" value={(!required).to_string()} name="required"/>
+                        <input
+                            class=button_class
+                            type="submit"
+                            value={if required { "إلغاء إلزام المصادقة الثنائية" } else { "إلزام المصادقة الثنائية" }}
+                        />
+                    </ActionForm>
+                    <Show when=move || enabled>
+                        <ActionForm action={reset}>
+                            <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
+                            <input
+                                class="w-full px-4 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-2"
+                                type="submit"
+                                value="إعادة تعيين المصادقة الثنائية"
+                            />
+                        </ActionForm>
+                    </Show>
+                </div>
+            </ShowLet>
+        </Suspense>
+    }
+}
+
 #[component]
 fn UpdatePassword(id: uuid::Uuid) -> impl IntoView {
     let update_password = ServerAction::<UpdatePassword>::new();
+    let error_msg = move || {
+        update_password
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
     view! {
         <ActionForm action={update_password}>
             <input class="hidden" type="text" value={id.to_string()} name="target_id"/>
             <div class="grid grid-cols-1 gap-2 my-10">
+                <ShowLet some=error_msg let(msg)>
+                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                </ShowLet>
                 <input
                     class="text-center w-full px-4 py-2 border-2 border-gray-300 rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500 md:border-green-400"
                     type="password"
@@ -116,8 +433,12 @@ fn UpdateLevel(id: uuid::Uuid, level: Level) -> impl IntoView {
                     id="level"
                     class="p-5 border-2 border-gray-300 rounded-lg text-center block text-sm font-bold mb-2 sm:text-base lg:text-xl"
                 >
-                  <option value="Admin" selected={matches!(level,Level::Admin)}>"ادمين"</option>
-                  <option value="User" selected={matches!(level,Level::User)}>"مستخدم"</option>
+                  {Level::ALL
+                      .into_iter()
+                      .map(|x| view! {
+                          <option value={x.to_string()} selected={x == level}>{x.label()}</option>
+                      })
+                      .collect_view()}
                 </select>
                 <input
                     class="w-auto px-4 py-2 text-white bg-blue-600 rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2"
@@ -0,0 +1,588 @@
+use leptos::prelude::*;
+use leptos_router::components::Redirect;
+
+use crate::{
+    LoadingSpinner,
+    app::{SecureUser, dashboard::sessions::ActiveSessions},
+    auth::password::MIN_PASSWORD_LENGTH,
+    models::{TotpEnrollment, TwoFactorStatus},
+};
+
+#[server]
+async fn get_my_account() -> Result<(SecureUser, bool), ServerFnError> {
+    use crate::auth::require_login;
+    let user_id = require_login().await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let must_change_password = user.must_change_password;
+
+    Ok((SecureUser::from(user), must_change_password))
+}
+
+#[server]
+async fn change_own_password(
+    current_password: String,
+    new_password: String,
+    confirm_password: String,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{
+            password::check_password_strength, require_login, session_from_context,
+            set_must_change_password,
+        },
+        models::AuditEntity,
+    };
+    let user_id = require_login().await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    if password_auth::verify_password(&current_password, &user.password).is_err() {
+        return Err(ServerFnError::new("كلمة السر الحالية غير صحيحة"));
+    }
+    if new_password != confirm_password {
+        return Err(ServerFnError::new("كلمتا السر الجديدتان غير متطابقتين"));
+    }
+    if new_password == current_password {
+        return Err(ServerFnError::new(
+            "كلمة السر الجديدة يجب أن تختلف عن الحالية",
+        ));
+    }
+    check_password_strength(&new_password, &user.name)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let hashed = password_auth::generate_hash(new_password);
+    crate::db::users::update_user_password(&app_state.pool, user_id, hashed, false)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let session = session_from_context()?;
+    let current = session.id().map(|x| x.to_string());
+    crate::db::sessions::revoke_user_sessions(&app_state.pool, user_id, current.as_deref())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    set_must_change_password(session, false)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.change_password",
+        AuditEntity::User,
+        Some(user_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect("/dashboard");
+    Ok(())
+}
+
+#[server]
+async fn get_two_factor_status() -> Result<TwoFactorStatus, ServerFnError> {
+    use crate::auth::{
+        require_login, session_from_context,
+        two_factor::{
+            NEW_RECOVERY_CODES_KEY, PENDING_TOTP_SECRET_KEY, generate_secret, otpauth_uri, qr_svg,
+        },
+    };
+    let user_id = require_login().await?;
+    let session = session_from_context()?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    let enrollment = if user.totp_enabled {
+        None
+    } else {
+        let pending = session
+            .get::<String>(PENDING_TOTP_SECRET_KEY)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let secret = match pending {
+            Some(secret) => secret,
+            None => {
+                let secret = generate_secret();
+                session
+                    .insert(PENDING_TOTP_SECRET_KEY, &secret)
+                    .await
+                    .map_err(|e| ServerFnError::new(e.to_string()))?;
+                secret
+            }
+        };
+        let uri = otpauth_uri(&secret, &user.name).map_err(ServerFnError::new)?;
+        let qr_svg = qr_svg(&uri).map_err(ServerFnError::new)?;
+        Some(TotpEnrollment {
+            secret,
+            uri,
+            qr_svg,
+        })
+    };
+
+    let new_recovery_codes = session
+        .remove::<Vec<String>>(NEW_RECOVERY_CODES_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .unwrap_or_default();
+    let remaining_recovery_codes =
+        crate::db::two_factor::count_unused_recovery_codes(&app_state.pool, user_id)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    Ok(TwoFactorStatus {
+        enabled: user.totp_enabled,
+        required: user.totp_required,
+        remaining_recovery_codes,
+        enrollment,
+        new_recovery_codes,
+    })
+}
+
+/// Replaces the caller's recovery codes and keeps the plain codes in the
+/// session so the next status fetch shows them once.
+#[cfg(feature = "ssr")]
+async fn issue_recovery_codes(
+    pool: &sqlx::PgPool,
+    session: tower_sessions::Session,
+    user_id: uuid::Uuid,
+) -> Result<(), ServerFnError> {
+    use crate::auth::two_factor::{
+        NEW_RECOVERY_CODES_KEY, generate_recovery_codes, hash_recovery_codes,
+    };
+
+    let codes = generate_recovery_codes();
+    crate::db::two_factor::replace_recovery_codes(pool, user_id, &hash_recovery_codes(&codes))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    session
+        .insert(NEW_RECOVERY_CODES_KEY, codes)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn confirm_totp_enrollment(code: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{
+            MY_ACCOUNT_PATH, require_login, session_from_context, set_must_enroll_totp,
+            two_factor::{PENDING_TOTP_SECRET_KEY, verify_totp},
+        },
+        models::AuditEntity,
+    };
+    let user_id = require_login().await?;
+    let session = session_from_context()?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let secret = session
+        .get::<String>(PENDING_TOTP_SECRET_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?
+        .ok_or_else(|| ServerFnError::new("انتهت صلاحية عملية التفعيل، برجاء إعادة المحاولة"))?;
+    if !verify_totp(&secret, &user.name, &code) {
+        return Err(ServerFnError::new("رمز التحقق غير صحيح"));
+    }
+
+    crate::db::two_factor::enable_totp(&app_state.pool, user_id, &secret)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    issue_recovery_codes(&app_state.pool, session.clone(), user_id).await?;
+    session
+        .remove::<String>(PENDING_TOTP_SECRET_KEY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    set_must_enroll_totp(session, false)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.enable_totp",
+        AuditEntity::User,
+        Some(user_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(MY_ACCOUNT_PATH);
+    Ok(())
+}
+
+#[server]
+async fn regenerate_recovery_codes(code: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{MY_ACCOUNT_PATH, require_login, session_from_context, two_factor::verify_totp},
+        models::AuditEntity,
+    };
+    let user_id = require_login().await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let verified = user
+        .totp_secret
+        .as_deref()
+        .is_some_and(|secret| verify_totp(secret, &user.name, &code));
+    if !verified {
+        return Err(ServerFnError::new("رمز التحقق غير صحيح"));
+    }
+
+    issue_recovery_codes(&app_state.pool, session_from_context()?, user_id).await?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.regenerate_recovery_codes",
+        AuditEntity::User,
+        Some(user_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(MY_ACCOUNT_PATH);
+    Ok(())
+}
+
+#[server]
+async fn disable_two_factor(code: String) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{MY_ACCOUNT_PATH, require_login, two_factor::verify_totp},
+        models::AuditEntity,
+    };
+    let user_id = require_login().await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let user = crate::db::users::get_user_by_id(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if user.totp_required {
+        return Err(ServerFnError::new(
+            "المصادقة الثنائية إلزامية لحسابك ولا يمكن إيقافها",
+        ));
+    }
+    let verified = user
+        .totp_secret
+        .as_deref()
+        .is_some_and(|secret| verify_totp(secret, &user.name, &code));
+    if !verified {
+        return Err(ServerFnError::new("رمز التحقق غير صحيح"));
+    }
+
+    crate::db::two_factor::disable_totp(&app_state.pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.disable_totp",
+        AuditEntity::User,
+        Some(user_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(MY_ACCOUNT_PATH);
+    Ok(())
+}
+
+#[component]
+pub fn MyAccount() -> impl IntoView {
+    let account_res = Resource::new(|| (), |_| get_my_account());
+    let logged_out = move || account_res.get().is_some_and(|x| x.is_err());
+    let account = move || account_res.get().and_then(|x| x.ok());
+
+    view! {
+        <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
+            <div class="max-w-2xl mx-auto">
+                <Suspense fallback=LoadingSpinner>
+                    <Show when=logged_out>
+                        <Redirect path="/login"/>
+                    </Show>
+                    <ShowLet some=account let((SecureUser { id, name, level }, must_change_password))>
+                        <Titles name level=level.label()/>
+                        <Show when=move || must_change_password>
+                            <div class="mb-6 bg-yellow-50 border-l-4 border-yellow-500 p-4 rounded-lg">
+                                <p class="text-sm text-yellow-800 font-semibold">
+                                    "يجب تغيير كلمة السر قبل متابعة استخدام لوحة التحكم"
+                                </p>
+                            </div>
+                        </Show>
+                        <ChangePassword/>
+                        <TwoFactor/>
+                        <ActiveSessions target_id=id/>
+                    </ShowLet>
+                </Suspense>
+                <NavButton/>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ChangePassword() -> impl IntoView {
+    let change_password = ServerAction::<ChangeOwnPassword>::new();
+    let error_msg = move || {
+        change_password
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+    let input_class = "w-full px-5 py-4 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent focus:bg-white transition-all duration-300 text-gray-800";
+    let label_class = "block text-gray-700 font-bold mb-3 text-lg";
+
+    view! {
+        <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl p-8 border border-gray-100 mb-8">
+            <ShowLet some=error_msg let(msg)>
+                <div class="mb-6 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                    <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                </div>
+            </ShowLet>
+            <ActionForm action={change_password}>
+                <div class="space-y-6">
+                    <div>
+                        <label class=label_class for="current_password">"كلمة السر الحالية"</label>
+                        <input
+                            class=input_class
+                            type="password"
+                            name="current_password"
+                            id="current_password"
+                            required
+                        />
+                    </div>
+                    <div>
+                        <label class=label_class for="new_password">"كلمة السر الجديدة"</label>
+                        <input
+                            class=input_class
+                            type="password"
+                            name="new_password"
+                            id="new_password"
+                            required
+                            minlength=MIN_PASSWORD_LENGTH
+                        />
+                        <p class="text-sm text-gray-500 mt-2">
+                            {format!(
+                                "{MIN_PASSWORD_LENGTH} أحرف على الأقل، تحتوي على حروف وأرقام ولا تحتوي على اسم المستخدم"
+                            )}
+                        </p>
+                    </div>
+                    <div>
+                        <label class=label_class for="confirm_password">"تأكيد كلمة السر الجديدة"</label>
+                        <input
+                            class=input_class
+                            type="password"
+                            name="confirm_password"
+                            id="confirm_password"
+                            required
+                            minlength=MIN_PASSWORD_LENGTH
+                        />
+                    </div>
+                    <button
+                        class="w-full px-8 py-4 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold text-lg rounded-xl shadow-lg hover:shadow-2xl hover:scale-105 active:scale-95 transition-all duration-300"
+                        type="submit"
+                    >
+                        "تغيير كلمة السر"
+                    </button>
+                </div>
+            </ActionForm>
+        </div>
+    }
+}
+
+#[component]
+fn TwoFactor() -> impl IntoView {
+    let status_res = Resource::new(|| (), |_| get_two_factor_status());
+    let status = move || status_res.get().and_then(|x| x.ok());
+
+    view! {
+        <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl p-8 border border-gray-100 mb-8">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">"المصادقة الثنائية"</h2>
+            <Suspense fallback=LoadingSpinner>
+                <ShowLet some=status let(status)>
+                    <RecoveryCodes codes=status.new_recovery_codes/>
+                    {match status.enrollment {
+                        Some(enrollment) => view! { <Enroll enrollment required=status.required/> }.into_any(),
+                        None => view! {
+                            <Enabled
+                                required=status.required
+                                remaining_recovery_codes=status.remaining_recovery_codes
+                            />
+                        }
+                        .into_any(),
+                    }}
+                </ShowLet>
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn Enroll(enrollment: TotpEnrollment, required: bool) -> impl IntoView {
+    let confirm = ServerAction::<ConfirmTotpEnrollment>::new();
+    let error_msg = move || {
+        confirm
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+    let TotpEnrollment {
+        secret,
+        uri,
+        qr_svg,
+    } = enrollment;
+    let href = uri.clone();
+
+    view! {
+        <Show when=move || required>
+            <div class="mb-6 bg-yellow-50 border-l-4 border-yellow-500 p-4 rounded-lg">
+                <p class="text-sm text-yellow-800 font-semibold">
+                    "المدير جعل المصادقة الثنائية إلزامية لحسابك، فعّلها للمتابعة"
+                </p>
+            </div>
+        </Show>
+        <p class="text-gray-600 mb-4">
+            "امسح الرمز بتطبيق المصادقة (مثل Google Authenticator) ثم أدخل الرمز المكون من 6 أرقام لتأكيد التفعيل"
+        </p>
+        <div class="flex justify-center mb-4" inner_html=qr_svg></div>
+        <p class="text-sm text-gray-500 text-center mb-2">"أو أدخل المفتاح يدوياً:"</p>
+        <p class="font-mono text-center text-gray-800 break-all mb-2" dir="ltr">{secret}</p>
+        <a class="block text-sm text-blue-600 text-center break-all mb-6" dir="ltr" href=href>{uri}</a>
+        <ActionForm action={confirm}>
+            <CodeInput error_msg=Signal::derive(error_msg) label="تفعيل المصادقة الثنائية"/>
+        </ActionForm>
+    }
+}
+
+#[component]
+fn Enabled(required: bool, remaining_recovery_codes: i64) -> impl IntoView {
+    let regenerate = ServerAction::<RegenerateRecoveryCodes>::new();
+    let regenerate_error = move || {
+        regenerate
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+    let disable = ServerAction::<DisableTwoFactor>::new();
+    let disable_error = move || {
+        disable
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+
+    view! {
+        <p class="text-green-700 font-semibold mb-2">"المصادقة الثنائية مفعّلة"</p>
+        <p class="text-gray-600 mb-6">
+            {format!("رموز الاسترداد المتبقية: {remaining_recovery_codes}")}
+        </p>
+        <ActionForm action={regenerate}>
+            <CodeInput error_msg=Signal::derive(regenerate_error) label="إنشاء رموز استرداد جديدة"/>
+        </ActionForm>
+        <Show when=move || !required>
+            <ActionForm action={disable}>
+                <CodeInput error_msg=Signal::derive(disable_error) label="إيقاف المصادقة الثنائية"/>
+            </ActionForm>
+        </Show>
+    }
+}
+
+#[component]
+fn RecoveryCodes(codes: Vec<String>) -> impl IntoView {
+    let show = !codes.is_empty();
+    view! {
+        <Show when=move || show>
+            <div class="mb-6 bg-blue-50 border-l-4 border-blue-500 p-4 rounded-lg">
+                <p class="text-sm text-blue-800 font-semibold mb-3">
+                    "احفظ رموز الاسترداد في مكان آمن، لن تظهر مرة أخرى وكل رمز يُستخدم مرة واحدة فقط"
+                </p>
+                <ul class="grid grid-cols-2 gap-2 font-mono text-gray-800" dir="ltr">
+                    {codes.clone().into_iter().map(|x| view! { <li>{x}</li> }).collect_view()}
+                </ul>
+            </div>
+        </Show>
+    }
+}
+
+/// Body of a form posting a single TOTP `code`.
+#[component]
+fn CodeInput(error_msg: Signal<Option<String>>, label: &'static str) -> impl IntoView {
+    view! {
+        <div class="grid grid-cols-1 gap-2 mb-4">
+            <ShowLet some=error_msg let(msg)>
+                <p class="text-sm text-red-800 font-semibold">{msg}</p>
+            </ShowLet>
+            <input
+                class="w-full px-5 py-3 bg-gray-50 border-2 border-gray-200 rounded-xl text-center tracking-widest text-gray-800"
+                type="text"
+                name="code"
+                autocomplete="one-time-code"
+                placeholder="رمز التحقق"
+                dir="ltr"
+                required
+            />
+            <button
+                class="w-full px-6 py-3 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold rounded-xl shadow-lg hover:shadow-xl transition-all duration-300"
+                type="submit"
+            >
+                {label}
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn NavButton() -> impl IntoView {
+    view! {
+        <div class="flex justify-center gap-4">
+            <a
+                href="/dashboard"
+                class="px-8 py-4 bg-white text-gray-700 font-semibold text-lg rounded-xl shadow-lg hover:shadow-xl hover:scale-105 transition-all duration-300 border-2 border-gray-200 hover:border-blue-300"
+            >
+                "← العودة إلى لوحة التحكم"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn Titles(name: String, level: &'static str) -> impl IntoView {
+    view! {
+        <div class="text-center mb-12">
+            <h1 class="text-4xl md:text-5xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-4 p-4">
+                "حسابي"
+            </h1>
+            <p class="text-gray-600 text-lg">{format!("{name} - {level}")}</p>
+        </div>
+    }
+}
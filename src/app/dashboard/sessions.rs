@@ -0,0 +1,194 @@
+use leptos::prelude::*;
+use uuid::Uuid;
+
+use crate::models::UserSession;
+
+/// Users may always manage their own sessions; other users' sessions need
+/// `permission`, and a `SuperAdmin`'s need a `SuperAdmin`.
+#[cfg(feature = "ssr")]
+async fn authorize_sessions_access(
+    target_id: Uuid,
+    permission: crate::auth::Permission,
+) -> Result<Uuid, ServerFnError> {
+    use crate::auth::{Level, require_level, require_login, require_permission};
+
+    let user_id = require_login().await?;
+    if user_id == target_id {
+        return Ok(user_id);
+    }
+    require_permission(permission).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+    let target = crate::db::users::get_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+    Ok(user_id)
+}
+
+#[cfg(feature = "ssr")]
+fn sessions_page(user_id: Uuid, target_id: Uuid) -> String {
+    if user_id == target_id {
+        crate::auth::MY_ACCOUNT_PATH.to_string()
+    } else {
+        format!("/dashboard/updateUser/{}", target_id)
+    }
+}
+
+#[server]
+async fn get_sessions(target_id: Uuid) -> Result<Vec<UserSession>, ServerFnError> {
+    use crate::auth::{Permission, session_from_context};
+    authorize_sessions_access(target_id, Permission::UsersView).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+    let current = session_from_context()?.id().map(|x| x.to_string());
+
+    crate::db::sessions::get_user_sessions(&app_state.pool, target_id, current.as_deref())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn revoke_session(target_id: Uuid, id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{auth::Permission, models::AuditEntity};
+    let user_id = authorize_sessions_access(target_id, Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::sessions::revoke_user_session(&app_state.pool, target_id, id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.revoke_session",
+        AuditEntity::User,
+        Some(target_id),
+        Some(serde_json::json!({ "session": id })),
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&sessions_page(user_id, target_id));
+    Ok(())
+}
+
+/// Revokes all of the target's sessions, except the caller's own one when
+/// users sign themselves out everywhere else.
+#[server]
+async fn revoke_all_sessions(target_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Permission, session_from_context},
+        models::AuditEntity,
+    };
+    let user_id = authorize_sessions_access(target_id, Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+    let keep = if user_id == target_id {
+        session_from_context()?.id().map(|x| x.to_string())
+    } else {
+        None
+    };
+
+    crate::db::sessions::revoke_user_sessions(&app_state.pool, target_id, keep.as_deref())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.revoke_sessions",
+        AuditEntity::User,
+        Some(target_id),
+        None,
+        None,
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect(&sessions_page(user_id, target_id));
+    Ok(())
+}
+
+#[component]
+pub fn ActiveSessions(target_id: Uuid) -> impl IntoView {
+    let revoke_all = ServerAction::<RevokeAllSessions>::new();
+    let sessions_res = Resource::new(move || target_id, get_sessions);
+    let sessions = move || sessions_res.get().and_then(|x| x.ok()).unwrap_or_default();
+
+    view! {
+        <div class="bg-white/90 backdrop-blur-lg rounded-3xl shadow-2xl p-8 border border-gray-100 mb-8">
+            <h2 class="text-2xl font-bold text-gray-800 mb-6">"الجلسات النشطة"</h2>
+            <Suspense>
+                <div class="space-y-3 mb-6">
+                    <For
+                        each={sessions}
+                        key=|x| x.id
+                        let(session)
+                    >
+                        <SessionRow target_id session/>
+                    </For>
+                </div>
+            </Suspense>
+            <ActionForm action={revoke_all}>
+                <input class="hidden" type="text" value={target_id.to_string()} name="target_id"/>
+                <button
+                    class="w-full px-6 py-3 bg-gradient-to-r from-red-500 to-pink-500 text-white font-bold rounded-xl shadow-lg hover:shadow-xl transition-all duration-300"
+                    type="submit"
+                >
+                    "إنهاء جميع الجلسات الأخرى"
+                </button>
+            </ActionForm>
+        </div>
+    }
+}
+
+#[component]
+fn SessionRow(target_id: Uuid, session: UserSession) -> impl IntoView {
+    let revoke = ServerAction::<RevokeSession>::new();
+    let UserSession {
+        id,
+        user_agent,
+        ip,
+        created_at,
+        last_seen_at,
+        current,
+    } = session;
+
+    view! {
+        <div class="flex items-center justify-between gap-4 border-2 border-gray-100 rounded-xl p-4">
+            <div class="text-sm text-gray-700 space-y-1">
+                <p class="font-semibold text-gray-800 break-all" dir="ltr">
+                    {user_agent.unwrap_or_else(|| "—".to_string())}
+                </p>
+                <p>{format!("عنوان IP: {}", ip.unwrap_or_default())}</p>
+                <p>{format!("بدأت: {created_at}")}</p>
+                <p>{format!("آخر نشاط: {last_seen_at}")}</p>
+            </div>
+            {if current {
+                view! { <span class="text-green-700 font-semibold whitespace-nowrap">"الجلسة الحالية"</span> }
+                    .into_any()
+            } else {
+                view! {
+                    <ActionForm action={revoke}>
+                        <input class="hidden" type="text" value={target_id.to_string()} name="target_id"/>
+                        <input class="hidden" type="text" value={id.to_string()} name="id"/>
+                        <button
+                            class="px-4 py-2 text-white bg-red-600 rounded-md hover:bg-red-700 whitespace-nowrap"
+                            type="submit"
+                        >
+                            "إنهاء"
+                        </button>
+                    </ActionForm>
+                }
+                .into_any()
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,277 @@
+use leptos::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    LoadingSpinner,
+    auth::{AuthRequired, Permission, PermissionRequired},
+    models::{DeletedEstate, DeletedUser},
+};
+
+/// Deleted estates the caller may restore.
+#[server]
+async fn get_deleted_estates() -> Result<Vec<DeletedEstate>, ServerFnError> {
+    use crate::auth::require_permission;
+    let user_id = require_permission(Permission::EstatesDelete).await?;
+    let manage_all = require_permission(Permission::EstatesManageAll)
+        .await
+        .is_ok();
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::estates::get_deleted_estates(
+        &app_state.pool,
+        crate::trash::retention_days(),
+        (!manage_all).then_some(user_id),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn get_deleted_users() -> Result<Vec<DeletedUser>, ServerFnError> {
+    use crate::auth::require_permission;
+    require_permission(Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    crate::db::users::get_deleted_users(&app_state.pool, crate::trash::retention_days())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server]
+async fn restore_estate(target_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{auth::require_permission, models::AuditEntity};
+    let user_id = require_permission(Permission::EstatesDelete).await?;
+    let manage_all = require_permission(Permission::EstatesManageAll)
+        .await
+        .is_ok();
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let restored = crate::db::estates::restore_estate(
+        &app_state.pool,
+        target_id,
+        (!manage_all).then_some(user_id),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if !restored {
+        return Err(ServerFnError::new("could not find estate in the trash"));
+    }
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "estate.restore",
+        AuditEntity::Estate,
+        Some(target_id),
+        None,
+        Some(serde_json::json!(estate)),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect("/dashboard/trash");
+    Ok(())
+}
+
+#[server]
+async fn restore_user(target_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::{Level, require_level, require_permission},
+        models::{AuditEntity, SecureUser},
+    };
+    let user_id = require_permission(Permission::UsersManage).await?;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let target = crate::db::users::get_deleted_user_by_id(&app_state.pool, target_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if target.level == Level::SuperAdmin {
+        require_level(Level::SuperAdmin).await?;
+    }
+
+    // The name is not freed up for the restored user: whoever took it
+    // meanwhile keeps it, and one of the two has to be renamed first.
+    crate::db::users::restore_user(&app_state.pool, target_id)
+        .await
+        .map_err(|e| match e {
+            e if crate::db::users::is_name_taken(&e) => ServerFnError::new(format!(
+                "another user is already named {}; rename them before restoring",
+                target.name
+            )),
+            e => ServerFnError::new(e.to_string()),
+        })?;
+    crate::db::audit::record(
+        &app_state.pool,
+        user_id,
+        "user.restore",
+        AuditEntity::User,
+        Some(target_id),
+        None,
+        Some(serde_json::json!(SecureUser::from(target))),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    leptos_axum::redirect("/dashboard/trash");
+    Ok(())
+}
+
+#[component]
+pub fn Trash() -> impl IntoView {
+    view! {
+        <AuthRequired>
+            <div class="min-h-screen bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 py-12 px-4">
+                <div class="max-w-5xl mx-auto">
+                    <Titles/>
+                    <PermissionRequired permission=Permission::EstatesDelete>
+                        <DeletedEstates/>
+                    </PermissionRequired>
+                    <PermissionRequired permission=Permission::UsersManage>
+                        <DeletedUsers/>
+                    </PermissionRequired>
+                    <NavButton/>
+                </div>
+            </div>
+        </AuthRequired>
+    }
+}
+
+#[component]
+fn DeletedEstates() -> impl IntoView {
+    let restore = ServerAction::<RestoreEstate>::new();
+    let estates_res = Resource::new(move || restore.version().get(), |_| get_deleted_estates());
+    let estates = move || estates_res.get().and_then(|x| x.ok()).unwrap_or_default();
+
+    view! {
+        <Section title="العقارات المحذوفة">
+            <Suspense fallback=LoadingSpinner>
+                <Show when=move || !estates().is_empty() fallback=|| view! { <Empty/> }>
+                    <For
+                        each={estates}
+                        key=|x| x.id
+                        let(DeletedEstate { id, name, address, deleted_at, purge_at })
+                    >
+                        <Entry name subtitle=address deleted_at purge_at>
+                            <ActionForm action={restore}>
+                                <input class="hidden" name="target_id" value={id.to_string()}/>
+                                <RestoreButton/>
+                            </ActionForm>
+                        </Entry>
+                    </For>
+                </Show>
+            </Suspense>
+        </Section>
+    }
+}
+
+#[component]
+fn DeletedUsers() -> impl IntoView {
+    let restore = ServerAction::<RestoreUser>::new();
+    let users_res = Resource::new(move || restore.version().get(), |_| get_deleted_users());
+    let users = move || users_res.get().and_then(|x| x.ok()).unwrap_or_default();
+
+    view! {
+        <Section title="المستخدمون المحذوفون">
+            <Suspense fallback=LoadingSpinner>
+                <Show when=move || !users().is_empty() fallback=|| view! { <Empty/> }>
+                    <For
+                        each={users}
+                        key=|x| x.id
+                        let(DeletedUser { id, name, level, deleted_at, purge_at })
+                    >
+                        <Entry name subtitle=level.label().to_string() deleted_at purge_at>
+                            <ActionForm action={restore}>
+                                <input class="hidden" name="target_id" value={id.to_string()}/>
+                                <RestoreButton/>
+                            </ActionForm>
+                        </Entry>
+                    </For>
+                </Show>
+            </Suspense>
+        </Section>
+    }
+}
+
+#[component]
+fn Section(title: &'static str, children: Children) -> impl IntoView {
+    view! {
+        <div class="bg-white/80 backdrop-blur-sm rounded-xl shadow-lg p-6 border border-gray-100 mb-8">
+            <h2 class="text-2xl font-bold text-gray-800 mb-4">{title}</h2>
+            <div class="space-y-4">{children()}</div>
+        </div>
+    }
+}
+
+#[component]
+fn Entry(
+    name: String,
+    subtitle: String,
+    deleted_at: String,
+    purge_at: String,
+    children: Children,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-wrap items-center justify-between gap-4 border-t border-gray-100 pt-4">
+            <div>
+                <h3 class="text-lg font-bold text-gray-800">{name}</h3>
+                <p class="text-sm text-gray-500">{subtitle}</p>
+                <p class="text-sm text-gray-600">"حُذف في "{deleted_at}</p>
+                <p class="text-sm text-red-600 font-semibold">"يُحذف نهائياً في "{purge_at}</p>
+            </div>
+            {children()}
+        </div>
+    }
+}
+
+#[component]
+fn RestoreButton() -> impl IntoView {
+    view! {
+        <button
+            type="submit"
+            class="px-5 py-2.5 bg-gradient-to-r from-green-500 to-emerald-500 text-white font-semibold rounded-lg shadow-md hover:shadow-lg hover:scale-105 transition-all duration-300"
+        >
+            "استعادة"
+        </button>
+    }
+}
+
+#[component]
+fn Empty() -> impl IntoView {
+    view! { <p class="text-gray-500">"سلة المحذوفات فارغة"</p> }
+}
+
+#[component]
+fn NavButton() -> impl IntoView {
+    view! {
+        <div class="flex justify-center gap-4">
+            <a
+                href="/dashboard"
+                class="px-8 py-4 bg-white text-gray-700 font-semibold text-lg rounded-xl shadow-lg hover:shadow-xl hover:scale-105 transition-all duration-300 border-2 border-gray-200 hover:border-blue-300"
+            >
+                "← العودة إلى لوحة التحكم"
+            </a>
+        </div>
+    }
+}
+
+#[component]
+fn Titles() -> impl IntoView {
+    view! {
+        <div class="text-center mb-12">
+            <h1 class="text-4xl md:text-5xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-4 p-4">
+                "سلة المحذوفات"
+            </h1>
+            <p class="text-gray-600 text-lg">"استعادة العقارات والمستخدمين المحذوفين قبل حذفهم نهائياً"</p>
+        </div>
+    }
+}
@@ -1,8 +1,66 @@
 use leptos::prelude::*;
 
+/// Rejects the attempt while the client IP or the username is throttled,
+/// and otherwise counts it as a failure before the credentials are even
+/// checked. Both rows stay locked until the count is in, so concurrent
+/// guesses queue up instead of all passing the same check; a successful
+/// login clears the count again.
+#[cfg(feature = "ssr")]
+async fn begin_login_attempt(
+    pool: &sqlx::PgPool,
+    ip: &str,
+    username: &str,
+) -> Result<(), ServerFnError> {
+    use crate::{
+        auth::throttle::{IP_POLICY, USERNAME_POLICY},
+        db::login_attempts::{IP_KIND, USERNAME_KIND, lock_attempt_state, record_failure},
+    };
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let ip_state = lock_attempt_state(&mut *tx, IP_KIND, ip)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let username_state = lock_attempt_state(&mut *tx, USERNAME_KIND, username)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    IP_POLICY
+        .check(&ip_state)
+        .and_then(|_| USERNAME_POLICY.check(&username_state))
+        .map_err(|e| ServerFnError::new(e.message()))?;
+
+    record_failure(&mut *tx, IP_KIND, ip, &IP_POLICY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    record_failure(&mut *tx, USERNAME_KIND, username, &USERNAME_POLICY)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[cfg(feature = "ssr")]
+async fn clear_login_failures(
+    pool: &sqlx::PgPool,
+    ip: &str,
+    username: &str,
+) -> Result<(), ServerFnError> {
+    use crate::db::login_attempts::{IP_KIND, USERNAME_KIND, clear_attempts};
+
+    clear_attempts(pool, IP_KIND, ip)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    clear_attempts(pool, USERNAME_KIND, username)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
 #[server]
 async fn login(username: String, password: String) -> Result<(), ServerFnError> {
-    use crate::auth::set_user_session;
+    use crate::auth::{LOGIN_VERIFY_PATH, PENDING_LOGIN_KEY, client_ip, complete_login};
     use tower_sessions::Session;
 
     let app_state = use_context::<crate::AppState>()
@@ -16,21 +74,91 @@ async fn login(username: String, password: String) -> Result<(), ServerFnError>
         .ok_or_else(|| ServerFnError::new("No session found".to_string()))?
         .clone();
 
+    let pool = &app_state.pool;
+    let ip = client_ip(&parts);
+    begin_login_attempt(pool, &ip, &username).await?;
+
     let user_error = "اسم المستخدم أو كلمة السر غير صحيحة".to_string();
 
-    let user = crate::db::users::get_user_by_name(&app_state.pool, &username)
+    let user = crate::db::users::get_user_by_name(pool, &username)
         .await
-        .map_err(|_| ServerFnError::new(&user_error))?;
-
-    if password_auth::verify_password(&password, &user.password).is_err() {
+        .ok()
+        .filter(|user| password_auth::verify_password(&password, &user.password).is_ok());
+    let Some(user) = user else {
         return Err(ServerFnError::Args(user_error));
+    };
+
+    // The attempt stays counted until the second step succeeds too.
+    if user.totp_enabled {
+        session
+            .insert(PENDING_LOGIN_KEY, user.id.to_string())
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        leptos_axum::redirect(LOGIN_VERIFY_PATH);
+        return Ok(());
     }
 
-    set_user_session(session, user.id, user.level)
+    clear_login_failures(pool, &ip, &username).await?;
+    let next = complete_login(session, &parts, &user).await?;
+
+    leptos_axum::redirect(next);
+    Ok(())
+}
+
+/// Second login step for accounts with 2FA, accepting either the current
+/// TOTP code or one of the unused recovery codes.
+#[server]
+async fn verify_login(code: String) -> Result<(), ServerFnError> {
+    use crate::auth::{
+        PENDING_LOGIN_KEY, client_ip, complete_login,
+        two_factor::{redeem_recovery_code, verify_totp},
+    };
+    use tower_sessions::Session;
+    use uuid::Uuid;
+
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+
+    let parts = use_context::<axum::http::request::Parts>()
+        .ok_or_else(|| ServerFnError::new("No request parts found".to_string()))?;
+    let session = parts
+        .extensions
+        .get::<Session>()
+        .ok_or_else(|| ServerFnError::new("No session found".to_string()))?
+        .clone();
+
+    let expired_error = "انتهت صلاحية محاولة الدخول، برجاء تسجيل الدخول مرة أخرى".to_string();
+    let user_id = session
+        .get::<String>(PENDING_LOGIN_KEY)
         .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
+        .ok()
+        .flatten()
+        .and_then(|x| Uuid::parse_str(&x).ok())
+        .ok_or_else(|| ServerFnError::new(&expired_error))?;
+
+    let pool = &app_state.pool;
+    let user = crate::db::users::get_user_by_id(pool, user_id)
+        .await
+        .map_err(|_| ServerFnError::new(&expired_error))?;
+    let ip = client_ip(&parts);
+    begin_login_attempt(pool, &ip, &user.name).await?;
+
+    let totp_ok = user
+        .totp_secret
+        .as_deref()
+        .is_some_and(|secret| verify_totp(secret, &user.name, &code));
+    let verified = totp_ok
+        || redeem_recovery_code(pool, user.id, &code)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    if !verified {
+        return Err(ServerFnError::Args("رمز التحقق غير صحيح".to_string()));
+    }
+
+    clear_login_failures(pool, &ip, &user.name).await?;
+    let next = complete_login(session, &parts, &user).await?;
 
-    leptos_axum::redirect(&format!("/dashboard"));
+    leptos_axum::redirect(next);
     Ok(())
 }
 
@@ -129,6 +257,73 @@ pub fn Login() -> impl IntoView {
     }
 }
 
+#[component]
+pub fn LoginVerify() -> impl IntoView {
+    let verify_ac = ServerAction::<VerifyLogin>::new();
+    let error_msg = move || {
+        verify_ac
+            .value()
+            .get()
+            .and_then(|res| res.err())
+            .map(|e| e.to_string())
+    };
+
+    view! {
+        <div class="min-h-screen flex items-center justify-center bg-gradient-to-br from-blue-50 via-purple-50 to-pink-50 p-4">
+            <div class="w-full max-w-md">
+                <div class="bg-white/80 backdrop-blur-xl rounded-2xl shadow-2xl p-8 border border-white/20">
+                    <div class="text-center mb-8">
+                        <h1 class="text-3xl font-bold bg-gradient-to-r from-blue-600 to-purple-600 bg-clip-text text-transparent mb-2">
+                            "التحقق بخطوتين"
+                        </h1>
+                        <p class="text-gray-600">
+                            "أدخل الرمز المكون من 6 أرقام من تطبيق المصادقة، أو أحد رموز الاسترداد"
+                        </p>
+                    </div>
+
+                    <ShowLet some=error_msg let(msg)>
+                        <div class="mb-6 bg-red-50 border-l-4 border-red-500 p-4 rounded-lg">
+                            <div class="flex items-center gap-2">
+                                <RedCircleIcon/>
+                                <p class="text-sm text-red-800 font-semibold">{msg}</p>
+                            </div>
+                        </div>
+                    </ShowLet>
+
+                    <ActionForm action={verify_ac}>
+                        <div class="space-y-6">
+                            <input
+                                class="w-full px-4 py-3 bg-gray-50 border-2 border-gray-200 rounded-xl focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-transparent transition-all duration-300 text-gray-800 text-center tracking-widest"
+                                type="text"
+                                name="code"
+                                id="code"
+                                autocomplete="one-time-code"
+                                dir="ltr"
+                                required
+                            />
+                            <button
+                                class="w-full py-3 bg-gradient-to-r from-blue-600 to-purple-600 text-white font-bold rounded-xl shadow-lg hover:shadow-xl hover:scale-[1.02] active:scale-[0.98] transition-all duration-300"
+                                type="submit"
+                            >
+                                "تحقق"
+                            </button>
+                        </div>
+                    </ActionForm>
+
+                    <div class="mt-6 text-center">
+                        <a
+                            href="/login"
+                            class="text-gray-600 hover:text-blue-600 transition-colors duration-300 font-medium"
+                        >
+                            "← العودة إلى تسجيل الدخول"
+                        </a>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
 #[component]
 fn RedCircleIcon() -> impl IntoView {
     view! {
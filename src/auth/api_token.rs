@@ -0,0 +1,87 @@
+use uuid::Uuid;
+
+use super::{Level, Permission};
+
+#[cfg(feature = "ssr")]
+use {
+    axum::{
+        extract::{Request, State},
+        http::{StatusCode, header::AUTHORIZATION},
+        middleware::Next,
+        response::{IntoResponse, Response},
+    },
+    sha2::{Digest, Sha256},
+};
+
+pub const TOKEN_PREFIX: &str = "cpt_";
+/// Characters of a token kept in clear to tell tokens apart in the dashboard.
+pub const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// Session key holding a freshly created token until it is shown once.
+pub const NEW_API_TOKEN_KEY: &str = "new_api_token";
+
+/// Caller authenticated by `Authorization: Bearer`, put into the request
+/// extensions by [`api_token_auth`].
+#[derive(Debug, Clone)]
+pub struct TokenCaller {
+    pub user_id: Uuid,
+    pub level: Level,
+    pub scopes: Vec<Permission>,
+}
+
+#[cfg(feature = "ssr")]
+pub fn generate_token() -> String {
+    format!(
+        "{TOKEN_PREFIX}{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Tokens are long random strings, so a plain SHA-256 is enough and keeps
+/// lookups by hash possible.
+#[cfg(feature = "ssr")]
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(feature = "ssr")]
+fn unauthorized(message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        axum::Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+/// Resolves `Authorization: Bearer <token>` into a [`TokenCaller`]. Requests
+/// without a bearer token pass through untouched to the session layer.
+#[cfg(feature = "ssr")]
+pub async fn api_token_auth(
+    State(app_state): State<crate::AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.strip_prefix("Bearer "))
+        .map(|x| x.trim().to_string());
+    let Some(token) = token else {
+        return next.run(req).await;
+    };
+
+    match crate::db::api_tokens::use_api_token(&app_state.pool, &hash_token(&token)).await {
+        Ok(Some((user_id, level, scopes))) => {
+            req.extensions_mut().insert(TokenCaller {
+                user_id,
+                level,
+                scopes,
+            });
+            next.run(req).await
+        }
+        Ok(None) => unauthorized("invalid or expired token"),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
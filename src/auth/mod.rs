@@ -4,32 +4,184 @@ use leptos_router::components::Redirect;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "ssr")]
 use tower_sessions::Session;
-#[cfg(feature = "ssr")]
 use uuid::Uuid;
 
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
+
+pub mod api_token;
+pub mod password;
+mod permission;
+pub use permission::Permission;
+pub mod throttle;
+pub mod two_factor;
 
 pub const USER_ID_KEY: &str = "user_id";
 pub const USER_LEVEL_KEY: &str = "user_level";
+pub const MUST_CHANGE_PASSWORD_KEY: &str = "must_change_password";
+pub const MUST_ENROLL_TOTP_KEY: &str = "must_enroll_totp";
+/// Session key of a user that passed the password check and still has to
+/// enter a TOTP or recovery code.
+pub const PENDING_LOGIN_KEY: &str = "pending_login_user_id";
+
+/// Page every session flagged with [`MUST_CHANGE_PASSWORD_KEY`] or
+/// [`MUST_ENROLL_TOTP_KEY`] is sent to.
+pub const MY_ACCOUNT_PATH: &str = "/dashboard/myAccount";
+pub const LOGIN_VERIFY_PATH: &str = "/login/verify";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "ssr", derive(sqlx::Type))]
 #[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "PascalCase"))]
 pub enum Level {
+    SuperAdmin,
     Admin,
+    Editor,
+    Agent,
     User,
+    Viewer,
 }
 
 impl Display for Level {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let res = match self {
+            Level::SuperAdmin => "SuperAdmin",
             Level::Admin => "Admin",
+            Level::Editor => "Editor",
+            Level::Agent => "Agent",
             Level::User => "User",
+            Level::Viewer => "Viewer",
         };
         write!(f, "{res}")
     }
 }
 
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Level::ALL
+            .into_iter()
+            .find(|x| x.to_string() == s)
+            .ok_or_else(|| format!("unknown level: {s}"))
+    }
+}
+
+impl Level {
+    pub const ALL: [Level; 6] = [
+        Level::SuperAdmin,
+        Level::Admin,
+        Level::Editor,
+        Level::Agent,
+        Level::User,
+        Level::Viewer,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::SuperAdmin => "مدير عام",
+            Level::Admin => "مدير",
+            Level::Editor => "محرر",
+            Level::Agent => "وكيل عقاري",
+            Level::User => "مستخدم",
+            Level::Viewer => "مشاهد",
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Level::SuperAdmin => 5,
+            Level::Admin => 4,
+            Level::Editor => 3,
+            Level::Agent => 2,
+            Level::User => 1,
+            Level::Viewer => 0,
+        }
+    }
+
+    pub fn satisfies(&self, required: &Level) -> bool {
+        self.rank() >= required.rank()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    Unauthenticated,
+    Forbidden(Level),
+    MissingPermission(Permission),
+    NotOwner,
+    /// API tokens only carry permission scopes, so level checks refuse them.
+    TokenNotAllowed,
+    PasswordChangeRequired,
+    TwoFactorEnrollmentRequired,
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::Unauthenticated => write!(f, "Unauthorized: Please log in"),
+            AuthError::Forbidden(level) => write!(f, "Forbidden: {level} access required"),
+            AuthError::MissingPermission(permission) => {
+                write!(f, "Forbidden: {permission} permission required")
+            }
+            AuthError::NotOwner => write!(f, "Forbidden: you can only manage your own listings"),
+            AuthError::TokenNotAllowed => {
+                write!(
+                    f,
+                    "Forbidden: this operation is not available to API tokens"
+                )
+            }
+            AuthError::PasswordChangeRequired => {
+                write!(f, "Forbidden: you must change your password first")
+            }
+            AuthError::TwoFactorEnrollmentRequired => {
+                write!(
+                    f,
+                    "Forbidden: you must enable two-factor authentication first"
+                )
+            }
+        }
+    }
+}
+
+/// Decides whether the caller (`None` when nobody is logged in) may perform
+/// an operation that needs `required`, returning the caller's id on success.
+pub fn authorize(caller: Option<(Uuid, Level)>, required: &Level) -> Result<Uuid, AuthError> {
+    let (user_id, level) = caller.ok_or(AuthError::Unauthenticated)?;
+    if level.satisfies(required) {
+        Ok(user_id)
+    } else {
+        Err(AuthError::Forbidden(required.clone()))
+    }
+}
+
+/// Permission counterpart of [`authorize`]. `granted` is what the caller's
+/// level holds in `role_permissions`; a `SuperAdmin` is never refused.
+pub fn authorize_permission(
+    caller: Option<(Uuid, Level)>,
+    granted: &[Permission],
+    required: Permission,
+) -> Result<Uuid, AuthError> {
+    let (user_id, level) = caller.ok_or(AuthError::Unauthenticated)?;
+    if level == Level::SuperAdmin || granted.contains(&required) {
+        Ok(user_id)
+    } else {
+        Err(AuthError::MissingPermission(required))
+    }
+}
+
+/// [`authorize_permission`] for a caller using an API token, which is further
+/// limited to the token's `scopes`, even for a `SuperAdmin`.
+pub fn authorize_scoped(
+    caller: Option<(Uuid, Level)>,
+    granted: &[Permission],
+    scopes: &[Permission],
+    required: Permission,
+) -> Result<Uuid, AuthError> {
+    if caller.is_some() && !scopes.contains(&required) {
+        return Err(AuthError::MissingPermission(required));
+    }
+    authorize_permission(caller, granted, required)
+}
+
 #[cfg(feature = "ssr")]
 pub async fn get_user_id_from_session(session: Session) -> Option<Uuid> {
     session
@@ -56,69 +208,311 @@ pub async fn set_user_session(
     Ok(())
 }
 
+#[cfg(feature = "ssr")]
+async fn set_session_flag(
+    session: Session,
+    key: &str,
+    value: bool,
+) -> Result<(), tower_sessions::session::Error> {
+    if value {
+        session.insert(key, true).await
+    } else {
+        session.remove::<bool>(key).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn get_session_flag(session: &Session, key: &str) -> bool {
+    session
+        .get::<bool>(key)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn set_must_change_password(
+    session: Session,
+    must_change: bool,
+) -> Result<(), tower_sessions::session::Error> {
+    set_session_flag(session, MUST_CHANGE_PASSWORD_KEY, must_change).await
+}
+
+#[cfg(feature = "ssr")]
+pub async fn set_must_enroll_totp(
+    session: Session,
+    must_enroll: bool,
+) -> Result<(), tower_sessions::session::Error> {
+    set_session_flag(session, MUST_ENROLL_TOTP_KEY, must_enroll).await
+}
+
+/// Turns a fully verified login into an authenticated session and returns
+/// the page the user should continue to. The session gets a fresh id, which
+/// is recorded in `user_sessions` so it can be listed and revoked.
+#[cfg(feature = "ssr")]
+pub async fn complete_login(
+    session: Session,
+    parts: &axum::http::request::Parts,
+    user: &crate::models::User,
+) -> Result<&'static str, ServerFnError> {
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+    let must_enroll = user.totp_required && !user.totp_enabled;
+
+    async {
+        session.cycle_id().await?;
+        session.remove::<String>(PENDING_LOGIN_KEY).await?;
+        set_user_session(session.clone(), user.id, user.level.clone()).await?;
+        set_must_change_password(session.clone(), user.must_change_password).await?;
+        set_must_enroll_totp(session.clone(), must_enroll).await?;
+        session.save().await
+    }
+    .await
+    .map_err(|e: tower_sessions::session::Error| ServerFnError::new(e.to_string()))?;
+
+    let session_id = session
+        .id()
+        .ok_or_else(|| ServerFnError::new("No session id after save".to_string()))?;
+    let user_agent = parts
+        .headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|x| x.to_str().ok());
+    crate::db::sessions::create_user_session(
+        &app_state.pool,
+        &session_id.to_string(),
+        user.id,
+        user_agent,
+        &client_ip(parts),
+    )
+    .await
+    .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    if user.must_change_password || must_enroll {
+        Ok(MY_ACCOUNT_PATH)
+    } else {
+        Ok("/dashboard")
+    }
+}
+
 #[cfg(feature = "ssr")]
 pub async fn clear_user_session(session: Session) -> Result<(), tower_sessions::session::Error> {
     let _ = session.remove::<String>(USER_ID_KEY).await;
     let _ = session.remove::<Level>(USER_LEVEL_KEY).await;
+    let _ = session.remove::<bool>(MUST_CHANGE_PASSWORD_KEY).await;
+    let _ = session.remove::<bool>(MUST_ENROLL_TOTP_KEY).await;
+    if let (Some(id), Some(app_state)) = (session.id(), use_context::<crate::AppState>()) {
+        let _ = crate::db::sessions::forget_user_session(&app_state.pool, &id.to_string()).await;
+    }
     session.flush().await?;
     Ok(())
 }
 
+/// Also checks the session is still tracked in `user_sessions`, so revoked
+/// sessions stop working even if their store record is written back.
 #[cfg(feature = "ssr")]
-pub async fn require_auth(session: Session) -> Result<Uuid, String> {
-    get_user_id_from_session(session)
+async fn get_caller_from_session(session: Session) -> Option<(Uuid, Level)> {
+    let user_id = get_user_id_from_session(session.clone()).await?;
+    let level = get_user_level_from_session(session.clone()).await?;
+
+    let app_state = use_context::<crate::AppState>()?;
+    let session_id = session.id()?.to_string();
+    let owner = crate::db::sessions::touch_user_session(&app_state.pool, &session_id)
         .await
-        .ok_or_else(|| "Unauthorized: Please log in".to_string())
+        .ok()
+        .flatten();
+    (owner == Some(user_id)).then_some((user_id, level))
 }
 
-#[server]
-async fn check_auth() -> Result<uuid::Uuid, ServerFnError> {
-    use tower_sessions::Session;
+/// Caller of a regular operation. Sessions that still have to replace their
+/// password or enable 2FA may only reach [`require_login`] guarded functions.
+#[cfg(feature = "ssr")]
+async fn get_active_caller(session: Session) -> Result<Option<(Uuid, Level)>, AuthError> {
+    let must_change = get_session_flag(&session, MUST_CHANGE_PASSWORD_KEY).await;
+    let must_enroll = get_session_flag(&session, MUST_ENROLL_TOTP_KEY).await;
+    match get_caller_from_session(session).await {
+        Some(_) if must_change => Err(AuthError::PasswordChangeRequired),
+        Some(_) if must_enroll => Err(AuthError::TwoFactorEnrollmentRequired),
+        caller => Ok(caller),
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub async fn require_auth(session: Session) -> Result<Uuid, String> {
+    authorize(get_caller_from_session(session).await, &Level::Viewer).map_err(|e| e.to_string())
+}
 
+#[cfg(feature = "ssr")]
+pub async fn require_admin(session: Session) -> Result<Uuid, String> {
+    authorize(get_caller_from_session(session).await, &Level::Admin).map_err(|e| e.to_string())
+}
+
+/// Address used to throttle logins. `X-Forwarded-For` is only honoured when
+/// `TRUST_FORWARDED_FOR=true`, i.e. when the site runs behind a reverse proxy.
+#[cfg(feature = "ssr")]
+pub fn client_ip(parts: &axum::http::request::Parts) -> String {
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+
+    let trust_forwarded = std::env::var("TRUST_FORWARDED_FOR").is_ok_and(|x| x == "true");
+    let forwarded = parts
+        .headers
+        .get("x-forwarded-for")
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.split(',').next())
+        .map(|x| x.trim().to_string())
+        .filter(|x| trust_forwarded && !x.is_empty());
+
+    forwarded
+        .or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(feature = "ssr")]
+pub fn session_from_context() -> Result<Session, ServerFnError> {
     let parts = use_context::<axum::http::request::Parts>()
         .ok_or_else(|| ServerFnError::new("No request parts found".to_string()))?;
-    let session = parts
+    parts
         .extensions
         .get::<Session>()
-        .ok_or_else(|| ServerFnError::new("No session found".to_string()))?
-        .clone();
+        .cloned()
+        .ok_or_else(|| ServerFnError::new("No session found".to_string()))
+}
+
+#[cfg(feature = "ssr")]
+fn token_caller_from_context() -> Option<api_token::TokenCaller> {
+    use_context::<axum::http::request::Parts>()?
+        .extensions
+        .get::<api_token::TokenCaller>()
+        .cloned()
+}
+
+/// Server-side guard every server function calls before touching data.
+/// Resolves the caller from the request session and rejects anonymous
+/// callers and callers whose level does not satisfy `required`.
+///
+/// API token callers are refused: a token is limited to its scopes, which
+/// a level check cannot honor. Use [`require_permission`] for operations
+/// tokens may perform.
+#[cfg(feature = "ssr")]
+pub async fn require_level(required: Level) -> Result<Uuid, ServerFnError> {
+    if token_caller_from_context().is_some() {
+        return Err(ServerFnError::ServerError(
+            AuthError::TokenNotAllowed.to_string(),
+        ));
+    }
+    require_session_level(required).await
+}
 
-    require_auth(session)
+/// [`require_level`] without the explicit token check, for operations like
+/// token management that must come from an interactive login.
+#[cfg(feature = "ssr")]
+pub async fn require_session_level(required: Level) -> Result<Uuid, ServerFnError> {
+    let session = session_from_context()?;
+    get_active_caller(session)
         .await
-        .map_err(|e| ServerFnError::ServerError(e))
+        .and_then(|caller| authorize(caller, &required))
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
-#[server]
-async fn check_admin() -> Result<uuid::Uuid, ServerFnError> {
-    use tower_sessions::Session;
+/// Accepts any logged-in caller, including one that still has to change
+/// their password. Only for the account page that performs that change.
+#[cfg(feature = "ssr")]
+pub async fn require_login() -> Result<Uuid, ServerFnError> {
+    let session = session_from_context()?;
+    authorize(get_caller_from_session(session).await, &Level::Viewer)
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+}
 
-    let parts = use_context::<axum::http::request::Parts>()
-        .ok_or_else(|| ServerFnError::new("No request parts found".to_string()))?;
-    let session = parts
-        .extensions
-        .get::<Session>()
-        .ok_or_else(|| ServerFnError::new("No session found".to_string()))?
-        .clone();
+/// Like [`require_level`] but checks `required` against the permission
+/// matrix stored in the database for the caller's level.
+#[cfg(feature = "ssr")]
+pub async fn require_permission(required: Permission) -> Result<Uuid, ServerFnError> {
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
 
-    require_admin(session)
+    if let Some(caller) = token_caller_from_context() {
+        let granted = crate::db::permissions::get_level_permissions(&app_state.pool, &caller.level)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        return authorize_scoped(
+            Some((caller.user_id, caller.level)),
+            &granted,
+            &caller.scopes,
+            required,
+        )
+        .map_err(|e| ServerFnError::ServerError(e.to_string()));
+    }
+
+    let session = session_from_context()?;
+    let caller = get_active_caller(session)
         .await
-        .map_err(|e| ServerFnError::ServerError(e))
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let granted = match &caller {
+        Some((_, level)) => crate::db::permissions::get_level_permissions(&app_state.pool, level)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?,
+        None => Vec::new(),
+    };
+
+    authorize_permission(caller, &granted, required)
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
+/// [`require_permission`] for an operation on a single listing. Callers
+/// without [`Permission::EstatesManageAll`] are limited to the listings they
+/// created or are assigned to.
 #[cfg(feature = "ssr")]
-pub async fn require_admin(session: Session) -> Result<Uuid, String> {
-    let user_id = get_user_id_from_session(session.clone())
+pub async fn require_estate_permission(
+    estate_id: Uuid,
+    required: Permission,
+) -> Result<Uuid, ServerFnError> {
+    let user_id = require_permission(required).await?;
+    if require_permission(Permission::EstatesManageAll)
         .await
-        .ok_or_else(|| "Unauthorized: Please log in".to_string())?;
+        .is_ok()
+    {
+        return Ok(user_id);
+    }
 
-    let level = get_user_level_from_session(session)
+    let app_state = use_context::<crate::AppState>()
+        .ok_or_else(|| ServerFnError::new("No App State found".to_string()))?;
+    let estate = crate::db::estates::get_estate_by_id(&app_state.pool, estate_id)
         .await
-        .ok_or_else(|| "Unable to determine user level".to_string())?;
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+
+    if estate.is_managed_by(user_id) {
+        Ok(user_id)
+    } else {
+        Err(ServerFnError::ServerError(AuthError::NotOwner.to_string()))
+    }
+}
 
-    match level {
-        Level::Admin => Ok(user_id),
-        Level::User => Err("Forbidden: Admin access required".to_string()),
+/// Lets anyone see a public listing; drafts and archived listings only
+/// those who may view them in the dashboard.
+#[cfg(feature = "ssr")]
+pub async fn require_estate_visible(estate: &crate::models::Estate) -> Result<(), ServerFnError> {
+    if estate.status.is_public() {
+        return Ok(());
     }
+    require_estate_permission(estate.id, Permission::EstatesView)
+        .await
+        .map(|_| ())
+}
+
+#[server]
+async fn check_auth() -> Result<uuid::Uuid, ServerFnError> {
+    require_level(Level::Viewer).await
+}
+
+#[server]
+async fn check_permission(permission: Permission) -> Result<uuid::Uuid, ServerFnError> {
+    require_permission(permission).await
 }
 
 #[component]
@@ -131,8 +525,20 @@ where
     let children = children.into_inner();
 
     let fallback = move || {
+        let restricted = auth_check.get().is_some_and(|x| {
+            x.is_err_and(|e| {
+                let e = e.to_string();
+                e.contains(&AuthError::PasswordChangeRequired.to_string())
+                    || e.contains(&AuthError::TwoFactorEnrollmentRequired.to_string())
+            })
+        });
+        let path = if restricted {
+            MY_ACCOUNT_PATH
+        } else {
+            "/login"
+        };
         view! {
-            <Redirect path="/login"/>
+            <Redirect path/>
         }
     };
     view! {
@@ -149,13 +555,13 @@ where
 }
 
 #[component]
-pub fn AdminOnly<C>(children: TypedChildrenFn<C>) -> impl IntoView
+pub fn PermissionRequired<C>(permission: Permission, children: TypedChildrenFn<C>) -> impl IntoView
 where
     C: IntoView + 'static,
 {
-    let admin_id = Resource::new(|| (), |_| check_admin());
+    let permitted = Resource::new(move || permission, check_permission);
 
-    let autherized = move || admin_id.get().map(|x| x.is_ok()).unwrap_or(true);
+    let autherized = move || permitted.get().map(|x| x.is_ok()).unwrap_or(true);
 
     let children = children.into_inner();
 
@@ -0,0 +1,69 @@
+use std::fmt::Display;
+
+pub const MIN_PASSWORD_LENGTH: usize = 10;
+
+const COMMON_PASSWORDS: [&str; 10] = [
+    "admin123",
+    "password",
+    "password1",
+    "password123",
+    "12345678",
+    "1234567890",
+    "123456789a",
+    "qwerty123",
+    "qwertyuiop",
+    "cryptos123",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PasswordPolicyError {
+    TooShort,
+    MissingLetter,
+    MissingDigit,
+    ContainsUsername,
+    TooCommon,
+}
+
+impl Display for PasswordPolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordPolicyError::TooShort => write!(
+                f,
+                "كلمة السر يجب أن تتكون من {MIN_PASSWORD_LENGTH} أحرف على الأقل"
+            ),
+            PasswordPolicyError::MissingLetter => {
+                write!(f, "كلمة السر يجب أن تحتوي على حرف واحد على الأقل")
+            }
+            PasswordPolicyError::MissingDigit => {
+                write!(f, "كلمة السر يجب أن تحتوي على رقم واحد على الأقل")
+            }
+            PasswordPolicyError::ContainsUsername => {
+                write!(f, "كلمة السر يجب ألا تحتوي على اسم المستخدم")
+            }
+            PasswordPolicyError::TooCommon => write!(f, "كلمة السر شائعة جداً، اختر كلمة أخرى"),
+        }
+    }
+}
+
+/// Server-side password policy applied whenever a password is set.
+pub fn check_password_strength(password: &str, username: &str) -> Result<(), PasswordPolicyError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(PasswordPolicyError::TooShort);
+    }
+    if !password.chars().any(char::is_alphabetic) {
+        return Err(PasswordPolicyError::MissingLetter);
+    }
+    if !password.chars().any(|x| x.is_ascii_digit()) {
+        return Err(PasswordPolicyError::MissingDigit);
+    }
+
+    let lowercase = password.to_lowercase();
+    let username = username.trim().to_lowercase();
+    if !username.is_empty() && lowercase.contains(&username) {
+        return Err(PasswordPolicyError::ContainsUsername);
+    }
+    if COMMON_PASSWORDS.contains(&lowercase.as_str()) {
+        return Err(PasswordPolicyError::TooCommon);
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// A single capability checked by `require_permission`. Which levels hold
+/// which permissions is stored in the `role_permissions` table.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Permission {
+    #[serde(rename = "estates.view")]
+    EstatesView,
+    #[serde(rename = "estates.create")]
+    EstatesCreate,
+    #[serde(rename = "estates.edit")]
+    EstatesEdit,
+    #[serde(rename = "estates.delete")]
+    EstatesDelete,
+    /// Making a listing public, directly or through its `publish_at`.
+    #[serde(rename = "estates.publish")]
+    EstatesPublish,
+    #[serde(rename = "estates.manage_all")]
+    EstatesManageAll,
+    #[serde(rename = "users.view")]
+    UsersView,
+    #[serde(rename = "users.manage")]
+    UsersManage,
+    #[serde(rename = "leads.view")]
+    LeadsView,
+    #[serde(rename = "roles.manage")]
+    RolesManage,
+    #[serde(rename = "audit.view")]
+    AuditView,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 11] = [
+        Permission::EstatesView,
+        Permission::EstatesCreate,
+        Permission::EstatesEdit,
+        Permission::EstatesDelete,
+        Permission::EstatesPublish,
+        Permission::EstatesManageAll,
+        Permission::UsersView,
+        Permission::UsersManage,
+        Permission::LeadsView,
+        Permission::RolesManage,
+        Permission::AuditView,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Permission::EstatesView => "estates.view",
+            Permission::EstatesCreate => "estates.create",
+            Permission::EstatesEdit => "estates.edit",
+            Permission::EstatesDelete => "estates.delete",
+            Permission::EstatesPublish => "estates.publish",
+            Permission::EstatesManageAll => "estates.manage_all",
+            Permission::UsersView => "users.view",
+            Permission::UsersManage => "users.manage",
+            Permission::LeadsView => "leads.view",
+            Permission::RolesManage => "roles.manage",
+            Permission::AuditView => "audit.view",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Permission::EstatesView => "عرض العقارات",
+            Permission::EstatesCreate => "إضافة العقارات",
+            Permission::EstatesEdit => "تعديل العقارات",
+            Permission::EstatesDelete => "حذف العقارات",
+            Permission::EstatesPublish => "نشر العقارات",
+            Permission::EstatesManageAll => "إدارة عقارات الآخرين",
+            Permission::UsersView => "عرض المستخدمين",
+            Permission::UsersManage => "إدارة المستخدمين",
+            Permission::LeadsView => "عرض العملاء المحتملين",
+            Permission::RolesManage => "إدارة الصلاحيات",
+            Permission::AuditView => "عرض سجل العمليات",
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .into_iter()
+            .find(|x| x.code() == s)
+            .ok_or_else(|| format!("unknown permission: {s}"))
+    }
+}
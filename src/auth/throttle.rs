@@ -0,0 +1,79 @@
+/// Limits on failed logins for one key (a username or a client IP).
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottlePolicy {
+    /// Failures tolerated before every further attempt is delayed.
+    pub free_attempts: i32,
+    pub max_delay_secs: i64,
+    /// Failures after which the key is locked for `lock_secs`.
+    pub lock_after: i32,
+    pub lock_secs: i64,
+}
+
+pub const USERNAME_POLICY: ThrottlePolicy = ThrottlePolicy {
+    free_attempts: 3,
+    max_delay_secs: 60,
+    lock_after: 10,
+    lock_secs: 15 * 60,
+};
+
+pub const IP_POLICY: ThrottlePolicy = ThrottlePolicy {
+    free_attempts: 10,
+    max_delay_secs: 30,
+    lock_after: 50,
+    lock_secs: 30 * 60,
+};
+
+/// Failed login history of one key as stored in `login_attempts`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AttemptState {
+    pub failed_count: i32,
+    pub secs_since_last_failure: i64,
+    pub lock_remaining_secs: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Throttled {
+    Locked { remaining_secs: i64 },
+    Delayed { remaining_secs: i64 },
+}
+
+impl ThrottlePolicy {
+    /// Seconds a caller must wait after the latest failure, doubling with
+    /// every failure past `free_attempts`.
+    pub fn delay_secs(&self, failed_count: i32) -> i64 {
+        let over = failed_count - self.free_attempts;
+        if over < 0 {
+            return 0;
+        }
+        2_i64
+            .checked_pow(over as u32)
+            .map_or(self.max_delay_secs, |x| x.min(self.max_delay_secs))
+    }
+
+    pub fn check(&self, state: &AttemptState) -> Result<(), Throttled> {
+        if state.lock_remaining_secs > 0 {
+            return Err(Throttled::Locked {
+                remaining_secs: state.lock_remaining_secs,
+            });
+        }
+        let remaining_secs = self.delay_secs(state.failed_count) - state.secs_since_last_failure;
+        if remaining_secs > 0 {
+            return Err(Throttled::Delayed { remaining_secs });
+        }
+        Ok(())
+    }
+}
+
+impl Throttled {
+    pub fn message(&self) -> String {
+        match self {
+            Throttled::Locked { remaining_secs } => format!(
+                "تم إيقاف تسجيل الدخول مؤقتاً بسبب تكرار المحاولات الفاشلة، حاول مرة أخرى بعد {} دقيقة أو تواصل مع المدير",
+                (remaining_secs + 59) / 60
+            ),
+            Throttled::Delayed { remaining_secs } => format!(
+                "محاولات دخول كثيرة، برجاء الانتظار {remaining_secs} ثانية قبل المحاولة مرة أخرى"
+            ),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::PgPool,
+    totp_rs::{Algorithm, Secret, TOTP},
+    uuid::Uuid,
+};
+
+pub const TOTP_ISSUER: &str = "Cryptos";
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Session key holding the secret shown during enrollment until the user
+/// confirms it with a valid code.
+pub const PENDING_TOTP_SECRET_KEY: &str = "pending_totp_secret";
+/// Session key holding freshly generated recovery codes until they are shown once.
+pub const NEW_RECOVERY_CODES_KEY: &str = "new_recovery_codes";
+
+/// Strips the spaces and dashes users tend to type into codes.
+pub fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|x| x.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(feature = "ssr")]
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+#[cfg(feature = "ssr")]
+fn totp(secret: &str, account_name: &str) -> Result<TOTP, String> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| e.to_string())?;
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name.replace(':', "_"),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "ssr")]
+pub fn otpauth_uri(secret: &str, account_name: &str) -> Result<String, String> {
+    totp(secret, account_name).map(|x| x.get_url())
+}
+
+#[cfg(feature = "ssr")]
+pub fn verify_totp(secret: &str, account_name: &str, code: &str) -> bool {
+    totp(secret, account_name)
+        .ok()
+        .and_then(|x| x.check_current(&normalize_code(code)).ok())
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "ssr")]
+pub fn qr_svg(uri: &str) -> Result<String, String> {
+    use qrcode::{QrCode, render::svg};
+
+    let code = QrCode::new(uri.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(code.render::<svg::Color>().min_dimensions(200, 200).build())
+}
+
+/// Random `xxxxx-xxxxx` codes; only their hashes are ever stored.
+#[cfg(feature = "ssr")]
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw = Uuid::new_v4().simple().to_string();
+            format!("{}-{}", &raw[..5], &raw[5..10])
+        })
+        .collect()
+}
+
+#[cfg(feature = "ssr")]
+pub fn hash_recovery_codes(codes: &[String]) -> Vec<String> {
+    codes
+        .iter()
+        .map(|x| password_auth::generate_hash(normalize_code(x)))
+        .collect()
+}
+
+/// Consumes one of the user's unused recovery codes if `code` matches it.
+#[cfg(feature = "ssr")]
+pub async fn redeem_recovery_code(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let code = normalize_code(code);
+    let matching = crate::db::two_factor::get_unused_recovery_codes(pool, user_id)
+        .await?
+        .into_iter()
+        .find(|(_, hash)| password_auth::verify_password(&code, hash).is_ok());
+
+    match matching {
+        Some((id, _)) => crate::db::two_factor::use_recovery_code(pool, id).await,
+        None => Ok(false),
+    }
+}
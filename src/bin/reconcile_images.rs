@@ -0,0 +1,62 @@
+use std::{env, time::Duration};
+
+use cryptos_site::{
+    db::create_pool,
+    storage::{self, reconcile},
+};
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    let mut dry_run = false;
+    let mut grace = reconcile::DEFAULT_GRACE;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--grace-hours" => match args.next().and_then(|x| x.parse::<u64>().ok()) {
+                Some(hours) => grace = Duration::from_secs(hours * 60 * 60),
+                None => usage(),
+            },
+            _ => usage(),
+        }
+    }
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let pool = create_pool(&database_url)
+        .await
+        .expect("Failed to create database pool");
+    let storage = storage::from_env().await;
+
+    let report = match reconcile::reconcile(&pool, storage.as_ref(), grace, dry_run).await {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("❌ FAILED: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("Orphaned objects: {}", report.orphans.len());
+    for key in &report.orphans {
+        println!("  {key}");
+    }
+    println!("Missing objects: {}", report.missing.len());
+    for key in &report.missing {
+        println!("  {key}");
+    }
+    if dry_run {
+        println!("Would delete: {}", report.deleted.len());
+    } else {
+        println!("Deleted: {}", report.deleted.len());
+    }
+    for key in &report.deleted {
+        println!("  {key}");
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: reconcile_images [--dry-run] [--grace-hours <hours>]");
+    eprintln!("Example: reconcile_images --dry-run --grace-hours 48");
+    std::process::exit(1);
+}
@@ -0,0 +1,124 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "ssr")]
+use super::models::ApiToken;
+#[cfg(feature = "ssr")]
+use crate::auth::{Level, Permission};
+
+#[cfg(feature = "ssr")]
+pub async fn create_api_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    token_prefix: &str,
+    token_hash: &str,
+    scopes: &[Permission],
+    expires_in_days: Option<i32>,
+) -> Result<Uuid, Error> {
+    let scopes = scopes
+        .iter()
+        .map(|x| x.code().to_string())
+        .collect::<Vec<_>>();
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_prefix, token_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(days => $6))
+        RETURNING id
+        "#,
+        user_id,
+        name,
+        token_prefix,
+        token_hash,
+        &scopes,
+        expires_in_days
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_user_api_tokens(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiToken>, Error> {
+    let tokens = sqlx::query!(
+        r#"
+        SELECT
+            id,
+            name,
+            token_prefix,
+            scopes,
+            TO_CHAR(expires_at, 'YYYY-MM-DD HH24:MI') AS expires_at,
+            TO_CHAR(last_used_at, 'YYYY-MM-DD HH24:MI') AS last_used_at,
+            TO_CHAR(created_at, 'YYYY-MM-DD HH24:MI') AS "created_at!"
+        FROM api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|x| ApiToken {
+        id: x.id,
+        name: x.name,
+        token_prefix: x.token_prefix,
+        scopes: x.scopes.iter().filter_map(|x| x.parse().ok()).collect(),
+        expires_at: x.expires_at,
+        last_used_at: x.last_used_at,
+        created_at: x.created_at,
+    })
+    .collect();
+
+    Ok(tokens)
+}
+
+/// Resolves an unexpired token hash to its owner, the owner's level and the
+/// token's scopes, marking the token as used.
+#[cfg(feature = "ssr")]
+pub async fn use_api_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<(Uuid, Level, Vec<Permission>)>, Error> {
+    let row = sqlx::query!(
+        r#"
+        UPDATE api_tokens t
+        SET last_used_at = NOW()
+        FROM users u
+        WHERE u.id = t.user_id
+          AND u.deleted_at IS NULL
+          AND t.token_hash = $1
+          AND (t.expires_at IS NULL OR t.expires_at > NOW())
+        RETURNING t.user_id, u.level, t.scopes
+        "#,
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|x| {
+        let level = x.level.parse().ok()?;
+        let scopes = x.scopes.iter().filter_map(|x| x.parse().ok()).collect();
+        Some((x.user_id, level, scopes))
+    }))
+}
+
+#[cfg(feature = "ssr")]
+pub async fn delete_api_token(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM api_tokens
+        WHERE id = $1 AND user_id = $2
+        "#,
+        id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
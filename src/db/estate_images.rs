@@ -0,0 +1,297 @@
+#[cfg(feature = "ssr")]
+use sqlx::{Error, PgPool, Postgres, Transaction};
+#[cfg(feature = "ssr")]
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+use super::models::{EstateImage, ImageKeys};
+
+#[cfg(feature = "ssr")]
+struct EstateImageRow {
+    id: Uuid,
+    estate_id: Uuid,
+    image_key: String,
+    position: i32,
+    is_cover: bool,
+    caption: String,
+    alt_text: String,
+    thumbnail_key: Option<String>,
+    medium_key: Option<String>,
+    large_key: Option<String>,
+}
+
+#[cfg(feature = "ssr")]
+impl From<EstateImageRow> for EstateImage {
+    fn from(x: EstateImageRow) -> Self {
+        let keys = ImageKeys {
+            original: x.image_key,
+            thumbnail: x.thumbnail_key,
+            medium: x.medium_key,
+            large: x.large_key,
+        };
+        EstateImage {
+            id: x.id,
+            estate_id: x.estate_id,
+            position: x.position,
+            is_cover: x.is_cover,
+            caption: x.caption,
+            alt_text: x.alt_text,
+            urls: keys.public_urls(crate::storage::public_base()),
+            keys,
+        }
+    }
+}
+
+/// Copies the cover's keys into `estates`, which listing cards use.
+#[cfg(feature = "ssr")]
+async fn sync_cover(tx: &mut Transaction<'_, Postgres>, estate_id: Uuid) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estates
+        SET image_key = COALESCE(
+                (SELECT image_key FROM estate_images WHERE estate_id = $1 AND is_cover),
+                ''
+            ),
+            thumbnail_key = (
+                SELECT thumbnail_key FROM estate_images WHERE estate_id = $1 AND is_cover
+            ),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        estate_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Makes the first image in gallery order the cover when there is none.
+#[cfg(feature = "ssr")]
+async fn ensure_cover(tx: &mut Transaction<'_, Postgres>, estate_id: Uuid) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estate_images
+        SET is_cover = TRUE
+        WHERE id = (
+            SELECT id FROM estate_images
+            WHERE estate_id = $1
+            ORDER BY position, created_at
+            LIMIT 1
+        )
+        AND NOT EXISTS (SELECT 1 FROM estate_images WHERE estate_id = $1 AND is_cover)
+        "#,
+        estate_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sync_cover(tx, estate_id).await
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_estate_images(pool: &PgPool, estate_id: Uuid) -> Result<Vec<EstateImage>, Error> {
+    let images = sqlx::query_as!(
+        EstateImageRow,
+        r#"
+        SELECT id, estate_id, image_key, position, is_cover, caption, alt_text,
+            thumbnail_key, medium_key, large_key
+        FROM estate_images
+        WHERE estate_id = $1
+        ORDER BY position, created_at
+        "#,
+        estate_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(images.into_iter().map(EstateImage::from).collect())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_estate_image(pool: &PgPool, id: Uuid) -> Result<EstateImage, Error> {
+    let image = sqlx::query_as!(
+        EstateImageRow,
+        r#"
+        SELECT id, estate_id, image_key, position, is_cover, caption, alt_text,
+            thumbnail_key, medium_key, large_key
+        FROM estate_images
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(image.into())
+}
+
+/// Appends `images` to the end of the gallery; the first image of an empty
+/// gallery becomes its cover.
+#[cfg(feature = "ssr")]
+pub async fn add_estate_images(
+    pool: &PgPool,
+    estate_id: Uuid,
+    images: &[ImageKeys],
+) -> Result<Vec<Uuid>, Error> {
+    let originals = images
+        .iter()
+        .map(|x| x.original.clone())
+        .collect::<Vec<_>>();
+    let thumbnails = images
+        .iter()
+        .map(|x| x.thumbnail.clone())
+        .collect::<Vec<_>>();
+    let mediums = images.iter().map(|x| x.medium.clone()).collect::<Vec<_>>();
+    let larges = images.iter().map(|x| x.large.clone()).collect::<Vec<_>>();
+
+    let mut tx = pool.begin().await?;
+    let ids = sqlx::query_scalar!(
+        r#"
+        INSERT INTO estate_images (estate_id, image_key, thumbnail_key, medium_key, large_key, position)
+        SELECT $1, x.image_key, x.thumbnail_key, x.medium_key, x.large_key,
+            (SELECT COALESCE(MAX(position), -1) FROM estate_images WHERE estate_id = $1) + x.n::INTEGER
+        FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[])
+            WITH ORDINALITY AS x(image_key, thumbnail_key, medium_key, large_key, n)
+        RETURNING id
+        "#,
+        estate_id,
+        &originals,
+        &thumbnails as &[Option<String>],
+        &mediums as &[Option<String>],
+        &larges as &[Option<String>]
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    ensure_cover(&mut tx, estate_id).await?;
+    tx.commit().await?;
+
+    Ok(ids)
+}
+
+/// Deletes an image, handing the cover over to the next image if needed.
+#[cfg(feature = "ssr")]
+pub async fn delete_estate_image(pool: &PgPool, estate_id: Uuid, id: Uuid) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM estate_images
+        WHERE id = $1 AND estate_id = $2
+        "#,
+        id,
+        estate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    ensure_cover(&mut tx, estate_id).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn set_cover_image(pool: &PgPool, estate_id: Uuid, id: Uuid) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE estate_images
+        SET is_cover = FALSE
+        WHERE estate_id = $1 AND is_cover AND id <> $2
+        "#,
+        estate_id,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE estate_images
+        SET is_cover = TRUE
+        WHERE id = $1 AND estate_id = $2
+        "#,
+        id,
+        estate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    ensure_cover(&mut tx, estate_id).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Stores the gallery order given by `ids`; images of other estates are ignored.
+#[cfg(feature = "ssr")]
+pub async fn reorder_estate_images(
+    pool: &PgPool,
+    estate_id: Uuid,
+    ids: &[Uuid],
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estate_images i
+        SET position = x.n::INTEGER - 1
+        FROM UNNEST($2::UUID[]) WITH ORDINALITY AS x(id, n)
+        WHERE i.id = x.id AND i.estate_id = $1
+        "#,
+        estate_id,
+        ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn update_image_details(
+    pool: &PgPool,
+    estate_id: Uuid,
+    id: Uuid,
+    caption: &str,
+    alt_text: &str,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estate_images
+        SET caption = $3, alt_text = $4
+        WHERE id = $1 AND estate_id = $2
+        "#,
+        id,
+        estate_id,
+        caption,
+        alt_text
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Every object key the database refers to, variants and estate covers
+/// included. Urls of images hosted elsewhere are left out.
+#[cfg(feature = "ssr")]
+pub async fn referenced_image_keys(pool: &PgPool) -> Result<Vec<String>, Error> {
+    let keys = sqlx::query_scalar!(
+        r#"
+        SELECT key AS "key!"
+        FROM (
+            SELECT image_key AS key FROM estates
+            UNION SELECT thumbnail_key FROM estates
+            UNION SELECT image_key FROM estate_images
+            UNION SELECT thumbnail_key FROM estate_images
+            UNION SELECT medium_key FROM estate_images
+            UNION SELECT large_key FROM estate_images
+            UNION SELECT image_key FROM pending_images
+            UNION SELECT thumbnail_key FROM pending_images
+            UNION SELECT medium_key FROM pending_images
+            UNION SELECT large_key FROM pending_images
+        ) AS x
+        WHERE key IS NOT NULL AND key <> '' AND key NOT LIKE '%://%'
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
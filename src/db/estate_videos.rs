@@ -0,0 +1,175 @@
+#[cfg(feature = "ssr")]
+use sqlx::{Error, PgPool};
+#[cfg(feature = "ssr")]
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+use super::models::EstateVideo;
+#[cfg(feature = "ssr")]
+use crate::videos::VideoInfo;
+
+#[cfg(feature = "ssr")]
+struct EstateVideoRow {
+    id: Uuid,
+    estate_id: Uuid,
+    position: i32,
+    video_key: String,
+    poster_key: Option<String>,
+    content_type: String,
+    size: i64,
+    duration_secs: f64,
+    width: i32,
+    height: i32,
+}
+
+#[cfg(feature = "ssr")]
+impl From<EstateVideoRow> for EstateVideo {
+    fn from(x: EstateVideoRow) -> Self {
+        let base = crate::storage::public_base();
+        EstateVideo {
+            id: x.id,
+            estate_id: x.estate_id,
+            position: x.position,
+            video_url: crate::models::public_url(base, &x.video_key),
+            poster_url: x
+                .poster_key
+                .as_deref()
+                .map(|key| crate::models::public_url(base, key)),
+            video_key: x.video_key,
+            poster_key: x.poster_key,
+            content_type: x.content_type,
+            size: x.size,
+            duration_secs: x.duration_secs,
+            width: x.width,
+            height: x.height,
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_estate_videos(pool: &PgPool, estate_id: Uuid) -> Result<Vec<EstateVideo>, Error> {
+    let videos = sqlx::query_as!(
+        EstateVideoRow,
+        r#"
+        SELECT id, estate_id, position, video_key, poster_key, content_type, size,
+            duration_secs, width, height
+        FROM estate_videos
+        WHERE estate_id = $1
+        ORDER BY position, created_at
+        "#,
+        estate_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(videos.into_iter().map(EstateVideo::from).collect())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_estate_video(pool: &PgPool, id: Uuid) -> Result<EstateVideo, Error> {
+    let video = sqlx::query_as!(
+        EstateVideoRow,
+        r#"
+        SELECT id, estate_id, position, video_key, poster_key, content_type, size,
+            duration_secs, width, height
+        FROM estate_videos
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(video.into())
+}
+
+/// Appends a checked video to the end of the estate's videos.
+#[cfg(feature = "ssr")]
+pub async fn add_estate_video(
+    pool: &PgPool,
+    estate_id: Uuid,
+    video_key: &str,
+    size: i64,
+    info: &VideoInfo,
+) -> Result<Uuid, Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO estate_videos (estate_id, video_key, content_type, size, duration_secs,
+            width, height, video_codec, audio_codec, position)
+        SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9,
+            (SELECT COALESCE(MAX(position), -1) + 1 FROM estate_videos WHERE estate_id = $1)
+        RETURNING id
+        "#,
+        estate_id,
+        video_key,
+        info.container.content_type(),
+        size,
+        info.duration_secs,
+        info.width,
+        info.height,
+        info.video_codec,
+        info.audio_codec
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn set_video_poster(
+    pool: &PgPool,
+    estate_id: Uuid,
+    id: Uuid,
+    poster_key: &str,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estate_videos
+        SET poster_key = $3
+        WHERE id = $1 AND estate_id = $2
+        "#,
+        id,
+        estate_id,
+        poster_key
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn delete_estate_video(pool: &PgPool, estate_id: Uuid, id: Uuid) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM estate_videos
+        WHERE id = $1 AND estate_id = $2
+        "#,
+        id,
+        estate_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Every object key of a video or poster.
+#[cfg(feature = "ssr")]
+pub async fn referenced_video_keys(pool: &PgPool) -> Result<Vec<String>, Error> {
+    let keys = sqlx::query_scalar!(
+        r#"
+        SELECT key AS "key!"
+        FROM (
+            SELECT video_key AS key FROM estate_videos
+            UNION SELECT poster_key FROM estate_videos
+        ) AS x
+        WHERE key IS NOT NULL
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
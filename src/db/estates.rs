@@ -1,37 +1,56 @@
 #[cfg(feature = "ssr")]
-use sqlx::{Error, PgPool};
+use sqlx::{Error, PgExecutor, PgPool, QueryBuilder};
 #[cfg(feature = "ssr")]
 use uuid::Uuid;
 
 #[cfg(feature = "ssr")]
-use super::models::Estate;
+use super::models::{
+    DeletedEstate, Estate, EstateAttributes, EstateCursor, EstateFilter, EstateListing, EstatePage,
+    EstateSort, EstateStatus, Finishing, HIGHLIGHT_END, HIGHLIGHT_START, InstallmentFrequency,
+    ListingPurpose, ListingSchedule, NewEstate, PaymentTerms, PropertyType,
+};
 
 #[cfg(feature = "ssr")]
 pub async fn create_estate(
     pool: &PgPool,
-    name: String,
-    address: String,
-    image_url: String,
-    price_in_cents: i64,
-    space_in_meters: i32,
-    description: String,
-) -> Result<(), Error> {
-    sqlx::query!(
+    estate: &NewEstate,
+    created_by: Uuid,
+) -> Result<Uuid, Error> {
+    let id = sqlx::query_scalar!(
         r#"
-            INSERT INTO estates (name, address, image_url, price_in_cents, space_in_meters,description)
-            VALUES ($1, $2, $3, $4, $5,$6)
+            INSERT INTO estates (name, address, image_key, price_in_cents, space_in_meters,description, created_by, assigned_agent,
+                property_type, bedrooms, bathrooms, floor, finishing, furnished,
+                purpose, down_payment_in_cents, installment_years, installment_frequency, installment_in_cents,
+                publish_at, expire_at)
+            VALUES ($1, $2, $3, $4, $5,$6, $7, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                $19::TEXT::TIMESTAMPTZ, $20::TEXT::TIMESTAMPTZ)
+            RETURNING id
         "#,
-        &name,
-        &address,
-        &image_url,
-        price_in_cents,
-        space_in_meters,
-        &description
+        &estate.name,
+        &estate.address,
+        &estate.image_url,
+        estate.price_in_cents,
+        estate.space_in_meters,
+        &estate.description,
+        created_by,
+        estate.attributes.property_type as PropertyType,
+        estate.attributes.bedrooms,
+        estate.attributes.bathrooms,
+        estate.attributes.floor,
+        estate.attributes.finishing as Option<Finishing>,
+        estate.attributes.furnished,
+        estate.terms.purpose as ListingPurpose,
+        estate.terms.down_payment_in_cents,
+        estate.terms.installment_years,
+        estate.terms.installment_frequency as Option<InstallmentFrequency>,
+        estate.terms.installment_in_cents,
+        estate.schedule.publish_at.as_deref(),
+        estate.schedule.expire_at.as_deref()
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok(())
+    Ok(id)
 }
 
 #[cfg(feature = "ssr")]
@@ -39,52 +58,260 @@ pub async fn get_estate_by_id(pool: &PgPool, id: Uuid) -> Result<Estate, Error>
     let estate = sqlx::query_as!(
         Estate,
         r#"
-        SELECT id, name, address, image_url,description, price_in_cents, space_in_meters
+        SELECT id, name, address, image_key AS image_url, thumbnail_key AS thumbnail_url, description, price_in_cents, space_in_meters, created_by, assigned_agent,
+            property_type AS "property_type: PropertyType", bedrooms, bathrooms, floor, finishing AS "finishing: Finishing", furnished,
+            purpose AS "purpose: ListingPurpose", down_payment_in_cents, installment_years,
+            installment_frequency AS "installment_frequency: InstallmentFrequency", installment_in_cents,
+            status AS "status: EstateStatus", TO_CHAR(published_at, 'YYYY-MM-DD HH24:MI') AS published_at,
+            TO_CHAR(reserved_at, 'YYYY-MM-DD HH24:MI') AS reserved_at, TO_CHAR(sold_at, 'YYYY-MM-DD HH24:MI') AS sold_at,
+            TO_CHAR(archived_at, 'YYYY-MM-DD HH24:MI') AS archived_at,
+            TO_CHAR(publish_at, 'YYYY-MM-DD"T"HH24:MI') AS publish_at, TO_CHAR(expire_at, 'YYYY-MM-DD"T"HH24:MI') AS expire_at
         FROM estates
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         id
     )
     .fetch_one(pool)
     .await?;
 
-    Ok(estate)
+    Ok(estate.with_public_urls(crate::storage::public_base()))
 }
 
 #[cfg(feature = "ssr")]
-pub async fn get_image_url_by_id(pool: &PgPool, id: Uuid) -> Result<String, Error> {
-    let res = sqlx::query!(
+pub async fn get_all_estates(pool: &PgPool) -> Result<Vec<Estate>, Error> {
+    let estates = sqlx::query_as!(
+        Estate,
         r#"
-        SELECT image_url
+        SELECT id, name, address, image_key AS image_url, thumbnail_key AS thumbnail_url, description, price_in_cents, space_in_meters, created_by, assigned_agent,
+            property_type AS "property_type: PropertyType", bedrooms, bathrooms, floor, finishing AS "finishing: Finishing", furnished,
+            purpose AS "purpose: ListingPurpose", down_payment_in_cents, installment_years,
+            installment_frequency AS "installment_frequency: InstallmentFrequency", installment_in_cents,
+            status AS "status: EstateStatus", TO_CHAR(published_at, 'YYYY-MM-DD HH24:MI') AS published_at,
+            TO_CHAR(reserved_at, 'YYYY-MM-DD HH24:MI') AS reserved_at, TO_CHAR(sold_at, 'YYYY-MM-DD HH24:MI') AS sold_at,
+            TO_CHAR(archived_at, 'YYYY-MM-DD HH24:MI') AS archived_at,
+            TO_CHAR(publish_at, 'YYYY-MM-DD"T"HH24:MI') AS publish_at, TO_CHAR(expire_at, 'YYYY-MM-DD"T"HH24:MI') AS expire_at
         FROM estates
-        WHERE id = $1
+        WHERE deleted_at IS NULL
+        ORDER BY created_at DESC
         "#,
-        id
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await?;
 
-    Ok(res.image_url)
+    let base = crate::storage::public_base();
+    Ok(estates
+        .into_iter()
+        .map(|x| x.with_public_urls(base))
+        .collect())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn get_all_estates(pool: &PgPool) -> Result<Vec<Estate>, Error> {
+#[derive(sqlx::FromRow)]
+struct EstateRow {
+    #[sqlx(flatten)]
+    estate: Estate,
+    sort_key: i64,
+    snippet: Option<String>,
+}
+
+/// One page of estates matching `filter`, starting after `after`. Ordering
+/// and the keyset comparison depend on the sort, so the query is built at
+/// runtime from fixed fragments; user input only ever travels as binds.
+///
+/// The search text goes through the same `normalize_arabic` as the indexed
+/// `search_vector`, so spelling variants and tashkeel do not matter. The
+/// snippet is cut from the normalized name, address and description for the
+/// same reason, so every match gets highlighted wherever it was found.
+#[cfg(feature = "ssr")]
+pub async fn get_estates_page(
+    pool: &PgPool,
+    filter: &EstateFilter,
+    after: Option<EstateCursor>,
+    limit: i64,
+) -> Result<EstatePage, Error> {
+    let text = filter
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|x| !x.is_empty());
+    let (column, sort_key, descending) = match filter.sort {
+        EstateSort::Relevance if text.is_some() => {
+            let key = "(ts_rank_cd(search_vector, search.query) * 1000000)::BIGINT";
+            (key, key, true)
+        }
+        EstateSort::Newest | EstateSort::Relevance => {
+            let key = "(EXTRACT(EPOCH FROM created_at) * 1000000)::BIGINT";
+            (key, key, true)
+        }
+        EstateSort::PriceAsc => ("price_in_cents", "price_in_cents", false),
+        EstateSort::PriceDesc => ("price_in_cents", "price_in_cents", true),
+        EstateSort::SpaceAsc => ("space_in_meters", "space_in_meters::BIGINT", false),
+        EstateSort::SpaceDesc => ("space_in_meters", "space_in_meters::BIGINT", true),
+    };
+
+    let mut query = QueryBuilder::new(
+        "WITH search AS (SELECT websearch_to_tsquery('arabic', normalize_arabic(",
+    );
+    query.push_bind(text).push(format!(
+        r#"
+        )) AS query)
+        SELECT id, name, address, image_key AS image_url, thumbnail_key AS thumbnail_url, description, price_in_cents, space_in_meters, created_by, assigned_agent,
+            property_type, bedrooms, bathrooms, floor, finishing, furnished,
+            purpose, down_payment_in_cents, installment_years, installment_frequency, installment_in_cents,
+            status, TO_CHAR(published_at, 'YYYY-MM-DD HH24:MI') AS published_at,
+            TO_CHAR(reserved_at, 'YYYY-MM-DD HH24:MI') AS reserved_at, TO_CHAR(sold_at, 'YYYY-MM-DD HH24:MI') AS sold_at,
+            TO_CHAR(archived_at, 'YYYY-MM-DD HH24:MI') AS archived_at,
+            TO_CHAR(publish_at, 'YYYY-MM-DD"T"HH24:MI') AS publish_at, TO_CHAR(expire_at, 'YYYY-MM-DD"T"HH24:MI') AS expire_at,
+            {sort_key} AS sort_key,
+            ts_headline('arabic', normalize_arabic(concat_ws(' - ', name, address, description)), search.query,
+                'StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_END}, MaxWords=30, MinWords=10, MaxFragments=2') AS snippet
+        FROM estates, search
+        WHERE deleted_at IS NULL
+        "#
+    ));
+    if let Some(min_price) = filter.min_price {
+        query.push(" AND price_in_cents >= ").push_bind(min_price);
+    }
+    if let Some(max_price) = filter.max_price {
+        query.push(" AND price_in_cents <= ").push_bind(max_price);
+    }
+    if let Some(min_space) = filter.min_space {
+        query.push(" AND space_in_meters >= ").push_bind(min_space);
+    }
+    if let Some(max_space) = filter.max_space {
+        query.push(" AND space_in_meters <= ").push_bind(max_space);
+    }
+    if let Some(property_type) = filter.property_type {
+        query.push(" AND property_type = ").push_bind(property_type);
+    }
+    if let Some(min_bedrooms) = filter.min_bedrooms {
+        query.push(" AND bedrooms >= ").push_bind(min_bedrooms);
+    }
+    if let Some(min_bathrooms) = filter.min_bathrooms {
+        query.push(" AND bathrooms >= ").push_bind(min_bathrooms);
+    }
+    if let Some(min_floor) = filter.min_floor {
+        query.push(" AND floor >= ").push_bind(min_floor);
+    }
+    if let Some(max_floor) = filter.max_floor {
+        query.push(" AND floor <= ").push_bind(max_floor);
+    }
+    if let Some(finishing) = filter.finishing {
+        query.push(" AND finishing = ").push_bind(finishing);
+    }
+    if let Some(furnished) = filter.furnished {
+        query.push(" AND furnished = ").push_bind(furnished);
+    }
+    if let Some(purpose) = filter.purpose {
+        query.push(" AND purpose = ").push_bind(purpose);
+    }
+    if let Some(installments) = filter.installments {
+        query.push(if installments {
+            " AND installment_years IS NOT NULL"
+        } else {
+            " AND installment_years IS NULL"
+        });
+    }
+    if let Some(max_down_payment) = filter.max_down_payment {
+        query
+            .push(" AND down_payment_in_cents <= ")
+            .push_bind(max_down_payment);
+    }
+    if let Some(status) = filter.status {
+        query.push(" AND status = ").push_bind(status);
+    }
+    if filter.listed_only {
+        query.push(" AND status IN ('published', 'reserved')");
+    }
+    if text.is_some() {
+        query.push(" AND search_vector @@ search.query");
+    }
+    if let Some(user_id) = filter.managed_by {
+        query
+            .push(" AND (created_by = ")
+            .push_bind(user_id)
+            .push(" OR assigned_agent = ")
+            .push_bind(user_id)
+            .push(")");
+    }
+    let (comparison, order) = if descending {
+        ("<", "DESC")
+    } else {
+        (">", "ASC")
+    };
+    if let Some(after) = after {
+        query
+            .push(format!(" AND ({column}, id) {comparison} ("))
+            .push_bind(after.key)
+            .push(", ")
+            .push_bind(after.id)
+            .push(")");
+    }
+    query
+        .push(format!(" ORDER BY {column} {order}, id {order} LIMIT "))
+        .push_bind(limit + 1);
+
+    let mut rows: Vec<EstateRow> = query.build_query_as().fetch_all(pool).await?;
+    let next = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|x| {
+            EstateCursor {
+                key: x.sort_key,
+                id: x.estate.id,
+            }
+            .to_string()
+        })
+    } else {
+        None
+    };
+
+    Ok(EstatePage {
+        items: rows
+            .into_iter()
+            .map(|x| EstateListing {
+                estate: x.estate.with_public_urls(crate::storage::public_base()),
+                snippet: x.snippet,
+            })
+            .collect(),
+        next,
+    })
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_estates_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Estate>, Error> {
     let estates = sqlx::query_as!(
         Estate,
         r#"
-        SELECT id, name, address, image_url,description, price_in_cents, space_in_meters
+        SELECT id, name, address, image_key AS image_url, thumbnail_key AS thumbnail_url, description, price_in_cents, space_in_meters, created_by, assigned_agent,
+            property_type AS "property_type: PropertyType", bedrooms, bathrooms, floor, finishing AS "finishing: Finishing", furnished,
+            purpose AS "purpose: ListingPurpose", down_payment_in_cents, installment_years,
+            installment_frequency AS "installment_frequency: InstallmentFrequency", installment_in_cents,
+            status AS "status: EstateStatus", TO_CHAR(published_at, 'YYYY-MM-DD HH24:MI') AS published_at,
+            TO_CHAR(reserved_at, 'YYYY-MM-DD HH24:MI') AS reserved_at, TO_CHAR(sold_at, 'YYYY-MM-DD HH24:MI') AS sold_at,
+            TO_CHAR(archived_at, 'YYYY-MM-DD HH24:MI') AS archived_at,
+            TO_CHAR(publish_at, 'YYYY-MM-DD"T"HH24:MI') AS publish_at, TO_CHAR(expire_at, 'YYYY-MM-DD"T"HH24:MI') AS expire_at
         FROM estates
+        WHERE (created_by = $1 OR assigned_agent = $1) AND deleted_at IS NULL
         ORDER BY created_at DESC
         "#,
+        user_id
     )
     .fetch_all(pool)
     .await?;
 
-    Ok(estates)
+    let base = crate::storage::public_base();
+    Ok(estates
+        .into_iter()
+        .map(|x| x.with_public_urls(base))
+        .collect())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_estate_name(pool: &PgPool, id: Uuid, name: String) -> Result<(), Error> {
+pub async fn update_estate_name(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    name: String,
+) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
@@ -94,14 +321,18 @@ pub async fn update_estate_name(pool: &PgPool, id: Uuid, name: String) -> Result
         &name,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_estate_address(pool: &PgPool, id: Uuid, address: String) -> Result<(), Error> {
+pub async fn update_estate_address(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    address: String,
+) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
@@ -111,98 +342,303 @@ pub async fn update_estate_address(pool: &PgPool, id: Uuid, address: String) ->
         &address,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_estate_image_url(
-    pool: &PgPool,
+pub async fn update_description(
+    executor: impl PgExecutor<'_>,
     id: Uuid,
-    image_url: String,
+    description: String,
 ) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
-        SET image_url = $1, updated_at = NOW()
+        SET description = $1, updated_at = NOW()
         WHERE id = $2
         "#,
-        &image_url,
+        &description,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_description(pool: &PgPool, id: Uuid, description: String) -> Result<(), Error> {
+pub async fn update_estate_price(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    price_in_cents: i64,
+) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
-        SET description = $1, updated_at = NOW()
+        SET price_in_cents = $1, updated_at = NOW()
         WHERE id = $2
         "#,
-        &description,
+        price_in_cents,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_estate_price(
-    pool: &PgPool,
+pub async fn update_estate_space(
+    executor: impl PgExecutor<'_>,
     id: Uuid,
-    price_in_cents: i64,
+    space_in_meters: i32,
 ) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
-        SET price_in_cents = $1, updated_at = NOW()
+        SET space_in_meters = $1, updated_at = NOW()
         WHERE id = $2
         "#,
-        price_in_cents,
+        space_in_meters,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_estate_space(
+pub async fn update_estate_attributes(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    attributes: &EstateAttributes,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estates
+        SET property_type = $1, bedrooms = $2, bathrooms = $3, floor = $4, finishing = $5, furnished = $6, updated_at = NOW()
+        WHERE id = $7
+        "#,
+        attributes.property_type as PropertyType,
+        attributes.bedrooms,
+        attributes.bathrooms,
+        attributes.floor,
+        attributes.finishing as Option<Finishing>,
+        attributes.furnished,
+        id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn update_estate_payment_terms(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    terms: &PaymentTerms,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estates
+        SET purpose = $1, down_payment_in_cents = $2, installment_years = $3, installment_frequency = $4, installment_in_cents = $5, updated_at = NOW()
+        WHERE id = $6
+        "#,
+        terms.purpose as ListingPurpose,
+        terms.down_payment_in_cents,
+        terms.installment_years,
+        terms.installment_frequency as Option<InstallmentFrequency>,
+        terms.installment_in_cents,
+        id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Moves an estate from `from` to `to` and stamps when it entered `to`.
+/// Publishing settles a pending `publish_at` and drops an `expire_at` that
+/// has already passed, which would take the estate down again right away.
+/// Returns `false` when the estate was no longer in `from`, so of two
+/// concurrent transitions only the first applies.
+#[cfg(feature = "ssr")]
+pub async fn update_estate_status(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    from: EstateStatus,
+    to: EstateStatus,
+) -> Result<bool, Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE estates
+        SET status = $1,
+            published_at = CASE WHEN $1 = 'published' THEN NOW() ELSE published_at END,
+            reserved_at = CASE WHEN $1 = 'reserved' THEN NOW() ELSE reserved_at END,
+            sold_at = CASE WHEN $1 = 'sold' THEN NOW() ELSE sold_at END,
+            archived_at = CASE WHEN $1 = 'archived' THEN NOW() ELSE archived_at END,
+            publish_at = CASE WHEN $1 = 'published' THEN NULL ELSE publish_at END,
+            expire_at = CASE WHEN $1 = 'published' AND expire_at <= NOW() THEN NULL ELSE expire_at END,
+            updated_at = NOW()
+        WHERE id = $2 AND status = $3
+        "#,
+        to as EstateStatus,
+        id,
+        from as EstateStatus
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn update_estate_schedule(
+    executor: impl PgExecutor<'_>,
+    id: Uuid,
+    schedule: &ListingSchedule,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE estates
+        SET publish_at = $1::TEXT::TIMESTAMPTZ, expire_at = $2::TEXT::TIMESTAMPTZ, updated_at = NOW()
+        WHERE id = $3
+        "#,
+        schedule.publish_at.as_deref(),
+        schedule.expire_at.as_deref(),
+        id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Pushes the expiry of a published estate `days` past the later of now and
+/// its current expiry. Returns `false` for estates that are not published
+/// or do not expire.
+#[cfg(feature = "ssr")]
+pub async fn renew_estate(pool: &PgPool, id: Uuid, days: i32) -> Result<bool, Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE estates
+        SET expire_at = GREATEST(expire_at, NOW()) + make_interval(days => $1), updated_at = NOW()
+        WHERE id = $2 AND status = 'published' AND expire_at IS NOT NULL
+        "#,
+        days,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
+
+/// Published estates expiring within `days`, soonest first, limited to one
+/// user's estates when `managed_by` is set.
+#[cfg(feature = "ssr")]
+pub async fn get_expiring_estates(
     pool: &PgPool,
+    days: i32,
+    managed_by: Option<Uuid>,
+) -> Result<Vec<Estate>, Error> {
+    let estates = sqlx::query_as!(
+        Estate,
+        r#"
+        SELECT id, name, address, image_key AS image_url, thumbnail_key AS thumbnail_url, description, price_in_cents, space_in_meters, created_by, assigned_agent,
+            property_type AS "property_type: PropertyType", bedrooms, bathrooms, floor, finishing AS "finishing: Finishing", furnished,
+            purpose AS "purpose: ListingPurpose", down_payment_in_cents, installment_years,
+            installment_frequency AS "installment_frequency: InstallmentFrequency", installment_in_cents,
+            status AS "status: EstateStatus", TO_CHAR(published_at, 'YYYY-MM-DD HH24:MI') AS published_at,
+            TO_CHAR(reserved_at, 'YYYY-MM-DD HH24:MI') AS reserved_at, TO_CHAR(sold_at, 'YYYY-MM-DD HH24:MI') AS sold_at,
+            TO_CHAR(archived_at, 'YYYY-MM-DD HH24:MI') AS archived_at,
+            TO_CHAR(publish_at, 'YYYY-MM-DD"T"HH24:MI') AS publish_at, TO_CHAR(expire_at, 'YYYY-MM-DD"T"HH24:MI') AS expire_at
+        FROM estates
+        WHERE status = 'published' AND expire_at <= NOW() + make_interval(days => $1) AND deleted_at IS NULL
+          AND ($2::UUID IS NULL OR created_by = $2 OR assigned_agent = $2)
+        ORDER BY expire_at
+        "#,
+        days,
+        managed_by
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let base = crate::storage::public_base();
+    Ok(estates
+        .into_iter()
+        .map(|x| x.with_public_urls(base))
+        .collect())
+}
+
+/// Publishes every draft whose `publish_at` has passed, unless it would
+/// already be expired, and returns their ids.
+#[cfg(feature = "ssr")]
+pub async fn publish_scheduled_estates(pool: &PgPool) -> Result<Vec<Uuid>, Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE estates
+        SET status = 'published', published_at = NOW(), publish_at = NULL, updated_at = NOW()
+        WHERE status = 'draft' AND publish_at <= NOW() AND deleted_at IS NULL
+          AND (expire_at IS NULL OR expire_at > NOW())
+        RETURNING id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Archives every published estate whose `expire_at` has passed and
+/// returns their ids.
+#[cfg(feature = "ssr")]
+pub async fn archive_expired_estates(pool: &PgPool) -> Result<Vec<Uuid>, Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE estates
+        SET status = 'archived', archived_at = NOW(), updated_at = NOW()
+        WHERE status = 'published' AND expire_at <= NOW() AND deleted_at IS NULL
+        RETURNING id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(feature = "ssr")]
+pub async fn update_estate_assigned_agent(
+    executor: impl PgExecutor<'_>,
     id: Uuid,
-    space_in_meters: i32,
+    assigned_agent: Uuid,
 ) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE estates
-        SET space_in_meters = $1, updated_at = NOW()
+        SET assigned_agent = $1, updated_at = NOW()
         WHERE id = $2
         "#,
-        space_in_meters,
+        assigned_agent,
         id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
+/// Moves the estate to the trash. Its images and videos stay in storage
+/// until [`purge_deleted_estates`] removes the estate for good.
 #[cfg(feature = "ssr")]
 pub async fn delete_estate(pool: &PgPool, id: Uuid) -> Result<(), Error> {
     sqlx::query!(
         r#"
-        DELETE FROM estates
-        WHERE id = $1
+        UPDATE estates
+        SET deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         id
     )
@@ -212,11 +648,96 @@ pub async fn delete_estate(pool: &PgPool, id: Uuid) -> Result<(), Error> {
     Ok(())
 }
 
+/// Takes the estate back out of the trash. Returns `false` when it is not
+/// there or, with `managed_by` set, not managed by that user.
+#[cfg(feature = "ssr")]
+pub async fn restore_estate(
+    pool: &PgPool,
+    id: Uuid,
+    managed_by: Option<Uuid>,
+) -> Result<bool, Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE estates
+        SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+          AND ($2::UUID IS NULL OR created_by = $2 OR assigned_agent = $2)
+        "#,
+        id,
+        managed_by
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
+
+/// Estates in the trash, most recently deleted first, with when each is
+/// purged after `retention_days`. Limited to one user's estates when
+/// `managed_by` is set.
+#[cfg(feature = "ssr")]
+pub async fn get_deleted_estates(
+    pool: &PgPool,
+    retention_days: i32,
+    managed_by: Option<Uuid>,
+) -> Result<Vec<DeletedEstate>, Error> {
+    sqlx::query_as!(
+        DeletedEstate,
+        r#"
+        SELECT id, name, address, TO_CHAR(deleted_at, 'YYYY-MM-DD HH24:MI') AS "deleted_at!",
+            TO_CHAR(deleted_at + make_interval(days => $1), 'YYYY-MM-DD HH24:MI') AS "purge_at!"
+        FROM estates
+        WHERE deleted_at IS NOT NULL
+          AND ($2::UUID IS NULL OR created_by = $2 OR assigned_agent = $2)
+        ORDER BY deleted_at DESC
+        "#,
+        retention_days,
+        managed_by
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Ids of the estates that have been in the trash for more than
+/// `retention_days`.
+#[cfg(feature = "ssr")]
+pub async fn get_purgeable_estate_ids(
+    pool: &PgPool,
+    retention_days: i32,
+) -> Result<Vec<Uuid>, Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM estates
+        WHERE deleted_at <= NOW() - make_interval(days => $1)
+        "#,
+        retention_days
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Deletes an estate in the trash for good, its image and video rows with
+/// it. Returns `false` when it was restored in the meantime.
+#[cfg(feature = "ssr")]
+pub async fn purge_estate(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+    let res = sqlx::query!(
+        r#"
+        DELETE FROM estates
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
+
 #[cfg(feature = "ssr")]
 pub async fn count_estates(pool: &PgPool) -> Result<i64, Error> {
     let count = sqlx::query_scalar!(
         r#"
-        SELECT COUNT(*) FROM estates
+        SELECT COUNT(*) FROM estates WHERE deleted_at IS NULL
         "#,
     )
     .fetch_one(pool)
@@ -0,0 +1,180 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "ssr")]
+use super::models::{ImageKeys, ImageUpload};
+
+#[cfg(feature = "ssr")]
+pub async fn create_image_upload(
+    pool: &PgPool,
+    object_key: &str,
+    user_id: Uuid,
+    token_hash: &str,
+    content_type: &str,
+    size: i64,
+    expires_in_secs: f64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO image_uploads (object_key, user_id, token_hash, content_type, size, expires_at)
+        VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(secs => $6))
+        "#,
+        object_key,
+        user_id,
+        token_hash,
+        content_type,
+        size,
+        expires_in_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_image_upload(
+    pool: &PgPool,
+    object_key: &str,
+) -> Result<Option<ImageUpload>, Error> {
+    let upload = sqlx::query_as!(
+        ImageUpload,
+        r#"
+        SELECT object_key, user_id, token_hash, content_type, size,
+            expires_at < NOW() AS "expired!"
+        FROM image_uploads
+        WHERE object_key = $1
+        "#,
+        object_key
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(upload)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn delete_image_upload(pool: &PgPool, object_key: &str) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM image_uploads
+        WHERE object_key = $1
+        "#,
+        object_key
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Drops uploads that expired more than `older_than_secs` ago and were
+/// never finalized. Their objects are left to image reconciliation.
+#[cfg(feature = "ssr")]
+pub async fn delete_expired_image_uploads(
+    pool: &PgPool,
+    older_than_secs: f64,
+) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM image_uploads
+        WHERE expires_at < NOW() - make_interval(secs => $1)
+        "#,
+        older_than_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Keeps a finalized upload until the estate form it was made for is
+/// submitted, returning the id the form refers to it by.
+#[cfg(feature = "ssr")]
+pub async fn create_pending_image(
+    pool: &PgPool,
+    user_id: Uuid,
+    image: &ImageKeys,
+) -> Result<Uuid, Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO pending_images (user_id, image_key, thumbnail_key, medium_key, large_key)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        user_id,
+        image.original,
+        image.thumbnail,
+        image.medium,
+        image.large
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// The pending images of `user_id` among `ids`, in the order of `ids`.
+/// Images uploaded by anyone else are left out.
+#[cfg(feature = "ssr")]
+pub async fn get_pending_images(
+    pool: &PgPool,
+    user_id: Uuid,
+    ids: &[Uuid],
+) -> Result<Vec<ImageKeys>, Error> {
+    let images = sqlx::query_as!(
+        ImageKeys,
+        r#"
+        SELECT p.image_key AS "original!", p.thumbnail_key AS thumbnail,
+            p.medium_key AS medium, p.large_key AS large
+        FROM UNNEST($1::UUID[]) WITH ORDINALITY AS x(id, n)
+        JOIN pending_images p ON p.id = x.id
+        WHERE p.user_id = $2
+        ORDER BY x.n
+        "#,
+        ids,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(images)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn delete_pending_images(pool: &PgPool, ids: &[Uuid]) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM pending_images
+        WHERE id = ANY($1)
+        "#,
+        ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Drops pending images older than `older_than_secs`, whose form was never
+/// submitted. Their objects are left to image reconciliation.
+#[cfg(feature = "ssr")]
+pub async fn delete_stale_pending_images(
+    pool: &PgPool,
+    older_than_secs: f64,
+) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM pending_images
+        WHERE created_at < NOW() - make_interval(secs => $1)
+        "#,
+        older_than_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
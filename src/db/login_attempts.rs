@@ -0,0 +1,123 @@
+#[cfg(feature = "ssr")]
+use sqlx::{Error, PgExecutor, PgPool};
+
+#[cfg(feature = "ssr")]
+use crate::auth::throttle::{AttemptState, ThrottlePolicy};
+
+pub const USERNAME_KIND: &str = "username";
+pub const IP_KIND: &str = "ip";
+
+#[cfg(feature = "ssr")]
+pub async fn get_attempt_state(
+    pool: &PgPool,
+    kind: &str,
+    key: &str,
+) -> Result<AttemptState, Error> {
+    let state = sqlx::query!(
+        r#"
+        SELECT
+            failed_count,
+            EXTRACT(EPOCH FROM NOW() - last_failed_at)::BIGINT AS "secs_since_last_failure!",
+            COALESCE(EXTRACT(EPOCH FROM locked_until - NOW())::BIGINT, 0) AS "lock_remaining_secs!"
+        FROM login_attempts
+        WHERE kind = $1 AND key = $2
+        "#,
+        kind,
+        key
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|x| AttemptState {
+        failed_count: x.failed_count,
+        secs_since_last_failure: x.secs_since_last_failure,
+        lock_remaining_secs: x.lock_remaining_secs,
+    })
+    .unwrap_or_default();
+
+    Ok(state)
+}
+
+/// Like [`get_attempt_state`], but locks the row for the rest of the
+/// transaction, creating it when missing, so concurrent logins against the
+/// same key take turns.
+#[cfg(feature = "ssr")]
+pub async fn lock_attempt_state(
+    executor: impl PgExecutor<'_>,
+    kind: &str,
+    key: &str,
+) -> Result<AttemptState, Error> {
+    let state = sqlx::query!(
+        r#"
+        INSERT INTO login_attempts (kind, key)
+        VALUES ($1, $2)
+        ON CONFLICT (kind, key) DO UPDATE SET kind = EXCLUDED.kind
+        RETURNING
+            failed_count,
+            EXTRACT(EPOCH FROM NOW() - last_failed_at)::BIGINT AS "secs_since_last_failure!",
+            COALESCE(EXTRACT(EPOCH FROM locked_until - NOW())::BIGINT, 0) AS "lock_remaining_secs!"
+        "#,
+        kind,
+        key
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(AttemptState {
+        failed_count: state.failed_count,
+        secs_since_last_failure: state.secs_since_last_failure,
+        lock_remaining_secs: state.lock_remaining_secs,
+    })
+}
+
+/// Counts a failed login against `key`, locking it once `policy.lock_after`
+/// failures pile up. A lock that already expired starts a fresh count.
+#[cfg(feature = "ssr")]
+pub async fn record_failure(
+    executor: impl PgExecutor<'_>,
+    kind: &str,
+    key: &str,
+    policy: &ThrottlePolicy,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO login_attempts (kind, key, failed_count, last_failed_at)
+        VALUES ($1, $2, 1, NOW())
+        ON CONFLICT (kind, key) DO UPDATE SET
+            failed_count = CASE
+                WHEN login_attempts.locked_until <= NOW() THEN 1
+                ELSE login_attempts.failed_count + 1
+            END,
+            locked_until = CASE
+                WHEN login_attempts.locked_until <= NOW() THEN NULL
+                WHEN login_attempts.failed_count + 1 >= $3
+                    THEN NOW() + make_interval(secs => $4)
+                ELSE login_attempts.locked_until
+            END,
+            last_failed_at = NOW()
+        "#,
+        kind,
+        key,
+        policy.lock_after,
+        policy.lock_secs as f64
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn clear_attempts(pool: &PgPool, kind: &str, key: &str) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM login_attempts
+        WHERE kind = $1 AND key = $2
+        "#,
+        kind,
+        key
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
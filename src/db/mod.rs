@@ -6,8 +6,18 @@ use sqlx::{Pool, Postgres};
 use std::time::Duration;
 
 pub use crate::models;
-pub mod users;
+pub mod api_tokens;
+pub mod audit;
+pub mod estate_images;
+pub mod estate_videos;
 pub mod estates;
+pub mod image_uploads;
+pub mod login_attempts;
+pub mod permissions;
+pub mod sessions;
+pub mod two_factor;
+pub mod users;
+pub mod video_uploads;
 
 #[cfg(feature = "ssr")]
 pub type DbPool = Pool<Postgres>;
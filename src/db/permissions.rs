@@ -0,0 +1,90 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    std::str::FromStr,
+};
+
+#[cfg(feature = "ssr")]
+use crate::auth::{Level, Permission};
+
+#[cfg(feature = "ssr")]
+pub async fn get_level_permissions(pool: &PgPool, level: &Level) -> Result<Vec<Permission>, Error> {
+    let rows = sqlx::query_scalar!(
+        r#"
+        SELECT permission
+        FROM role_permissions
+        WHERE level = $1
+        "#,
+        &level.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|x| Permission::from_str(x).ok())
+        .collect())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_role_permissions(pool: &PgPool) -> Result<Vec<(Level, Permission)>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT level, permission
+        FROM role_permissions
+        ORDER BY level, permission
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|x| {
+            let level = Level::from_str(&x.level).ok()?;
+            let permission = Permission::from_str(&x.permission).ok()?;
+            Some((level, permission))
+        })
+        .collect())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn grant_permission(
+    pool: &PgPool,
+    level: &Level,
+    permission: Permission,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO role_permissions (level, permission)
+        VALUES ($1, $2)
+        ON CONFLICT (level, permission) DO NOTHING
+        "#,
+        &level.to_string(),
+        permission.code()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn revoke_permission(
+    pool: &PgPool,
+    level: &Level,
+    permission: Permission,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM role_permissions
+        WHERE level = $1 AND permission = $2
+        "#,
+        &level.to_string(),
+        permission.code()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "ssr")]
+use super::models::UserSession;
+
+/// Session records live in the `tower_sessions` schema created by
+/// `PostgresStore::migrate`, so queries touching it are checked at runtime.
+#[cfg(feature = "ssr")]
+const REVOKE_SESSIONS: &str = r#"
+    DELETE FROM tower_sessions.session
+    WHERE id = ANY($1)
+"#;
+
+#[cfg(feature = "ssr")]
+pub async fn create_user_session(
+    pool: &PgPool,
+    session_id: &str,
+    user_id: Uuid,
+    user_agent: Option<&str>,
+    ip: &str,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_sessions (session_id, user_id, user_agent, ip)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (session_id) DO UPDATE
+        SET user_id = $2, user_agent = $3, ip = $4, created_at = NOW(), last_seen_at = NOW()
+        "#,
+        session_id,
+        user_id,
+        user_agent,
+        ip
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Refreshes `last_seen_at` and returns the owner, `None` once revoked.
+#[cfg(feature = "ssr")]
+pub async fn touch_user_session(pool: &PgPool, session_id: &str) -> Result<Option<Uuid>, Error> {
+    let user_id = sqlx::query_scalar!(
+        r#"
+        UPDATE user_sessions
+        SET last_seen_at = NOW()
+        WHERE session_id = $1
+        RETURNING user_id
+        "#,
+        session_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user_id)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_user_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    current_session_id: Option<&str>,
+) -> Result<Vec<UserSession>, Error> {
+    let sessions = sqlx::query_as::<_, UserSession>(
+        r#"
+        SELECT
+            us.id,
+            us.user_agent,
+            us.ip,
+            TO_CHAR(us.created_at, 'YYYY-MM-DD HH24:MI') AS created_at,
+            TO_CHAR(us.last_seen_at, 'YYYY-MM-DD HH24:MI') AS last_seen_at,
+            us.session_id = COALESCE($2, '') AS current
+        FROM user_sessions us
+        JOIN tower_sessions.session s ON s.id = us.session_id
+        WHERE us.user_id = $1 AND s.expiry_date > NOW()
+        ORDER BY us.last_seen_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(current_session_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn revoke_user_session(pool: &PgPool, user_id: Uuid, id: Uuid) -> Result<(), Error> {
+    let session_ids = sqlx::query_scalar!(
+        r#"
+        DELETE FROM user_sessions
+        WHERE user_id = $1 AND id = $2
+        RETURNING session_id
+        "#,
+        user_id,
+        id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    sqlx::query(REVOKE_SESSIONS)
+        .bind(session_ids)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Revokes every session of the user except `keep_session_id`.
+#[cfg(feature = "ssr")]
+pub async fn revoke_user_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    keep_session_id: Option<&str>,
+) -> Result<(), Error> {
+    let session_ids = sqlx::query_scalar!(
+        r#"
+        DELETE FROM user_sessions
+        WHERE user_id = $1 AND session_id <> COALESCE($2, '')
+        RETURNING session_id
+        "#,
+        user_id,
+        keep_session_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    sqlx::query(REVOKE_SESSIONS)
+        .bind(session_ids)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Drops the tracking row of a session that is being logged out.
+#[cfg(feature = "ssr")]
+pub async fn forget_user_session(pool: &PgPool, session_id: &str) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM user_sessions
+        WHERE session_id = $1
+        "#,
+        session_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "ssr")]
+pub async fn enable_totp(pool: &PgPool, user_id: Uuid, secret: &str) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = $1, totp_enabled = TRUE, updated_at = NOW()
+        WHERE id = $2
+        "#,
+        secret,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Removes the user's TOTP secret and every recovery code.
+#[cfg(feature = "ssr")]
+pub async fn disable_totp(pool: &PgPool, user_id: Uuid) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = NULL, totp_enabled = FALSE, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM recovery_codes
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn set_totp_required(pool: &PgPool, user_id: Uuid, required: bool) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_required = $1, updated_at = NOW()
+        WHERE id = $2
+        "#,
+        required,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Replaces all of the user's recovery codes with `code_hashes`.
+#[cfg(feature = "ssr")]
+pub async fn replace_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hashes: &[String],
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM recovery_codes
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO recovery_codes (user_id, code_hash)
+        SELECT $1, UNNEST($2::TEXT[])
+        "#,
+        user_id,
+        code_hashes
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_unused_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<(Uuid, String)>, Error> {
+    let codes = sqlx::query!(
+        r#"
+        SELECT id, code_hash
+        FROM recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|x| (x.id, x.code_hash))
+    .collect();
+
+    Ok(codes)
+}
+
+/// Marks a recovery code as used, returning `false` when it already was.
+#[cfg(feature = "ssr")]
+pub async fn use_recovery_code(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE recovery_codes
+        SET used_at = NOW()
+        WHERE id = $1 AND used_at IS NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn count_unused_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<i64, Error> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
@@ -5,39 +5,50 @@ use {
 };
 
 #[cfg(feature = "ssr")]
-use super::models::User;
+use super::models::{DeletedUser, User};
 #[cfg(feature = "ssr")]
 use crate::auth::Level;
 
+/// Creates a user that has to pick a new password on first login, since the
+/// initial one was chosen by whoever created the account.
+/// Whether `e` is the database refusing a name another user outside the
+/// trash already has.
+#[cfg(feature = "ssr")]
+pub fn is_name_taken(e: &Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|x| x.is_unique_violation())
+}
+
 #[cfg(feature = "ssr")]
 pub async fn create_user(
     pool: &PgPool,
     name: String,
     password: String,
     level: Level,
-) -> Result<(), Error> {
-    sqlx::query!(
+) -> Result<Uuid, Error> {
+    let id = sqlx::query_scalar!(
         r#"
-        INSERT INTO users (name, password, level)
-        VALUES ($1, $2, $3)
+        INSERT INTO users (name, password, level, must_change_password)
+        VALUES ($1, $2, $3, TRUE)
+        RETURNING id
         "#,
         &name,
         &password,
         &level.to_string()
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok(())
+    Ok(id)
 }
 
 #[cfg(feature = "ssr")]
 pub async fn get_user_by_id(pool: &PgPool, id: Uuid) -> Result<User, Error> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, name, password, level, created_at, updated_at
+        SELECT id, name, password, level, must_change_password, totp_secret, totp_enabled, totp_required, created_at, updated_at
         FROM users
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(id)
@@ -51,9 +62,9 @@ pub async fn get_user_by_id(pool: &PgPool, id: Uuid) -> Result<User, Error> {
 pub async fn get_user_by_name(pool: &PgPool, name: &str) -> Result<User, Error> {
     let user = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, name, password, level, created_at, updated_at
+        SELECT id, name, password, level, must_change_password, totp_secret, totp_enabled, totp_required, created_at, updated_at
         FROM users
-        WHERE name = $1
+        WHERE name = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(name)
@@ -67,8 +78,9 @@ pub async fn get_user_by_name(pool: &PgPool, name: &str) -> Result<User, Error>
 pub async fn get_all_users(pool: &PgPool) -> Result<Vec<User>, Error> {
     let users = sqlx::query_as::<_, User>(
         r#"
-        SELECT id, name, password, level, created_at, updated_at
+        SELECT id, name, password, level, must_change_password, totp_secret, totp_enabled, totp_required, created_at, updated_at
         FROM users
+        WHERE deleted_at IS NULL
         ORDER BY created_at DESC
         "#,
     )
@@ -96,14 +108,20 @@ pub async fn update_user_name(pool: &PgPool, id: Uuid, name: String) -> Result<(
 }
 
 #[cfg(feature = "ssr")]
-pub async fn update_user_password(pool: &PgPool, id: Uuid, password: String) -> Result<(), Error> {
+pub async fn update_user_password(
+    pool: &PgPool,
+    id: Uuid,
+    password: String,
+    must_change_password: bool,
+) -> Result<(), Error> {
     sqlx::query!(
         r#"
         UPDATE users
-        SET password = $1, updated_at = NOW()
-        WHERE id = $2
+        SET password = $1, must_change_password = $2, updated_at = NOW()
+        WHERE id = $3
         "#,
         &password,
+        must_change_password,
         id
     )
     .execute(pool)
@@ -129,12 +147,15 @@ pub async fn update_user_level(pool: &PgPool, id: Uuid, level: Level) -> Result<
     Ok(())
 }
 
+/// Moves the user to the trash; they can no longer sign in or use their
+/// api tokens until restored.
 #[cfg(feature = "ssr")]
 pub async fn delete_user(pool: &PgPool, id: Uuid) -> Result<(), Error> {
     sqlx::query(
         r#"
-        DELETE FROM users
-        WHERE id = $1
+        UPDATE users
+        SET deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(id)
@@ -144,11 +165,88 @@ pub async fn delete_user(pool: &PgPool, id: Uuid) -> Result<(), Error> {
     Ok(())
 }
 
+#[cfg(feature = "ssr")]
+pub async fn get_deleted_user_by_id(pool: &PgPool, id: Uuid) -> Result<User, Error> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        SELECT id, name, password, level, must_change_password, totp_secret, totp_enabled, totp_required, created_at, updated_at
+        FROM users
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
+/// Takes the user back out of the trash. Returns `false` when they are not
+/// there; fails with [`is_name_taken`] when someone else has been given
+/// their name meanwhile.
+#[cfg(feature = "ssr")]
+pub async fn restore_user(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+    let res = sqlx::query(
+        r#"
+        UPDATE users
+        SET deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+}
+
+/// Users in the trash, most recently deleted first, with when each is
+/// purged after `retention_days`.
+#[cfg(feature = "ssr")]
+pub async fn get_deleted_users(
+    pool: &PgPool,
+    retention_days: i32,
+) -> Result<Vec<DeletedUser>, Error> {
+    let users = sqlx::query_as::<_, DeletedUser>(
+        r#"
+        SELECT id, name, level, TO_CHAR(deleted_at, 'YYYY-MM-DD HH24:MI') AS deleted_at,
+            TO_CHAR(deleted_at + make_interval(days => $1), 'YYYY-MM-DD HH24:MI') AS purge_at
+        FROM users
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC
+        "#,
+    )
+    .bind(retention_days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(users)
+}
+
+/// Deletes the users that have been in the trash for more than
+/// `retention_days` for good and returns their ids. Their estates and audit
+/// entries are kept without them.
+#[cfg(feature = "ssr")]
+pub async fn purge_deleted_users(pool: &PgPool, retention_days: i32) -> Result<Vec<Uuid>, Error> {
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        DELETE FROM users
+        WHERE deleted_at <= NOW() - make_interval(days => $1)
+        RETURNING id
+        "#,
+    )
+    .bind(retention_days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids)
+}
+
 #[cfg(feature = "ssr")]
 pub async fn count_users(pool: &PgPool) -> Result<i64, Error> {
     let count = sqlx::query_scalar::<_, i64>(
         r#"
-        SELECT COUNT(*) FROM users
+        SELECT COUNT(*) FROM users WHERE deleted_at IS NULL
         "#,
     )
     .fetch_one(pool)
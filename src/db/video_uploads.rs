@@ -0,0 +1,168 @@
+#[cfg(feature = "ssr")]
+use {
+    sqlx::{Error, PgPool},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "ssr")]
+use super::models::VideoUpload;
+
+#[cfg(feature = "ssr")]
+pub async fn create_video_upload(
+    pool: &PgPool,
+    upload: &VideoUpload,
+    expires_in_secs: f64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO video_uploads (upload_id, object_key, estate_id, user_id, file_name,
+            content_type, size, token_hash, token_expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW() + make_interval(secs => $9))
+        "#,
+        upload.upload_id,
+        upload.object_key,
+        upload.estate_id,
+        upload.user_id,
+        upload.file_name,
+        upload.content_type,
+        upload.size,
+        upload.token_hash,
+        expires_in_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// An unfinished upload of the same file by the same user, to resume.
+#[cfg(feature = "ssr")]
+pub async fn find_video_upload(
+    pool: &PgPool,
+    estate_id: Uuid,
+    user_id: Uuid,
+    file_name: &str,
+    size: i64,
+) -> Result<Option<VideoUpload>, Error> {
+    let upload = sqlx::query_as!(
+        VideoUpload,
+        r#"
+        SELECT upload_id, object_key, estate_id, user_id, file_name, content_type, size, token_hash
+        FROM video_uploads
+        WHERE estate_id = $1 AND user_id = $2 AND file_name = $3 AND size = $4
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        estate_id,
+        user_id,
+        file_name,
+        size
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(upload)
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_video_upload(
+    pool: &PgPool,
+    upload_id: &str,
+) -> Result<Option<VideoUpload>, Error> {
+    let upload = sqlx::query_as!(
+        VideoUpload,
+        r#"
+        SELECT upload_id, object_key, estate_id, user_id, file_name, content_type, size, token_hash
+        FROM video_uploads
+        WHERE upload_id = $1
+        "#,
+        upload_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(upload)
+}
+
+/// The upload a part url points at, as long as the url's token is valid.
+#[cfg(feature = "ssr")]
+pub async fn get_video_upload_by_key(
+    pool: &PgPool,
+    object_key: &str,
+) -> Result<Option<VideoUpload>, Error> {
+    let upload = sqlx::query_as!(
+        VideoUpload,
+        r#"
+        SELECT upload_id, object_key, estate_id, user_id, file_name, content_type, size, token_hash
+        FROM video_uploads
+        WHERE object_key = $1 AND token_expires_at > NOW()
+        "#,
+        object_key
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(upload)
+}
+
+/// Replaces the token when an upload is resumed; only its hash is kept, so
+/// the old one cannot be handed out again.
+#[cfg(feature = "ssr")]
+pub async fn set_video_upload_token(
+    pool: &PgPool,
+    upload_id: &str,
+    token_hash: &str,
+    expires_in_secs: f64,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE video_uploads
+        SET token_hash = $2, token_expires_at = NOW() + make_interval(secs => $3)
+        WHERE upload_id = $1
+        "#,
+        upload_id,
+        token_hash,
+        expires_in_secs
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+pub async fn delete_video_upload(pool: &PgPool, upload_id: &str) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM video_uploads
+        WHERE upload_id = $1
+        "#,
+        upload_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Uploads started more than `older_than_secs` ago, which nobody is going
+/// to finish any more.
+#[cfg(feature = "ssr")]
+pub async fn stale_video_uploads(
+    pool: &PgPool,
+    older_than_secs: f64,
+) -> Result<Vec<VideoUpload>, Error> {
+    let uploads = sqlx::query_as!(
+        VideoUpload,
+        r#"
+        SELECT upload_id, object_key, estate_id, user_id, file_name, content_type, size, token_hash
+        FROM video_uploads
+        WHERE created_at < NOW() - make_interval(secs => $1)
+        "#,
+        older_than_secs
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(uploads)
+}
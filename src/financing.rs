@@ -0,0 +1,162 @@
+//! Mortgage and installment quotes. The calculator island and the printable
+//! quote page both go through [`quote`], so a visitor sees the same numbers
+//! on screen and on paper.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{InstallmentFrequency, PaymentPlan};
+
+/// Longest term a quote is made for.
+pub const MAX_YEARS: i32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuoteRequest {
+    pub price_in_cents: i64,
+    /// Share of the price paid upfront, from 0 to 100.
+    pub down_payment_percent: f64,
+    pub years: i32,
+    /// Yearly interest in percent; 0 for developer plans without interest.
+    pub annual_rate_percent: f64,
+    pub frequency: InstallmentFrequency,
+}
+
+impl QuoteRequest {
+    /// Starting point for an estate: its installment plan when it has one,
+    /// otherwise a fifth down over ten years.
+    pub fn for_estate(price_in_cents: i64, plan: Option<PaymentPlan>) -> Self {
+        match plan {
+            Some(plan) if price_in_cents > 0 => Self {
+                price_in_cents,
+                down_payment_percent: (plan.down_payment_in_cents as f64 * 100.0
+                    / price_in_cents as f64)
+                    .clamp(0.0, 100.0),
+                years: plan.years.clamp(1, MAX_YEARS),
+                annual_rate_percent: 0.0,
+                frequency: plan.frequency,
+            },
+            _ => Self {
+                price_in_cents,
+                down_payment_percent: 20.0,
+                years: 10,
+                annual_rate_percent: 0.0,
+                frequency: InstallmentFrequency::Monthly,
+            },
+        }
+    }
+}
+
+/// One installment of the amortization schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Installment {
+    /// Counted from 1.
+    pub number: i32,
+    pub payment_in_cents: i64,
+    pub principal_in_cents: i64,
+    pub interest_in_cents: i64,
+    /// What is still owed after this installment.
+    pub balance_in_cents: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub request: QuoteRequest,
+    pub down_payment_in_cents: i64,
+    pub financed_in_cents: i64,
+    /// The regular installment; the last one may be a little smaller.
+    pub installment_in_cents: i64,
+    /// Down payment plus every installment.
+    pub total_paid_in_cents: i64,
+    pub total_interest_in_cents: i64,
+    pub schedule: Vec<Installment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteError {
+    Price,
+    DownPayment,
+    Term,
+    Rate,
+}
+
+impl std::fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuoteError::Price => write!(f, "the price must be positive"),
+            QuoteError::DownPayment => write!(f, "the down payment must be between 0 and 100%"),
+            QuoteError::Term => write!(f, "the term must be between 1 and {MAX_YEARS} years"),
+            QuoteError::Rate => write!(f, "the interest rate must be between 0 and 100%"),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+/// Splits what is left after the down payment into equal installments that
+/// pay off the loan with interest on the outstanding balance (an annuity).
+/// Amounts are rounded to whole cents; the last installment settles what
+/// rounding left over.
+pub fn quote(request: QuoteRequest) -> Result<Quote, QuoteError> {
+    if request.price_in_cents <= 0 {
+        return Err(QuoteError::Price);
+    }
+    if !(0.0..=100.0).contains(&request.down_payment_percent) {
+        return Err(QuoteError::DownPayment);
+    }
+    if !(1..=MAX_YEARS).contains(&request.years) {
+        return Err(QuoteError::Term);
+    }
+    if !(0.0..=100.0).contains(&request.annual_rate_percent) {
+        return Err(QuoteError::Rate);
+    }
+
+    let down_payment_in_cents =
+        ((request.price_in_cents as f64 * request.down_payment_percent / 100.0).round() as i64)
+            .min(request.price_in_cents);
+    let financed_in_cents = request.price_in_cents - down_payment_in_cents;
+    let count = request.years * request.frequency.per_year();
+    let rate = request.annual_rate_percent / 100.0 / request.frequency.per_year() as f64;
+
+    let financed = financed_in_cents as f64;
+    let installment_in_cents = if financed_in_cents == 0 {
+        0
+    } else if rate == 0.0 {
+        (financed / count as f64).ceil() as i64
+    } else {
+        (financed * rate / (1.0 - (1.0 + rate).powi(-count))).round() as i64
+    };
+
+    let mut schedule = Vec::with_capacity(count as usize);
+    let mut balance = financed_in_cents;
+    for number in 1..=count {
+        if balance == 0 {
+            break;
+        }
+        let interest_in_cents = (balance as f64 * rate).round() as i64;
+        let principal_in_cents = if number == count {
+            balance
+        } else {
+            (installment_in_cents - interest_in_cents).min(balance)
+        };
+        balance -= principal_in_cents;
+        schedule.push(Installment {
+            number,
+            payment_in_cents: principal_in_cents + interest_in_cents,
+            principal_in_cents,
+            interest_in_cents,
+            balance_in_cents: balance,
+        });
+    }
+
+    let total_interest_in_cents = schedule.iter().map(|x| x.interest_in_cents).sum();
+    let total_paid_in_cents =
+        down_payment_in_cents + schedule.iter().map(|x| x.payment_in_cents).sum::<i64>();
+    Ok(Quote {
+        request,
+        down_payment_in_cents,
+        financed_in_cents,
+        installment_in_cents,
+        total_paid_in_cents,
+        total_interest_in_cents,
+        schedule,
+    })
+}
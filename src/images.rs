@@ -0,0 +1,386 @@
+//! Upload pipeline for estate photos: the format is detected from the file's
+//! magic bytes, never from the client, and every image is decoded, turned
+//! upright and re-encoded. Re-encoding drops all EXIF metadata, GPS
+//! coordinates included.
+
+use std::io::Cursor;
+
+use image::{
+    DynamicImage, ImageDecoder, ImageReader, codecs::jpeg::JpegEncoder, codecs::png::PngEncoder,
+    imageops::FilterType,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{ImageKeys, ImageUpload, ImageVariant},
+    storage::ImageStorage,
+};
+
+/// Upload size limit unless `IMAGE_MAX_BYTES` says otherwise.
+pub const DEFAULT_MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+const ORIGINAL_QUALITY: u8 = 90;
+const VARIANT_QUALITY: f32 = 80.0;
+
+pub fn max_image_bytes() -> usize {
+    std::env::var("IMAGE_MAX_BYTES")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(DEFAULT_MAX_IMAGE_BYTES)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageKind {
+    Jpeg,
+    Png,
+    WebP,
+    Heic,
+}
+
+impl ImageKind {
+    /// Sniffs the format from the leading bytes of the file.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        const HEIC_BRANDS: [&[u8]; 7] = [
+            b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1",
+        ];
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageKind::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageKind::Png)
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(ImageKind::WebP)
+        } else if data.len() >= 12 && &data[4..8] == b"ftyp" && HEIC_BRANDS.contains(&&data[8..12])
+        {
+            Some(ImageKind::Heic)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a browser's `File.type`, the only hint there is before the
+    /// bytes arrive.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type {
+            "image/jpeg" => Some(ImageKind::Jpeg),
+            "image/png" => Some(ImageKind::Png),
+            "image/webp" => Some(ImageKind::WebP),
+            "image/heic" | "image/heif" => Some(ImageKind::Heic),
+            _ => None,
+        }
+    }
+}
+
+/// Checks a direct upload against what the browser announced before
+/// [`process`] does the real validation.
+pub fn verify_upload(data: &[u8], size: i64, content_type: &str) -> Result<(), ImageError> {
+    if data.len() as i64 != size {
+        return Err(ImageError::Mismatch);
+    }
+    let declared = ImageKind::from_content_type(content_type).ok_or(ImageError::Unsupported)?;
+    match ImageKind::detect(data) {
+        Some(kind) if kind == declared => Ok(()),
+        Some(_) => Err(ImageError::Mismatch),
+        None => Err(ImageError::Unsupported),
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    TooLarge(usize),
+    Unsupported,
+    /// A direct upload differs from the size or type it was announced with.
+    Mismatch,
+    /// A direct upload that was never issued, belongs to someone else or
+    /// never reached storage.
+    UploadNotFound,
+    HeicDisabled,
+    Decode(String),
+    Encode(String),
+    Storage(String),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::TooLarge(limit) => {
+                write!(f, "the image is larger than {} MB", limit / (1024 * 1024))
+            }
+            ImageError::Unsupported => {
+                write!(f, "unsupported image format, use JPEG, PNG, WebP or HEIC")
+            }
+            ImageError::Mismatch => {
+                write!(f, "the uploaded file does not match the announced one")
+            }
+            ImageError::UploadNotFound => write!(f, "the uploaded file was not found"),
+            ImageError::HeicDisabled => write!(f, "HEIC images are not supported by this server"),
+            ImageError::Decode(e) => write!(f, "could not read the image: {e}"),
+            ImageError::Encode(e) => write!(f, "could not process the image: {e}"),
+            ImageError::Storage(e) => write!(f, "image upload failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// An encoded file ready for storage.
+pub struct Encoded {
+    pub data: Vec<u8>,
+    pub content_type: &'static str,
+    pub extension: &'static str,
+}
+
+pub struct ProcessedImage {
+    /// The upload itself, upright and without metadata. HEIC is converted
+    /// to JPEG since browsers cannot show it.
+    pub original: Encoded,
+    pub variants: Vec<(ImageVariant, Encoded)>,
+}
+
+/// Validates and processes one upload. CPU heavy; call it from
+/// [`process_image`] or a blocking task.
+pub fn process(data: &[u8]) -> Result<ProcessedImage, ImageError> {
+    let limit = max_image_bytes();
+    if data.len() > limit {
+        return Err(ImageError::TooLarge(limit));
+    }
+    let kind = ImageKind::detect(data).ok_or(ImageError::Unsupported)?;
+    let image = decode(kind, data)?;
+
+    let original = match kind {
+        ImageKind::Png => encode_png(&image)?,
+        ImageKind::WebP => encode_webp(&image, ORIGINAL_QUALITY as f32)?,
+        ImageKind::Jpeg | ImageKind::Heic => encode_jpeg(&image)?,
+    };
+    let variants = ImageVariant::ALL
+        .into_iter()
+        .map(|variant| {
+            let resized = if image.width() > variant.width() {
+                image.resize(variant.width(), image.height(), FilterType::Lanczos3)
+            } else {
+                image.clone()
+            };
+            Ok((variant, encode_webp(&resized, VARIANT_QUALITY)?))
+        })
+        .collect::<Result<_, ImageError>>()?;
+
+    Ok(ProcessedImage { original, variants })
+}
+
+pub async fn process_image(data: Vec<u8>) -> Result<ProcessedImage, ImageError> {
+    tokio::task::spawn_blocking(move || process(&data))
+        .await
+        .map_err(|e| ImageError::Encode(e.to_string()))?
+}
+
+fn decode(kind: ImageKind, data: &[u8]) -> Result<DynamicImage, ImageError> {
+    let format = match kind {
+        ImageKind::Jpeg => image::ImageFormat::Jpeg,
+        ImageKind::Png => image::ImageFormat::Png,
+        ImageKind::WebP => image::ImageFormat::WebP,
+        ImageKind::Heic => return decode_heic(data),
+    };
+    let mut decoder = ImageReader::with_format(Cursor::new(data), format)
+        .into_decoder()
+        .map_err(|e| ImageError::Decode(e.to_string()))?;
+    let orientation = decoder
+        .orientation()
+        .map_err(|e| ImageError::Decode(e.to_string()))?;
+    let mut image =
+        DynamicImage::from_decoder(decoder).map_err(|e| ImageError::Decode(e.to_string()))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// libheif applies the file's rotation and mirroring while decoding.
+#[cfg(feature = "heic")]
+fn decode_heic(data: &[u8]) -> Result<DynamicImage, ImageError> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let decode_error = |e: libheif_rs::HeifError| ImageError::Decode(e.to_string());
+    let context = HeifContext::read_from_bytes(data).map_err(decode_error)?;
+    let handle = context.primary_image_handle().map_err(decode_error)?;
+    let decoded = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(decode_error)?;
+    let plane = decoded
+        .planes()
+        .interleaved
+        .ok_or_else(|| ImageError::Decode("missing pixel data".to_string()))?;
+
+    let row = plane.width as usize * 4;
+    let pixels = plane
+        .data
+        .chunks(plane.stride)
+        .take(plane.height as usize)
+        .flat_map(|x| &x[..row])
+        .copied()
+        .collect();
+    image::RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| ImageError::Decode("invalid pixel data".to_string()))
+}
+
+#[cfg(not(feature = "heic"))]
+fn decode_heic(_: &[u8]) -> Result<DynamicImage, ImageError> {
+    Err(ImageError::HeicDisabled)
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Result<Encoded, ImageError> {
+    let mut data = Vec::new();
+    image
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut data, ORIGINAL_QUALITY))
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(Encoded {
+        data,
+        content_type: "image/jpeg",
+        extension: "jpg",
+    })
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Encoded, ImageError> {
+    let mut data = Vec::new();
+    image
+        .write_with_encoder(PngEncoder::new(&mut data))
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(Encoded {
+        data,
+        content_type: "image/png",
+        extension: "png",
+    })
+}
+
+fn encode_webp(image: &DynamicImage, quality: f32) -> Result<Encoded, ImageError> {
+    let data = if image.color().has_alpha() {
+        let pixels = image.to_rgba8();
+        webp::Encoder::from_rgba(&pixels, pixels.width(), pixels.height())
+            .encode_simple(false, quality)
+    } else {
+        let pixels = image.to_rgb8();
+        webp::Encoder::from_rgb(&pixels, pixels.width(), pixels.height())
+            .encode_simple(false, quality)
+    }
+    .map_err(|e| ImageError::Encode(format!("{e:?}")))?;
+    Ok(Encoded {
+        data: data.to_vec(),
+        content_type: "image/webp",
+        extension: "webp",
+    })
+}
+
+/// Processes an upload and stores the original with its variants under
+/// fresh server-chosen keys.
+pub async fn store_processed(
+    storage: &dyn ImageStorage,
+    data: Vec<u8>,
+) -> Result<ImageKeys, ImageError> {
+    let processed = process_image(data).await?;
+    let stem = Uuid::new_v4();
+
+    let store = |key: String, encoded: Encoded| async move {
+        storage
+            .store(&key, encoded.content_type, encoded.data)
+            .await
+            .map_err(|e| ImageError::Storage(e.to_string()))?;
+        Ok::<_, ImageError>(key)
+    };
+    let original = store(
+        format!("{stem}.{}", processed.original.extension),
+        processed.original,
+    )
+    .await?;
+
+    let mut keys = ImageKeys {
+        original,
+        thumbnail: None,
+        medium: None,
+        large: None,
+    };
+    for (variant, encoded) in processed.variants {
+        let key = store(
+            format!("{stem}_{}.{}", variant.as_str(), encoded.extension),
+            encoded,
+        )
+        .await?;
+        match variant {
+            ImageVariant::Thumbnail => keys.thumbnail = Some(key),
+            ImageVariant::Medium => keys.medium = Some(key),
+            ImageVariant::Large => keys.large = Some(key),
+        }
+    }
+    Ok(keys)
+}
+
+/// Processes an upload and stores only the re-encoded original, for images
+/// shown at a single size such as video posters.
+pub async fn store_original(
+    storage: &dyn ImageStorage,
+    data: Vec<u8>,
+) -> Result<String, ImageError> {
+    let original = process_image(data).await?.original;
+    let key = format!("{}.{}", Uuid::new_v4(), original.extension);
+    storage
+        .store(&key, original.content_type, original.data)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?;
+    Ok(key)
+}
+
+/// Turns a direct upload into gallery images once it is in storage. The
+/// raw upload is removed whether it passes or not.
+pub async fn finalize_upload(
+    pool: &sqlx::PgPool,
+    storage: &dyn ImageStorage,
+    user_id: Uuid,
+    key: &str,
+) -> Result<ImageKeys, ImageError> {
+    let upload = crate::db::image_uploads::get_image_upload(pool, key)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?
+        .filter(|x| x.user_id == user_id)
+        .ok_or(ImageError::UploadNotFound)?;
+    let size = storage
+        .head(key)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?
+        .ok_or(ImageError::UploadNotFound)?
+        .size;
+
+    let result = match load_upload(storage, key, size, &upload).await {
+        Ok(data) => store_processed(storage, data).await,
+        Err(e) => Err(e),
+    };
+
+    storage
+        .delete(key)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?;
+    crate::db::image_uploads::delete_image_upload(pool, key)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?;
+    result
+}
+
+/// Reads a direct upload of `size` bytes once it is known to be no larger
+/// than announced, so an oversized object never ends up in memory.
+async fn load_upload(
+    storage: &dyn ImageStorage,
+    key: &str,
+    size: u64,
+    upload: &ImageUpload,
+) -> Result<Vec<u8>, ImageError> {
+    let limit = max_image_bytes();
+    if size > limit as u64 {
+        return Err(ImageError::TooLarge(limit));
+    }
+    if size != upload.size as u64 {
+        return Err(ImageError::Mismatch);
+    }
+    let object = storage
+        .load(key)
+        .await
+        .map_err(|e| ImageError::Storage(e.to_string()))?
+        .ok_or(ImageError::UploadNotFound)?;
+    verify_upload(&object.data, upload.size, &upload.content_type)?;
+    Ok(object.data)
+}
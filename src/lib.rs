@@ -1,20 +1,30 @@
 use leptos::prelude::*;
 
+#[cfg(feature = "ssr")]
+pub mod api;
 pub mod app;
 pub mod auth;
+pub mod financing;
 pub mod models;
 
 #[cfg(feature = "ssr")]
 pub mod db;
 #[cfg(feature = "ssr")]
-pub mod s3;
+pub mod images;
+#[cfg(feature = "ssr")]
+pub mod listing_schedule;
+#[cfg(feature = "ssr")]
+pub mod storage;
+#[cfg(feature = "ssr")]
+pub mod trash;
+pub mod videos;
 
 #[cfg(feature = "ssr")]
 #[derive(Clone, axum::extract::FromRef)]
 pub struct AppState {
     pub leptos_options: leptos::config::LeptosOptions,
     pub pool: sqlx::PgPool,
-    pub s3: s3::S3,
+    pub storage: storage::Storage,
 }
 
 #[cfg(feature = "hydrate")]
@@ -0,0 +1,87 @@
+//! Publishes drafts whose `publish_at` has passed and archives published
+//! listings past their `expire_at`, so agents can prepare launches ahead of
+//! time and stale listings take themselves down.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{AuditEntity, EstateStatus};
+
+/// How often the server looks for due listings unless
+/// `LISTING_SCHEDULER_INTERVAL_SECS` says otherwise.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How many days ahead the dashboard warns about expiring listings.
+pub fn notice_days() -> i32 {
+    env_days("LISTING_EXPIRY_NOTICE_DAYS", 7)
+}
+
+/// How many days a renewal keeps a listing up.
+pub fn renewal_days() -> i32 {
+    env_days("LISTING_RENEWAL_DAYS", 30)
+}
+
+fn env_days(key: &str, default: i32) -> i32 {
+    std::env::var(key)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .filter(|x| *x > 0)
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Default)]
+pub struct ScheduleReport {
+    pub published: Vec<Uuid>,
+    pub archived: Vec<Uuid>,
+}
+
+/// Applies every transition that is due and records each in the audit log.
+pub async fn run_due(pool: &PgPool) -> Result<ScheduleReport, sqlx::Error> {
+    let published = crate::db::estates::publish_scheduled_estates(pool).await?;
+    for id in &published {
+        crate::db::audit::record_system_change(
+            pool,
+            AuditEntity::Estate,
+            *id,
+            "status",
+            EstateStatus::Draft,
+            EstateStatus::Published,
+        )
+        .await?;
+    }
+    let archived = crate::db::estates::archive_expired_estates(pool).await?;
+    for id in &archived {
+        crate::db::audit::record_system_change(
+            pool,
+            AuditEntity::Estate,
+            *id,
+            "status",
+            EstateStatus::Published,
+            EstateStatus::Archived,
+        )
+        .await?;
+    }
+    Ok(ScheduleReport {
+        published,
+        archived,
+    })
+}
+
+/// Runs [`run_due`] every `period` for the life of the process.
+pub async fn run_periodically(pool: PgPool, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match run_due(&pool).await {
+            Ok(report) if report.published.is_empty() && report.archived.is_empty() => {}
+            Ok(report) => leptos::logging::log!(
+                "listing scheduler: {} published, {} archived",
+                report.published.len(),
+                report.archived.len(),
+            ),
+            Err(e) => leptos::logging::error!("listing scheduler failed: {e}"),
+        }
+    }
+}
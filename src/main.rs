@@ -2,10 +2,11 @@
 use {
     axum::Router,
     cryptos_site::{
-        AppState,
+        AppState, api,
         app::*,
+        auth::api_token::api_token_auth,
         db::{create_pool, run_migrations},
-        s3,
+        listing_schedule, storage, trash,
     },
     leptos::logging::log,
     leptos::prelude::*,
@@ -61,23 +62,62 @@ async fn main() {
     let app_state = AppState {
         leptos_options: leptos_options.clone(),
         pool: pool.clone(),
-        s3: s3::S3::get_from_env().await,
+        storage: storage::from_env().await,
     };
 
+    // Clean up orphaned images in the background when asked to
+    if let Some(hours) = var("IMAGE_RECONCILE_INTERVAL_HOURS")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .filter(|x| *x > 0)
+    {
+        tokio::spawn(storage::reconcile::run_periodically(
+            pool.clone(),
+            app_state.storage.clone(),
+            std::time::Duration::from_secs(hours * 60 * 60),
+            storage::reconcile::DEFAULT_GRACE,
+        ));
+    }
+
+    // Publish scheduled listings and take down expired ones
+    let period = var("LISTING_SCHEDULER_INTERVAL_SECS")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .filter(|x| *x > 0)
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(listing_schedule::DEFAULT_INTERVAL);
+    tokio::spawn(listing_schedule::run_periodically(pool.clone(), period));
+
+    // Purge what has been in the trash for longer than the retention period
+    tokio::spawn(trash::run_periodically(
+        pool.clone(),
+        app_state.storage.clone(),
+        trash::DEFAULT_INTERVAL,
+    ));
+
     let app = Router::new()
         .leptos_routes(&app_state, routes, {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
+        .nest("/api/v1", api::router())
+        .merge(storage::router())
         .fallback(leptos_axum::file_and_error_handler::<AppState, _>(shell))
         .layer(session_layer)
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            api_token_auth,
+        ))
         .with_state(app_state);
 
     log!("listening on http://{}", &addr);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 #[cfg(not(feature = "ssr"))]
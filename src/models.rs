@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-use crate::auth::Level;
+use crate::auth::{Level, Permission};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "ssr", derive(FromRow))]
@@ -12,18 +12,1096 @@ pub struct User {
     pub name: String,
     pub password: String,
     pub level: Level,
+    pub must_change_password: bool,
+    pub totp_secret: Option<String>,
+    pub totp_enabled: bool,
+    pub totp_required: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[cfg_attr(feature = "ssr", derive(FromRow))]
+#[cfg_attr(feature = "ssr", derive(FromRow, utoipa::ToSchema))]
 pub struct Estate {
     pub id: Uuid,
     pub name: String,
     pub address: String,
+    /// Built from the stored `image_key` when the estate is read.
     pub image_url: String,
+    /// Small variant of the cover image for listing cards.
+    pub thumbnail_url: Option<String>,
     pub price_in_cents: i64,
     pub space_in_meters: i32,
     pub description: String,
+    pub created_by: Option<Uuid>,
+    pub assigned_agent: Option<Uuid>,
+    pub property_type: PropertyType,
+    pub bedrooms: Option<i32>,
+    pub bathrooms: Option<i32>,
+    /// `0` is the ground floor, negative floors are basements.
+    pub floor: Option<i32>,
+    pub finishing: Option<Finishing>,
+    pub furnished: bool,
+    pub purpose: ListingPurpose,
+    pub down_payment_in_cents: Option<i64>,
+    pub installment_years: Option<i32>,
+    pub installment_frequency: Option<InstallmentFrequency>,
+    pub installment_in_cents: Option<i64>,
+    pub status: EstateStatus,
+    /// When the estate last entered each status, as `YYYY-MM-DD HH:MM`.
+    pub published_at: Option<String>,
+    pub reserved_at: Option<String>,
+    pub sold_at: Option<String>,
+    pub archived_at: Option<String>,
+    /// See [`ListingSchedule`].
+    pub publish_at: Option<String>,
+    pub expire_at: Option<String>,
+}
+
+impl Estate {
+    /// Turns the stored keys read into `image_url` and `thumbnail_url`
+    /// into urls under `base`.
+    pub fn with_public_urls(mut self, base: &str) -> Self {
+        self.image_url = public_url(base, &self.image_url);
+        self.thumbnail_url = self.thumbnail_url.map(|x| public_url(base, &x));
+        self
+    }
+
+    pub fn is_managed_by(&self, user_id: Uuid) -> bool {
+        self.created_by == Some(user_id) || self.assigned_agent == Some(user_id)
+    }
+
+    pub fn attributes(&self) -> EstateAttributes {
+        EstateAttributes {
+            property_type: self.property_type,
+            bedrooms: self.bedrooms,
+            bathrooms: self.bathrooms,
+            floor: self.floor,
+            finishing: self.finishing,
+            furnished: self.furnished,
+        }
+    }
+
+    pub fn schedule(&self) -> ListingSchedule {
+        ListingSchedule {
+            publish_at: self.publish_at.clone(),
+            expire_at: self.expire_at.clone(),
+        }
+    }
+
+    /// The statuses the estate has been in, with when it last entered each.
+    pub fn status_history(&self) -> Vec<(EstateStatus, String)> {
+        [
+            (EstateStatus::Published, &self.published_at),
+            (EstateStatus::Reserved, &self.reserved_at),
+            (EstateStatus::Sold, &self.sold_at),
+            (EstateStatus::Archived, &self.archived_at),
+        ]
+        .into_iter()
+        .filter_map(|(status, at)| Some((status, at.clone()?)))
+        .collect()
+    }
+
+    pub fn terms(&self) -> PaymentTerms {
+        PaymentTerms {
+            purpose: self.purpose,
+            down_payment_in_cents: self.down_payment_in_cents,
+            installment_years: self.installment_years,
+            installment_frequency: self.installment_frequency,
+            installment_in_cents: self.installment_in_cents,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct NewEstate {
+    pub name: String,
+    pub address: String,
+    /// An object key from an upload, or an absolute url of an image hosted
+    /// elsewhere.
+    pub image_url: String,
+    pub price_in_cents: i64,
+    pub space_in_meters: i32,
+    pub description: String,
+    #[serde(flatten)]
+    pub attributes: EstateAttributes,
+    #[serde(flatten)]
+    pub terms: PaymentTerms,
+    #[serde(flatten)]
+    pub schedule: ListingSchedule,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyType {
+    #[default]
+    Apartment,
+    Villa,
+    Penthouse,
+    Duplex,
+    Land,
+    Commercial,
+}
+
+impl PropertyType {
+    pub const ALL: [PropertyType; 6] = [
+        PropertyType::Apartment,
+        PropertyType::Villa,
+        PropertyType::Penthouse,
+        PropertyType::Duplex,
+        PropertyType::Land,
+        PropertyType::Commercial,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropertyType::Apartment => "apartment",
+            PropertyType::Villa => "villa",
+            PropertyType::Penthouse => "penthouse",
+            PropertyType::Duplex => "duplex",
+            PropertyType::Land => "land",
+            PropertyType::Commercial => "commercial",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PropertyType::Apartment => "شقة",
+            PropertyType::Villa => "فيلا",
+            PropertyType::Penthouse => "بنتهاوس",
+            PropertyType::Duplex => "دوبلكس",
+            PropertyType::Land => "أرض",
+            PropertyType::Commercial => "تجاري",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+}
+
+/// Finishing level (تشطيب), from bare walls to luxury.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum Finishing {
+    Unfinished,
+    SemiFinished,
+    Finished,
+    SuperLux,
+    UltraLux,
+}
+
+impl Finishing {
+    pub const ALL: [Finishing; 5] = [
+        Finishing::Unfinished,
+        Finishing::SemiFinished,
+        Finishing::Finished,
+        Finishing::SuperLux,
+        Finishing::UltraLux,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Finishing::Unfinished => "unfinished",
+            Finishing::SemiFinished => "semi_finished",
+            Finishing::Finished => "finished",
+            Finishing::SuperLux => "super_lux",
+            Finishing::UltraLux => "ultra_lux",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Finishing::Unfinished => "بدون تشطيب",
+            Finishing::SemiFinished => "نصف تشطيب",
+            Finishing::Finished => "تشطيب كامل",
+            Finishing::SuperLux => "سوبر لوكس",
+            Finishing::UltraLux => "ألترا لوكس",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+}
+
+/// The typed facts of an estate, set and changed together.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct EstateAttributes {
+    pub property_type: PropertyType,
+    pub bedrooms: Option<i32>,
+    pub bathrooms: Option<i32>,
+    /// `0` is the ground floor, negative floors are basements.
+    pub floor: Option<i32>,
+    pub finishing: Option<Finishing>,
+    pub furnished: bool,
+}
+
+impl EstateAttributes {
+    /// `(label, value)` pairs for display, leaving out unknown facts.
+    pub fn facts(&self) -> Vec<(&'static str, String)> {
+        let mut facts = vec![("نوع العقار", self.property_type.label().to_string())];
+        if let Some(bedrooms) = self.bedrooms {
+            facts.push(("غرف النوم", bedrooms.to_string()));
+        }
+        if let Some(bathrooms) = self.bathrooms {
+            facts.push(("الحمامات", bathrooms.to_string()));
+        }
+        if let Some(floor) = self.floor {
+            facts.push(("الطابق", floor_label(floor)));
+        }
+        if let Some(finishing) = self.finishing {
+            facts.push(("التشطيب", finishing.label().to_string()));
+        }
+        let furnished = if self.furnished {
+            "مفروش"
+        } else {
+            "غير مفروش"
+        };
+        facts.push(("الفرش", furnished.to_string()));
+        facts
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bedrooms.is_some_and(|x| x < 0) {
+            return Err("bedrooms must not be negative".to_string());
+        }
+        if self.bathrooms.is_some_and(|x| x < 0) {
+            return Err("bathrooms must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn floor_label(floor: i32) -> String {
+    match floor {
+        0 => "الأرضي".to_string(),
+        -1 => "البدروم".to_string(),
+        x if x < 0 => format!("البدروم {}", -x),
+        x => format!("الدور {x}"),
+    }
+}
+
+/// Whether an estate is sold or let, and for which period its price is.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum ListingPurpose {
+    #[default]
+    Sale,
+    RentMonthly,
+    RentYearly,
+}
+
+impl ListingPurpose {
+    pub const ALL: [ListingPurpose; 3] = [
+        ListingPurpose::Sale,
+        ListingPurpose::RentMonthly,
+        ListingPurpose::RentYearly,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListingPurpose::Sale => "sale",
+            ListingPurpose::RentMonthly => "rent_monthly",
+            ListingPurpose::RentYearly => "rent_yearly",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListingPurpose::Sale => "للبيع",
+            ListingPurpose::RentMonthly => "للإيجار الشهري",
+            ListingPurpose::RentYearly => "للإيجار السنوي",
+        }
+    }
+
+    /// What the price of a listing with this purpose is called.
+    pub fn price_label(&self) -> &'static str {
+        match self {
+            ListingPurpose::Sale => "السعر",
+            ListingPurpose::RentMonthly => "الإيجار الشهري",
+            ListingPurpose::RentYearly => "الإيجار السنوي",
+        }
+    }
+
+    /// Appended to a formatted price: rents are per period.
+    pub fn price_suffix(&self) -> &'static str {
+        match self {
+            ListingPurpose::Sale => "",
+            ListingPurpose::RentMonthly => " / شهرياً",
+            ListingPurpose::RentYearly => " / سنوياً",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum InstallmentFrequency {
+    Monthly,
+    Quarterly,
+}
+
+impl InstallmentFrequency {
+    pub const ALL: [InstallmentFrequency; 2] = [
+        InstallmentFrequency::Monthly,
+        InstallmentFrequency::Quarterly,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstallmentFrequency::Monthly => "monthly",
+            InstallmentFrequency::Quarterly => "quarterly",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InstallmentFrequency::Monthly => "شهري",
+            InstallmentFrequency::Quarterly => "ربع سنوي",
+        }
+    }
+
+    pub fn per_year(&self) -> i32 {
+        match self {
+            InstallmentFrequency::Monthly => 12,
+            InstallmentFrequency::Quarterly => 4,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+}
+
+/// How an estate is paid for: its purpose and, for sales, an optional
+/// developer installment plan. The plan fields are set all together or not
+/// at all.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct PaymentTerms {
+    pub purpose: ListingPurpose,
+    pub down_payment_in_cents: Option<i64>,
+    pub installment_years: Option<i32>,
+    pub installment_frequency: Option<InstallmentFrequency>,
+    /// The amount of each monthly or quarterly installment.
+    pub installment_in_cents: Option<i64>,
+}
+
+/// A complete installment plan: a down payment, then equal installments
+/// over a number of years.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaymentPlan {
+    pub down_payment_in_cents: i64,
+    pub years: i32,
+    pub frequency: InstallmentFrequency,
+    pub installment_in_cents: i64,
+}
+
+impl PaymentPlan {
+    /// The largest down payment or installment a plan accepts.
+    pub const MAX_AMOUNT_IN_CENTS: i64 = 100_000_000_000_000;
+
+    pub fn installments(&self) -> i32 {
+        self.years * self.frequency.per_year()
+    }
+
+    /// Down payment plus every installment, or `None` if it overflows.
+    pub fn total_in_cents(&self) -> Option<i64> {
+        self.installment_in_cents
+            .checked_mul(self.installments() as i64)?
+            .checked_add(self.down_payment_in_cents)
+    }
+}
+
+impl PaymentTerms {
+    pub fn plan(&self) -> Option<PaymentPlan> {
+        Some(PaymentPlan {
+            down_payment_in_cents: self.down_payment_in_cents?,
+            years: self.installment_years?,
+            frequency: self.installment_frequency?,
+            installment_in_cents: self.installment_in_cents?,
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let set = [
+            self.down_payment_in_cents.is_some(),
+            self.installment_years.is_some(),
+            self.installment_frequency.is_some(),
+            self.installment_in_cents.is_some(),
+        ];
+        let Some(plan) = self.plan() else {
+            if set.contains(&true) {
+                return Err(
+                    "an installment plan needs a down payment, years, frequency and amount"
+                        .to_string(),
+                );
+            }
+            return Ok(());
+        };
+        if self.purpose != ListingPurpose::Sale {
+            return Err("only estates for sale can have an installment plan".to_string());
+        }
+        if plan.down_payment_in_cents < 0 {
+            return Err("down_payment_in_cents must not be negative".to_string());
+        }
+        if plan.down_payment_in_cents > PaymentPlan::MAX_AMOUNT_IN_CENTS {
+            return Err("down_payment_in_cents is too large".to_string());
+        }
+        if !(1..=30).contains(&plan.years) {
+            return Err("installment_years must be between 1 and 30".to_string());
+        }
+        if plan.installment_in_cents <= 0 {
+            return Err("installment_in_cents must be positive".to_string());
+        }
+        if plan.installment_in_cents > PaymentPlan::MAX_AMOUNT_IN_CENTS {
+            return Err("installment_in_cents is too large".to_string());
+        }
+        if plan.total_in_cents().is_none() {
+            return Err("the installment plan total is too large".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Where a listing is in its lifecycle. Only published and reserved
+/// estates appear in public listings; sold ones stay reachable by link.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "text", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum EstateStatus {
+    #[default]
+    Draft,
+    Published,
+    Reserved,
+    Sold,
+    Archived,
+}
+
+impl EstateStatus {
+    pub const ALL: [EstateStatus; 5] = [
+        EstateStatus::Draft,
+        EstateStatus::Published,
+        EstateStatus::Reserved,
+        EstateStatus::Sold,
+        EstateStatus::Archived,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstateStatus::Draft => "draft",
+            EstateStatus::Published => "published",
+            EstateStatus::Reserved => "reserved",
+            EstateStatus::Sold => "sold",
+            EstateStatus::Archived => "archived",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EstateStatus::Draft => "مسودة",
+            EstateStatus::Published => "منشور",
+            EstateStatus::Reserved => "محجوز",
+            EstateStatus::Sold => "تم البيع",
+            EstateStatus::Archived => "مؤرشف",
+        }
+    }
+
+    /// Label of the button moving an estate into this status.
+    pub fn action_label(&self) -> &'static str {
+        match self {
+            EstateStatus::Draft => "إعادة إلى المسودات",
+            EstateStatus::Published => "نشر",
+            EstateStatus::Reserved => "حجز",
+            EstateStatus::Sold => "تسجيل البيع",
+            EstateStatus::Archived => "أرشفة",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+
+    /// Whether the estate shows up in public listings.
+    pub fn is_listed(&self) -> bool {
+        matches!(self, EstateStatus::Published | EstateStatus::Reserved)
+    }
+
+    /// Whether visitors may open the estate by link. Drafts and archived
+    /// estates are only visible to those who manage them.
+    pub fn is_public(&self) -> bool {
+        !matches!(self, EstateStatus::Draft | EstateStatus::Archived)
+    }
+
+    /// The statuses an estate in this status may move to. A reservation
+    /// that falls through goes back to published; sold estates can only be
+    /// archived, and archived ones start over as drafts.
+    pub fn next(&self) -> &'static [EstateStatus] {
+        match self {
+            EstateStatus::Draft => &[EstateStatus::Published, EstateStatus::Archived],
+            EstateStatus::Published => &[
+                EstateStatus::Reserved,
+                EstateStatus::Sold,
+                EstateStatus::Draft,
+                EstateStatus::Archived,
+            ],
+            EstateStatus::Reserved => &[
+                EstateStatus::Sold,
+                EstateStatus::Published,
+                EstateStatus::Archived,
+            ],
+            EstateStatus::Sold => &[EstateStatus::Archived],
+            EstateStatus::Archived => &[EstateStatus::Draft],
+        }
+    }
+
+    /// The permission moving to `to` takes besides editing the estate.
+    /// Only taking a listing public needs `estates.publish`; reserving or
+    /// selling a listing that is already public does not.
+    pub fn transition_permission(&self, to: EstateStatus) -> Option<Permission> {
+        (!self.is_public() && to.is_public()).then_some(Permission::EstatesPublish)
+    }
+
+    pub fn transition(&self, to: EstateStatus) -> Result<(), String> {
+        if self.next().contains(&to) {
+            Ok(())
+        } else {
+            Err(format!(
+                "a {} estate cannot become {}",
+                self.as_str(),
+                to.as_str()
+            ))
+        }
+    }
+}
+
+/// When a draft is published and when a published listing is archived,
+/// both checked by the server's listing scheduler. Times are
+/// `YYYY-MM-DDTHH:MM` in the server's time zone, as a `datetime-local`
+/// input sends them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct ListingSchedule {
+    pub publish_at: Option<String>,
+    pub expire_at: Option<String>,
+}
+
+impl ListingSchedule {
+    /// Treats times left empty in a form as not set.
+    pub fn trimmed(self) -> Self {
+        let trim = |x: Option<String>| x.filter(|x| !x.trim().is_empty());
+        Self {
+            publish_at: trim(self.publish_at),
+            expire_at: trim(self.expire_at),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, time) in [
+            ("publish_at", &self.publish_at),
+            ("expire_at", &self.expire_at),
+        ] {
+            if time.as_deref().is_some_and(|x| !is_schedule_time(x)) {
+                return Err(format!("{field} must look like 2024-01-31T18:30"));
+            }
+        }
+        // The fixed-width format orders like the times it stands for.
+        if let (Some(publish_at), Some(expire_at)) = (&self.publish_at, &self.expire_at)
+            && expire_at <= publish_at
+        {
+            return Err("expire_at must come after publish_at".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Whether `s` is a `YYYY-MM-DD` day the calendar has, so the database
+/// never gets a date it cannot cast.
+pub fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| b[range].iter().all(u8::is_ascii_digit);
+    let number = |range: std::ops::Range<usize>| s[range].parse::<u32>().unwrap_or(0);
+    b.len() == 10
+        && digits(0..4)
+        && b[4] == b'-'
+        && digits(5..7)
+        && b[7] == b'-'
+        && digits(8..10)
+        && number(0..4) > 0
+        && (1..=12).contains(&number(5..7))
+        && (1..=days_in_month(number(0..4), number(5..7))).contains(&number(8..10))
+}
+
+/// Whether `s` is a `YYYY-MM-DDTHH:MM` time on a day the calendar has,
+/// so the database never gets a time it cannot cast.
+pub fn is_schedule_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| b[range].iter().all(u8::is_ascii_digit);
+    let number = |range: std::ops::Range<usize>| s[range].parse::<u32>().unwrap_or(0);
+    b.len() == 16
+        && s.get(..10).is_some_and(is_date)
+        && b[10] == b'T'
+        && digits(11..13)
+        && b[13] == b':'
+        && digits(14..16)
+        && number(11..13) < 24
+        && number(14..16) < 60
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Url of the object stored under `key` when objects are served from
+/// `base`. Empty keys and absolute urls of images hosted elsewhere are
+/// returned unchanged.
+pub fn public_url(base: &str, key: &str) -> String {
+    if key.is_empty() || key.contains("://") {
+        key.to_string()
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), key)
+    }
+}
+
+/// Checks the price an estate is listed at; the dashboard and the API share
+/// it and [`validate_space`].
+pub fn validate_price(price_in_cents: i64) -> Result<(), String> {
+    if price_in_cents < 0 {
+        return Err("price_in_cents must not be negative".to_string());
+    }
+    Ok(())
+}
+
+/// Checks the floor area of an estate.
+pub fn validate_space(space_in_meters: i32) -> Result<(), String> {
+    if space_in_meters <= 0 {
+        return Err("space_in_meters must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `url` is an absolute http(s) url with a host, as required of
+/// images hosted elsewhere so they can never name a stored object.
+pub fn is_remote_url(url: &str) -> bool {
+    ["http://", "https://"].iter().any(|scheme| {
+        url.strip_prefix(scheme)
+            .and_then(|x| x.split(['/', '?', '#']).next())
+            .is_some_and(|host| !host.is_empty() && !host.contains(char::is_whitespace))
+    })
+}
+
+/// A photo in an estate's gallery. The cover image's keys are mirrored
+/// into `estates`, where [`Estate::image_url`] is built from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EstateImage {
+    pub id: Uuid,
+    pub estate_id: Uuid,
+    pub position: i32,
+    pub is_cover: bool,
+    pub caption: String,
+    pub alt_text: String,
+    pub keys: ImageKeys,
+    pub urls: ImageUrls,
+}
+
+/// Object keys of an uploaded image and its variants, as stored in the
+/// database. Images uploaded before processing existed have no variants.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ImageKeys {
+    pub original: String,
+    pub thumbnail: Option<String>,
+    pub medium: Option<String>,
+    pub large: Option<String>,
+}
+
+impl ImageKeys {
+    /// Every object stored for this image; images hosted elsewhere have none.
+    pub fn stored(&self) -> impl Iterator<Item = &str> {
+        [&self.thumbnail, &self.medium, &self.large]
+            .into_iter()
+            .filter_map(|x| x.as_deref())
+            .chain([self.original.as_str()])
+            .filter(|x| !x.is_empty() && !x.contains("://"))
+    }
+
+    pub fn public_urls(&self, base: &str) -> ImageUrls {
+        let url = |key: &Option<String>| key.as_deref().map(|x| public_url(base, x));
+        ImageUrls {
+            original: public_url(base, &self.original),
+            thumbnail: url(&self.thumbnail),
+            medium: url(&self.medium),
+            large: url(&self.large),
+        }
+    }
+}
+
+/// Where browsers load an image and its variants from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ImageUrls {
+    pub original: String,
+    pub thumbnail: Option<String>,
+    pub medium: Option<String>,
+    pub large: Option<String>,
+}
+
+impl ImageUrls {
+    /// Smallest available url, for thumbnails.
+    pub fn smallest(&self) -> &str {
+        self.thumbnail.as_deref().unwrap_or(&self.original)
+    }
+
+    /// `srcset` over the resized variants, when the image has them.
+    pub fn srcset(&self) -> Option<String> {
+        let variants = [
+            (ImageVariant::Thumbnail, &self.thumbnail),
+            (ImageVariant::Medium, &self.medium),
+            (ImageVariant::Large, &self.large),
+        ];
+        let srcset = variants
+            .into_iter()
+            .filter_map(|(variant, url)| Some(format!("{} {}w", url.as_ref()?, variant.width())))
+            .collect::<Vec<_>>()
+            .join(", ");
+        (!srcset.is_empty()).then_some(srcset)
+    }
+}
+
+/// A file the browser announces before putting it straight into storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadRequest {
+    pub content_type: String,
+    pub size: i64,
+}
+
+/// Where to `PUT` an announced file. The request must carry `content_type`
+/// as its `Content-Type`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedUpload {
+    pub key: String,
+    pub url: String,
+    pub content_type: String,
+}
+
+/// An issued upload as stored in `image_uploads`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct ImageUpload {
+    pub object_key: String,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub content_type: String,
+    pub size: i64,
+    pub expired: bool,
+}
+
+/// A video walkthrough of an estate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EstateVideo {
+    pub id: Uuid,
+    pub estate_id: Uuid,
+    pub position: i32,
+    pub video_key: String,
+    pub poster_key: Option<String>,
+    pub video_url: String,
+    pub poster_url: Option<String>,
+    pub content_type: String,
+    pub size: i64,
+    pub duration_secs: f64,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl EstateVideo {
+    /// Every object stored for this video.
+    pub fn stored(&self) -> impl Iterator<Item = &str> {
+        [Some(&self.video_key), self.poster_key.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|x| x.as_str())
+    }
+
+    /// Duration as `m:ss`, or `h:mm:ss` for an hour or more.
+    pub fn duration(&self) -> String {
+        let secs = self.duration_secs.round() as u64;
+        let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours > 0 {
+            format!("{hours}:{minutes:02}:{secs:02}")
+        } else {
+            format!("{minutes}:{secs:02}")
+        }
+    }
+}
+
+/// A multipart video upload in progress, as stored in `video_uploads`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct VideoUpload {
+    pub upload_id: String,
+    pub object_key: String,
+    pub estate_id: Uuid,
+    pub user_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub token_hash: String,
+}
+
+/// What is left of a video upload: the parts storage does not have yet,
+/// each with the url to `PUT` it to. Parts are cut at
+/// [`crate::videos::PART_SIZE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoUploadPlan {
+    pub upload_id: String,
+    /// Bytes already in storage from an earlier attempt.
+    pub uploaded: i64,
+    pub parts: Vec<VideoPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoPart {
+    pub number: i32,
+    pub url: String,
+}
+
+/// Resized WebP copies generated for every upload.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageVariant {
+    Thumbnail,
+    Medium,
+    Large,
+}
+
+impl ImageVariant {
+    pub const ALL: [ImageVariant; 3] = [
+        ImageVariant::Thumbnail,
+        ImageVariant::Medium,
+        ImageVariant::Large,
+    ];
+
+    /// Maximum width in pixels; smaller images are never upscaled.
+    pub fn width(self) -> u32 {
+        match self {
+            ImageVariant::Thumbnail => 400,
+            ImageVariant::Medium => 1024,
+            ImageVariant::Large => 1920,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageVariant::Thumbnail => "thumbnail",
+            ImageVariant::Medium => "medium",
+            ImageVariant::Large => "large",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EstateSort {
+    #[default]
+    Newest,
+    /// Best full-text match first; only meaningful with a search text.
+    Relevance,
+    PriceAsc,
+    PriceDesc,
+    SpaceAsc,
+    SpaceDesc,
+}
+
+impl EstateSort {
+    pub const ALL: [EstateSort; 6] = [
+        EstateSort::Newest,
+        EstateSort::Relevance,
+        EstateSort::PriceAsc,
+        EstateSort::PriceDesc,
+        EstateSort::SpaceAsc,
+        EstateSort::SpaceDesc,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstateSort::Newest => "newest",
+            EstateSort::Relevance => "relevance",
+            EstateSort::PriceAsc => "price_asc",
+            EstateSort::PriceDesc => "price_desc",
+            EstateSort::SpaceAsc => "space_asc",
+            EstateSort::SpaceDesc => "space_desc",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EstateSort::Newest => "الأحدث",
+            EstateSort::Relevance => "الأكثر صلة",
+            EstateSort::PriceAsc => "السعر: من الأقل",
+            EstateSort::PriceDesc => "السعر: من الأعلى",
+            EstateSort::SpaceAsc => "المساحة: من الأصغر",
+            EstateSort::SpaceDesc => "المساحة: من الأكبر",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.as_str() == s)
+    }
+
+    /// Order used when none is asked for: searches rank by relevance.
+    pub fn default_for(text: Option<&str>) -> Self {
+        if text.is_some_and(|x| !x.trim().is_empty()) {
+            EstateSort::Relevance
+        } else {
+            EstateSort::Newest
+        }
+    }
+}
+
+/// What an estate listing shows; `None` leaves a bound open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EstateFilter {
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    pub min_space: Option<i32>,
+    pub max_space: Option<i32>,
+    pub property_type: Option<PropertyType>,
+    pub min_bedrooms: Option<i32>,
+    pub min_bathrooms: Option<i32>,
+    pub min_floor: Option<i32>,
+    pub max_floor: Option<i32>,
+    pub finishing: Option<Finishing>,
+    pub furnished: Option<bool>,
+    pub purpose: Option<ListingPurpose>,
+    /// Only estates with (or without) an installment plan.
+    pub installments: Option<bool>,
+    pub max_down_payment: Option<i64>,
+    pub status: Option<EstateStatus>,
+    /// Full-text search over name, address and description.
+    pub text: Option<String>,
+    #[serde(default)]
+    pub sort: EstateSort,
+    /// Limits the listing to one user's estates. Always set by the server,
+    /// never taken from the request.
+    #[serde(skip)]
+    pub managed_by: Option<Uuid>,
+    /// Limits the listing to published and reserved estates. Always set by
+    /// the server, never taken from the request.
+    #[serde(skip)]
+    pub listed_only: bool,
+}
+
+/// Keyset position after the last estate of a page: the value of the sort
+/// column (creation time in microseconds for [`EstateSort::Newest`], rank
+/// in millionths for [`EstateSort::Relevance`]) and the id breaking ties. Travels as the opaque `<key>_<id>` string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstateCursor {
+    pub key: i64,
+    pub id: Uuid,
+}
+
+impl EstateCursor {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (key, id) = s.split_once('_')?;
+        Some(Self {
+            key: key.parse().ok()?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for EstateCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.key, self.id)
+    }
+}
+
+/// Marks the start of a search match inside [`EstateListing::snippet`].
+pub const HIGHLIGHT_START: char = '\u{1}';
+/// Marks the end of a search match inside [`EstateListing::snippet`].
+pub const HIGHLIGHT_END: char = '\u{2}';
+
+/// An estate in a listing, with the part of its name, address or
+/// description that matched the search text when there is one. The snippet
+/// is in normalized spelling, the form the search compares.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EstateListing {
+    #[serde(flatten)]
+    pub estate: Estate,
+    /// Matches are wrapped in [`HIGHLIGHT_START`] and [`HIGHLIGHT_END`].
+    pub snippet: Option<String>,
+}
+
+/// Splits a snippet into `(text, highlighted)` segments.
+pub fn highlight_segments(snippet: &str) -> Vec<(String, bool)> {
+    let mut segments = Vec::new();
+    for (i, part) in snippet.split(HIGHLIGHT_START).enumerate() {
+        match part.split_once(HIGHLIGHT_END) {
+            Some((marked, rest)) if i > 0 => {
+                segments.push((marked.to_string(), true));
+                segments.push((rest.to_string(), false));
+            }
+            _ => segments.push((part.replace(HIGHLIGHT_END, ""), false)),
+        }
+    }
+    segments.retain(|(text, _)| !text.is_empty());
+    segments
+}
+
+/// One page of a keyset-paginated listing; `next` is the cursor of the
+/// following page, absent on the last one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EstatePage {
+    pub items: Vec<EstateListing>,
+    pub next: Option<String>,
+}
+
+/// An estate in the trash and when it is purged for good.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct DeletedEstate {
+    pub id: Uuid,
+    pub name: String,
+    pub address: String,
+    pub deleted_at: String,
+    pub purge_at: String,
+}
+
+/// A user in the trash and when it is purged for good.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct DeletedUser {
+    pub id: Uuid,
+    pub name: String,
+    pub level: Level,
+    pub deleted_at: String,
+    pub purge_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,3 +1130,140 @@ impl From<User> for SecureUser {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum AuditEntity {
+    Estate,
+    User,
+    Role,
+}
+
+impl AuditEntity {
+    pub const ALL: [AuditEntity; 3] = [AuditEntity::Estate, AuditEntity::User, AuditEntity::Role];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Estate => "Estate",
+            AuditEntity::User => "User",
+            AuditEntity::Role => "Role",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditEntity::Estate => "عقار",
+            AuditEntity::User => "مستخدم",
+            AuditEntity::Role => "صلاحية",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<Uuid>,
+    /// Inclusive `YYYY-MM-DD` bounds.
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl AuditFilter {
+    /// Drops date bounds that are not real `YYYY-MM-DD` days.
+    pub fn with_valid_dates(self) -> Self {
+        Self {
+            from: self.from.filter(|x| is_date(x)),
+            to: self.to.filter(|x| is_date(x)),
+            ..self
+        }
+    }
+}
+
+/// Keyset position after the last entry of an audit log page: the entry's
+/// time in microseconds and its id. Travels as the opaque `<key>_<id>`
+/// string, like [`EstateCursor`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuditCursor {
+    pub created_at: i64,
+    pub id: Uuid,
+}
+
+impl AuditCursor {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (created_at, id) = s.split_once('_')?;
+        Some(Self {
+            created_at: created_at.parse().ok()?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for AuditCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.created_at, self.id)
+    }
+}
+
+/// One page of the audit log, newest first; `next` is the cursor of the
+/// following page, absent on the last one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub uri: String,
+    pub qr_svg: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub required: bool,
+    pub remaining_recovery_codes: i64,
+    /// Present while 2FA is off, holding the secret to enroll with.
+    pub enrollment: Option<TotpEnrollment>,
+    /// Recovery codes generated by the previous action, shown only once.
+    pub new_recovery_codes: Vec<String>,
+}
+
+/// A login session as shown to its owner or an admin. The underlying session
+/// id doubles as the cookie value, so only the tracking `id` leaves the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub name: String,
+    /// First characters of the token, enough to recognise it.
+    pub token_prefix: String,
+    pub scopes: Vec<Permission>,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+}
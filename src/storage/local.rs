@@ -0,0 +1,198 @@
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::PathBuf,
+};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use super::{
+    ImageStorage, ObjectHead, ObjectInfo, PUBLIC_PATH, PartInfo, StorageError, StoredObject,
+    is_valid_key,
+};
+
+/// Objects are plain files in one directory, served under
+/// [`super::PUBLIC_PATH`]. Parts of unfinished multipart uploads wait in
+/// `.multipart/<upload id>/`, which neither listing nor serving sees.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    pub async fn new(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir).await?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if is_valid_key(key) {
+            Ok(self.dir.join(key))
+        } else {
+            Err(StorageError(format!("invalid object key {key:?}")))
+        }
+    }
+
+    fn parts_dir(&self, upload_id: &str) -> Result<PathBuf, StorageError> {
+        if is_valid_key(upload_id) {
+            Ok(self.dir.join(".multipart").join(upload_id))
+        } else {
+            Err(StorageError(format!("invalid upload id {upload_id:?}")))
+        }
+    }
+}
+
+/// Files carry no metadata, so the type comes from the extension
+/// [`crate::images`] gave them.
+fn content_type(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|x| x.1) {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("mp4") => "video/mp4",
+        Some("mov") => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+#[async_trait]
+impl ImageStorage for LocalStorage {
+    async fn store(&self, key: &str, _: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        tokio::fs::write(self.path(key)?, data)
+            .await
+            .map_err(|e| StorageError(e.to_string()))
+    }
+
+    async fn load(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(StoredObject {
+                content_type: content_type(key).to_string(),
+                data,
+            })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageError(e.to_string())),
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectHead>, StorageError> {
+        match tokio::fs::metadata(self.path(key)?).await {
+            Ok(metadata) => Ok(Some(ObjectHead {
+                content_type: content_type(key).to_string(),
+                size: metadata.len(),
+            })),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageError(e.to_string())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(StorageError(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError> {
+        let error = |e: std::io::Error| StorageError(e.to_string());
+        let mut entries = tokio::fs::read_dir(&self.dir).await.map_err(error)?;
+        let mut objects = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(error)? {
+            let metadata = entry.metadata().await.map_err(error)?;
+            if !metadata.is_file() {
+                continue;
+            }
+            objects.push(ObjectInfo {
+                key: entry.file_name().to_string_lossy().into_owned(),
+                last_modified: metadata.modified().map_err(error)?,
+            });
+        }
+        Ok(objects)
+    }
+
+    async fn load_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let error = |e: std::io::Error| StorageError(e.to_string());
+        let mut file = match tokio::fs::File::open(self.path(key)?).await {
+            Ok(x) => x,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(error(e)),
+        };
+        file.seek(SeekFrom::Start(start)).await.map_err(error)?;
+        let mut data = Vec::new();
+        file.take(len).read_to_end(&mut data).await.map_err(error)?;
+        Ok(Some(data))
+    }
+
+    async fn create_multipart(&self, _: &str, _: &str) -> Result<String, StorageError> {
+        let upload_id = uuid::Uuid::new_v4().simple().to_string();
+        tokio::fs::create_dir_all(self.parts_dir(&upload_id)?)
+            .await
+            .map_err(|e| StorageError(e.to_string()))?;
+        Ok(upload_id)
+    }
+
+    async fn store_part(
+        &self,
+        _: &str,
+        upload_id: &str,
+        number: i32,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        tokio::fs::write(self.parts_dir(upload_id)?.join(number.to_string()), data)
+            .await
+            .map_err(|e| StorageError(e.to_string()))
+    }
+
+    async fn list_parts(&self, _: &str, upload_id: &str) -> Result<Vec<PartInfo>, StorageError> {
+        let error = |e: std::io::Error| StorageError(e.to_string());
+        let mut entries = tokio::fs::read_dir(self.parts_dir(upload_id)?)
+            .await
+            .map_err(error)?;
+        let mut parts = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(error)? {
+            let Some(number) = entry.file_name().to_str().and_then(|x| x.parse().ok()) else {
+                continue;
+            };
+            parts.push(PartInfo {
+                number,
+                size: entry.metadata().await.map_err(error)?.len() as i64,
+            });
+        }
+        parts.sort_by_key(|x| x.number);
+        Ok(parts)
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError> {
+        let error = |e: std::io::Error| StorageError(e.to_string());
+        let dir = self.parts_dir(upload_id)?;
+        let mut object = tokio::fs::File::create(self.path(key)?)
+            .await
+            .map_err(error)?;
+        for part in self.list_parts(key, upload_id).await? {
+            let mut part = tokio::fs::File::open(dir.join(part.number.to_string()))
+                .await
+                .map_err(error)?;
+            tokio::io::copy(&mut part, &mut object)
+                .await
+                .map_err(error)?;
+        }
+        object.flush().await.map_err(error)?;
+        tokio::fs::remove_dir_all(dir).await.map_err(error)
+    }
+
+    async fn abort_multipart(&self, _: &str, upload_id: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_dir_all(self.parts_dir(upload_id)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(StorageError(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    fn default_public_base(&self) -> String {
+        PUBLIC_PATH.to_string()
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+
+use super::{
+    ImageStorage, ObjectHead, ObjectInfo, PUBLIC_PATH, PartInfo, StorageError, StoredObject,
+};
+
+/// An unfinished multipart upload: its content type and parts by number.
+type Multipart = (String, BTreeMap<i32, Vec<u8>>);
+
+/// Keeps objects in the process, for tests and throwaway setups; everything
+/// is lost on restart.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    objects: Arc<RwLock<HashMap<String, (StoredObject, SystemTime)>>>,
+    uploads: Arc<RwLock<HashMap<String, Multipart>>>,
+}
+
+#[async_trait]
+impl ImageStorage for MemoryStorage {
+    async fn store(
+        &self,
+        key: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        let object = StoredObject {
+            content_type: content_type.to_string(),
+            data,
+        };
+        self.objects
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .insert(key.to_string(), (object, SystemTime::now()));
+        Ok(())
+    }
+
+    async fn load(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|e| StorageError(e.to_string()))?;
+        Ok(objects.get(key).map(|x| x.0.clone()))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectHead>, StorageError> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|e| StorageError(e.to_string()))?;
+        Ok(objects.get(key).map(|(x, _)| ObjectHead {
+            content_type: x.content_type.clone(),
+            size: x.data.len() as u64,
+        }))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.objects
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .remove(key);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|e| StorageError(e.to_string()))?;
+        Ok(objects
+            .iter()
+            .map(|(key, (_, last_modified))| ObjectInfo {
+                key: key.clone(),
+                last_modified: *last_modified,
+            })
+            .collect())
+    }
+
+    async fn load_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|e| StorageError(e.to_string()))?;
+        Ok(objects.get(key).map(|(x, _)| {
+            let start = (start as usize).min(x.data.len());
+            let end = start.saturating_add(len as usize).min(x.data.len());
+            x.data[start..end].to_vec()
+        }))
+    }
+
+    async fn create_multipart(&self, _: &str, content_type: &str) -> Result<String, StorageError> {
+        let upload_id = uuid::Uuid::new_v4().simple().to_string();
+        self.uploads
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .insert(
+                upload_id.clone(),
+                (content_type.to_string(), BTreeMap::new()),
+            );
+        Ok(upload_id)
+    }
+
+    async fn store_part(
+        &self,
+        _: &str,
+        upload_id: &str,
+        number: i32,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        self.uploads
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .get_mut(upload_id)
+            .ok_or_else(|| StorageError(format!("unknown upload {upload_id:?}")))?
+            .1
+            .insert(number, data);
+        Ok(())
+    }
+
+    async fn list_parts(&self, _: &str, upload_id: &str) -> Result<Vec<PartInfo>, StorageError> {
+        let uploads = self
+            .uploads
+            .read()
+            .map_err(|e| StorageError(e.to_string()))?;
+        let (_, parts) = uploads
+            .get(upload_id)
+            .ok_or_else(|| StorageError(format!("unknown upload {upload_id:?}")))?;
+        Ok(parts
+            .iter()
+            .map(|(number, data)| PartInfo {
+                number: *number,
+                size: data.len() as i64,
+            })
+            .collect())
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError> {
+        let (content_type, parts) = self
+            .uploads
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .remove(upload_id)
+            .ok_or_else(|| StorageError(format!("unknown upload {upload_id:?}")))?;
+        self.store(key, &content_type, parts.into_values().flatten().collect())
+            .await
+    }
+
+    async fn abort_multipart(&self, _: &str, upload_id: &str) -> Result<(), StorageError> {
+        self.uploads
+            .write()
+            .map_err(|e| StorageError(e.to_string()))?
+            .remove(upload_id);
+        Ok(())
+    }
+
+    fn default_public_base(&self) -> String {
+        PUBLIC_PATH.to_string()
+    }
+}
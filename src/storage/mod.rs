@@ -0,0 +1,358 @@
+//! Where uploaded images and videos live. The backend is picked by
+//! `STORAGE_BACKEND` (`s3`, `local` or `memory`); without it S3 is used when
+//! `S3_ENDPOINT_URL` is set and a local directory otherwise.
+//!
+//! The database holds object keys only. Urls are built when rows are read,
+//! from `IMAGE_BASE_URL` (a CDN for example) or the backend's own address.
+
+use std::{
+    env::var,
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, put},
+};
+use serde::Deserialize;
+
+use crate::{AppState, auth::api_token::hash_token, images::ImageError, videos};
+
+pub mod local;
+pub mod memory;
+pub mod reconcile;
+pub mod s3;
+
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+pub use s3::S3;
+
+/// Route prefix serving objects of the local and in-memory backends, and
+/// taking direct uploads for them.
+pub const PUBLIC_PATH: &str = "/uploads";
+
+/// How long an issued direct upload url stays valid.
+pub const UPLOAD_URL_TTL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone)]
+pub struct StorageError(pub String);
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// What is known about an object without reading it.
+#[derive(Debug, Clone)]
+pub struct ObjectHead {
+    pub content_type: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub last_modified: SystemTime,
+}
+
+/// A part of an unfinished multipart upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartInfo {
+    pub number: i32,
+    pub size: i64,
+}
+
+#[async_trait]
+pub trait ImageStorage: Send + Sync {
+    /// Saves `data` under `key`, replacing any object with that key.
+    async fn store(&self, key: &str, content_type: &str, data: Vec<u8>)
+    -> Result<(), StorageError>;
+
+    async fn load(&self, key: &str) -> Result<Option<StoredObject>, StorageError>;
+
+    /// Type and size of `key`, without reading it.
+    async fn head(&self, key: &str) -> Result<Option<ObjectHead>, StorageError>;
+
+    /// Deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Every stored object, in no particular order.
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError>;
+
+    /// Url the browser can `PUT` exactly `size` bytes of `key` to without
+    /// going through the app, or `None` when uploads go to the app's own
+    /// `PUT` route.
+    async fn presign_put(
+        &self,
+        _key: &str,
+        _content_type: &str,
+        _size: i64,
+        _expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+
+    /// Up to `len` bytes of `key` from `start`, for reading the headers of
+    /// objects too large to load whole and for serving them in pieces.
+    async fn load_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Starts a multipart upload of `key` and returns its id. Parts are
+    /// numbered from 1; nothing is visible under `key` until
+    /// [`ImageStorage::complete_multipart`].
+    async fn create_multipart(&self, key: &str, content_type: &str)
+    -> Result<String, StorageError>;
+
+    /// Saves part `number`, replacing an earlier attempt at it.
+    async fn store_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError>;
+
+    /// Url the browser can `PUT` part `number` to without going through the
+    /// app, or `None` when parts go to the app's own `PUT` route.
+    async fn presign_part(
+        &self,
+        _key: &str,
+        _upload_id: &str,
+        _number: i32,
+        _expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+
+    /// Parts stored so far, by number.
+    async fn list_parts(&self, key: &str, upload_id: &str) -> Result<Vec<PartInfo>, StorageError>;
+
+    /// Joins the stored parts into the object `key`.
+    async fn complete_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError>;
+
+    /// Drops an unfinished upload with its parts; unknown uploads are not
+    /// an error.
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError>;
+
+    /// Where objects are served from when `IMAGE_BASE_URL` is not set.
+    fn default_public_base(&self) -> String;
+}
+
+pub type Storage = Arc<dyn ImageStorage>;
+
+static PUBLIC_BASE: OnceLock<String> = OnceLock::new();
+
+/// Base url stored keys are resolved against, see
+/// [`crate::models::public_url`].
+pub fn public_base() -> &'static str {
+    PUBLIC_BASE.get().map(|x| x.as_str()).unwrap_or(PUBLIC_PATH)
+}
+
+/// Builds the configured backend and fixes [`public_base`] for the process.
+pub async fn from_env() -> Storage {
+    let storage = backend_from_env().await;
+    let base = var("IMAGE_BASE_URL").unwrap_or_else(|_| storage.default_public_base());
+    let _ = PUBLIC_BASE.set(base);
+    storage
+}
+
+async fn backend_from_env() -> Storage {
+    let backend = var("STORAGE_BACKEND").unwrap_or_else(|_| {
+        if var("S3_ENDPOINT_URL").is_ok() {
+            "s3".to_string()
+        } else {
+            "local".to_string()
+        }
+    });
+    match backend.as_str() {
+        "s3" => Arc::new(S3::get_from_env().await),
+        "local" => {
+            let dir = var("STORAGE_DIR").unwrap_or_else(|_| "uploads".to_string());
+            Arc::new(
+                LocalStorage::new(dir)
+                    .await
+                    .expect("STORAGE_DIR must be a writable directory"),
+            )
+        }
+        "memory" => Arc::new(MemoryStorage::default()),
+        x => panic!("unknown STORAGE_BACKEND {x:?}, use s3, local or memory"),
+    }
+}
+
+/// Keys are generated by the server; anything that could leave the
+/// storage directory is refused.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || matches!(x, '-' | '_' | '.'))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            &format!("{PUBLIC_PATH}/{{key}}"),
+            get(serve)
+                .put(receive)
+                .layer(DefaultBodyLimit::max(crate::images::max_image_bytes())),
+        )
+        .route(
+            &format!("{PUBLIC_PATH}/{{key}}/parts/{{number}}"),
+            put(receive_part).layer(DefaultBodyLimit::max(videos::PART_SIZE as usize)),
+        )
+}
+
+/// How much of an object [`serve`] reads at a time.
+const SERVE_CHUNK: u64 = 1024 * 1024;
+
+async fn serve(
+    State(storage): State<Storage>,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if !is_valid_key(&key) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let object = match storage.head(&key).await {
+        Ok(Some(x)) => x,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|x| x.to_str().ok())
+        .map_or(videos::ByteRange::Full, |x| {
+            videos::byte_range(x, object.size)
+        });
+    let (status, start, end) = match range {
+        videos::ByteRange::Full => (StatusCode::OK, 0, object.size),
+        videos::ByteRange::Partial { start, end } => (StatusCode::PARTIAL_CONTENT, start, end + 1),
+        videos::ByteRange::Unsatisfiable => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", object.size))],
+            )
+                .into_response();
+        }
+    };
+    // Read the object a chunk at a time so a long walkthrough never sits in
+    // memory whole.
+    let body = Body::from_stream(futures::stream::unfold(start, move |offset| {
+        let storage = storage.clone();
+        let key = key.clone();
+        async move {
+            if offset >= end {
+                return None;
+            }
+            let len = SERVE_CHUNK.min(end - offset);
+            let chunk = match storage.load_range(&key, offset, len).await {
+                Ok(Some(x)) if !x.is_empty() => x,
+                Ok(_) => return Some((Err(StorageError("object ended early".to_string())), end)),
+                Err(e) => return Some((Err(e), end)),
+            };
+            let next = offset + chunk.len() as u64;
+            Some((Ok::<_, StorageError>(Bytes::from(chunk)), next))
+        }
+    }));
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, object.content_type),
+            (header::CONTENT_LENGTH, (end - start).to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (
+                header::CACHE_CONTROL,
+                "public, max-age=31536000, immutable".to_string(),
+            ),
+        ],
+        body,
+    )
+        .into_response();
+    if status == StatusCode::PARTIAL_CONTENT {
+        let range = format!("bytes {start}-{}/{}", end - 1, object.size);
+        if let Ok(x) = range.parse() {
+            response.headers_mut().insert(header::CONTENT_RANGE, x);
+        }
+    }
+    response
+}
+
+#[derive(Deserialize)]
+struct UploadQuery {
+    token: String,
+}
+
+/// Takes a direct upload for backends that cannot presign urls; the token
+/// comes from the issued upload url.
+async fn receive(
+    State(app_state): State<AppState>,
+    Path(key): Path<String>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Response {
+    let upload = match crate::db::image_uploads::get_image_upload(&app_state.pool, &key).await {
+        Ok(Some(x)) if !x.expired && x.token_hash == hash_token(&query.token) => x,
+        Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if body.len() as i64 != upload.size {
+        return (StatusCode::BAD_REQUEST, ImageError::Mismatch.to_string()).into_response();
+    }
+    match app_state
+        .storage
+        .store(&key, &upload.content_type, body.to_vec())
+        .await
+    {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Takes a part of a multipart video upload for backends that cannot
+/// presign urls, until the plan's [`videos::PART_URL_TTL`] runs out.
+async fn receive_part(
+    State(app_state): State<AppState>,
+    Path((key, number)): Path<(String, i32)>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Response {
+    let upload =
+        match crate::db::video_uploads::get_video_upload_by_key(&app_state.pool, &key).await {
+            Ok(Some(x)) if x.token_hash == hash_token(&query.token) => x,
+            Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    if !(1..=videos::part_count(upload.size)).contains(&number)
+        || body.len() as i64 != videos::part_len(upload.size, number)
+    {
+        return (StatusCode::BAD_REQUEST, "unexpected part").into_response();
+    }
+    match app_state
+        .storage
+        .store_part(&key, &upload.upload_id, number, body.to_vec())
+        .await
+    {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
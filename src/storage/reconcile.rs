@@ -0,0 +1,142 @@
+//! Finds objects no row refers to (a failed delete, or images uploaded for
+//! an estate form that was never submitted) and rows whose objects are gone.
+//! Direct uploads that were never finalized or never made it into an estate,
+//! and video uploads abandoned halfway, are cleaned up along the way.
+
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+use sqlx::PgPool;
+
+use super::{ImageStorage, StorageError};
+
+/// Orphans younger than this may still belong to an estate form that is
+/// being filled in.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Default)]
+pub struct ReconcileReport {
+    /// Stored objects no row refers to.
+    pub orphans: Vec<String>,
+    /// Keys referenced from the database without a stored object.
+    pub missing: Vec<String>,
+    /// Orphans removed by this run, or that would be without `dry_run`.
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ReconcileError {
+    Db(sqlx::Error),
+    Storage(StorageError),
+}
+
+impl std::fmt::Display for ReconcileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReconcileError::Db(e) => write!(f, "database error: {e}"),
+            ReconcileError::Storage(e) => write!(f, "storage error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ReconcileError {}
+
+impl From<sqlx::Error> for ReconcileError {
+    fn from(e: sqlx::Error) -> Self {
+        ReconcileError::Db(e)
+    }
+}
+
+impl From<StorageError> for ReconcileError {
+    fn from(e: StorageError) -> Self {
+        ReconcileError::Storage(e)
+    }
+}
+
+/// Compares the stored objects with the keys in the database and deletes
+/// orphans older than `grace`, unless `dry_run` is set.
+pub async fn reconcile(
+    pool: &PgPool,
+    storage: &dyn ImageStorage,
+    grace: Duration,
+    dry_run: bool,
+) -> Result<ReconcileReport, ReconcileError> {
+    // Listing first means an upload finishing in between shows up as a
+    // young orphan at worst, never as a deleted object.
+    let objects = storage.list().await?;
+    let mut referenced = crate::db::estate_images::referenced_image_keys(pool)
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    referenced.extend(crate::db::estate_videos::referenced_video_keys(pool).await?);
+
+    let stored = objects
+        .iter()
+        .map(|x| x.key.as_str())
+        .collect::<HashSet<_>>();
+    let mut report = ReconcileReport {
+        missing: referenced
+            .iter()
+            .filter(|x| !stored.contains(x.as_str()))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+
+    let now = SystemTime::now();
+    for object in objects.iter().filter(|x| !referenced.contains(&x.key)) {
+        report.orphans.push(object.key.clone());
+        let age = now.duration_since(object.last_modified).unwrap_or_default();
+        if age < grace {
+            continue;
+        }
+        if !dry_run {
+            storage.delete(&object.key).await?;
+        }
+        report.deleted.push(object.key.clone());
+    }
+
+    if !dry_run {
+        crate::db::image_uploads::delete_expired_image_uploads(pool, grace.as_secs_f64()).await?;
+        crate::db::image_uploads::delete_stale_pending_images(pool, grace.as_secs_f64()).await?;
+        for upload in
+            crate::db::video_uploads::stale_video_uploads(pool, grace.as_secs_f64()).await?
+        {
+            storage
+                .abort_multipart(&upload.object_key, &upload.upload_id)
+                .await?;
+            crate::db::video_uploads::delete_video_upload(pool, &upload.upload_id).await?;
+        }
+    }
+
+    report.orphans.sort();
+    report.missing.sort();
+    report.deleted.sort();
+    Ok(report)
+}
+
+/// Runs [`reconcile`] every `period` for the life of the process, logging
+/// what it finds.
+pub async fn run_periodically(
+    pool: PgPool,
+    storage: super::Storage,
+    period: Duration,
+    grace: Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match reconcile(&pool, storage.as_ref(), grace, false).await {
+            Ok(report) => leptos::logging::log!(
+                "image reconciliation: {} orphans, {} deleted, {} missing {:?}",
+                report.orphans.len(),
+                report.deleted.len(),
+                report.missing.len(),
+                report.missing,
+            ),
+            Err(e) => leptos::logging::error!("image reconciliation failed: {e}"),
+        }
+    }
+}
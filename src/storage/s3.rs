@@ -0,0 +1,383 @@
+use std::{
+    env::var,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::{
+    Client,
+    config::Credentials,
+    presigning::PresigningConfig,
+    types::{CompletedMultipartUpload, CompletedPart},
+};
+
+use super::{ImageStorage, ObjectHead, ObjectInfo, PartInfo, StorageError, StoredObject};
+
+#[derive(Clone)]
+pub struct S3 {
+    pub username: String,
+    pub password: String,
+    pub region: String,
+    pub bucket: String,
+    pub endpoint_url: String,
+    pub client: Client,
+}
+
+impl S3 {
+    pub async fn get_from_env() -> Self {
+        let username = var("S3_USERNAME").expect("S3_USERNAME must be set in .env file");
+        let password = var("S3_PASSWORD").expect("S3_PASSWORD must be set in .env file");
+        let region = var("S3_REGION").expect("S3_REGION must be set in .env file");
+        let bucket = var("S3_BUCKET").expect("S3_BUCKET must be set in .env file");
+        let endpoint_url =
+            var("S3_ENDPOINT_URL").expect("S3_ENDPOINT_URL must be set in .env file");
+        let client = Self::create_client(
+            username.clone(),
+            password.clone(),
+            region.clone(),
+            endpoint_url.clone(),
+        )
+        .await;
+        Self {
+            username,
+            password,
+            region,
+            bucket,
+            endpoint_url,
+            client,
+        }
+    }
+
+    async fn create_client(
+        username: String,
+        password: String,
+        region: String,
+        endpoint_url: String,
+    ) -> Client {
+        let creds = Credentials::new(username, password, None, None, "static");
+        println!("configuring s3 ...");
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .credentials_provider(creds)
+            .region(aws_config::Region::new(region))
+            .endpoint_url(endpoint_url)
+            .load()
+            .await;
+        println!("s3 setup complete!");
+        Client::new(&config)
+    }
+}
+
+impl S3 {
+    /// Stored parts with their sizes. The browser never sees the ETags
+    /// completing an upload takes, they are read back from here.
+    async fn completed_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<(CompletedPart, i64)>, StorageError> {
+        let mut pages = self
+            .client
+            .list_parts()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .into_paginator()
+            .send();
+        let mut parts = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| StorageError(format!("{e:?}")))?;
+            for part in page.parts() {
+                let completed = CompletedPart::builder()
+                    .set_part_number(part.part_number())
+                    .set_e_tag(part.e_tag().map(|x| x.to_string()))
+                    .build();
+                parts.push((completed, part.size().unwrap_or_default()));
+            }
+        }
+        Ok(parts)
+    }
+}
+
+#[async_trait]
+impl ImageStorage for S3 {
+    async fn store(
+        &self,
+        key: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(data.into())
+            .send()
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(())
+    }
+
+    async fn load(&self, key: &str) -> Result<Option<StoredObject>, StorageError> {
+        let object = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(x) => x,
+            Err(e) if e.as_service_error().is_some_and(|x| x.is_no_such_key()) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(StorageError(format!("{e:?}"))),
+        };
+        let content_type = object
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError(e.to_string()))?
+            .into_bytes()
+            .to_vec();
+        Ok(Some(StoredObject { content_type, data }))
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectHead>, StorageError> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(x) => Ok(Some(ObjectHead {
+                content_type: x
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+                size: x.content_length().unwrap_or_default().max(0) as u64,
+            })),
+            Err(e) if e.as_service_error().is_some_and(|x| x.is_not_found()) => Ok(None),
+            Err(e) => Err(StorageError(format!("{e:?}"))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<ObjectInfo>, StorageError> {
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .into_paginator()
+            .send();
+        let mut objects = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| StorageError(format!("{e:?}")))?;
+            for object in page.contents() {
+                let (Some(key), Some(last_modified)) = (object.key(), object.last_modified())
+                else {
+                    continue;
+                };
+                objects.push(ObjectInfo {
+                    key: key.to_string(),
+                    last_modified: SystemTime::try_from(*last_modified)
+                        .map_err(|e| StorageError(e.to_string()))?,
+                });
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn presign_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: i64,
+        expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        let config =
+            PresigningConfig::expires_in(expires_in).map_err(|e| StorageError(e.to_string()))?;
+        // Signing the length stops a client from putting more than it
+        // announced through the url.
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .content_length(size)
+            .presigned(config)
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(Some(request.uri().to_string()))
+    }
+
+    async fn load_range(
+        &self,
+        key: &str,
+        start: u64,
+        len: u64,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        if len == 0 {
+            return Ok(Some(Vec::new()));
+        }
+        let object = match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .range(format!("bytes={start}-{}", start + len - 1))
+            .send()
+            .await
+        {
+            Ok(x) => x,
+            Err(e) if e.as_service_error().is_some_and(|x| x.is_no_such_key()) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(StorageError(format!("{e:?}"))),
+        };
+        let data = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError(e.to_string()))?
+            .into_bytes()
+            .to_vec();
+        Ok(Some(data))
+    }
+
+    async fn create_multipart(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> Result<String, StorageError> {
+        let upload = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        upload
+            .upload_id()
+            .map(|x| x.to_string())
+            .ok_or_else(|| StorageError("no upload id was returned".to_string()))
+    }
+
+    async fn store_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        data: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        self.client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(number)
+            .body(data.into())
+            .send()
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(())
+    }
+
+    async fn presign_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: i32,
+        expires_in: Duration,
+    ) -> Result<Option<String>, StorageError> {
+        let config =
+            PresigningConfig::expires_in(expires_in).map_err(|e| StorageError(e.to_string()))?;
+        let request = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(number)
+            .presigned(config)
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(Some(request.uri().to_string()))
+    }
+
+    async fn list_parts(&self, key: &str, upload_id: &str) -> Result<Vec<PartInfo>, StorageError> {
+        Ok(self
+            .completed_parts(key, upload_id)
+            .await?
+            .into_iter()
+            .map(|(part, size)| PartInfo {
+                number: part.part_number().unwrap_or_default(),
+                size,
+            })
+            .collect())
+    }
+
+    async fn complete_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError> {
+        let parts = self
+            .completed_parts(key, upload_id)
+            .await?
+            .into_iter()
+            .map(|x| x.0)
+            .collect();
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| StorageError(format!("{e:?}")))?;
+        Ok(())
+    }
+
+    async fn abort_multipart(&self, key: &str, upload_id: &str) -> Result<(), StorageError> {
+        match self
+            .client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+        {
+            Err(e) if !e.as_service_error().is_some_and(|x| x.is_no_such_upload()) => {
+                Err(StorageError(format!("{e:?}")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn default_public_base(&self) -> String {
+        format!("{}/{}", self.endpoint_url, self.bucket)
+    }
+}
@@ -0,0 +1,135 @@
+//! Deleted estates and users wait in the trash, where they can be restored,
+//! for `TRASH_RETENTION_DAYS`. After that they are purged for good, and only
+//! then are an estate's images and videos removed from storage.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::AuditEntity,
+    storage::{ImageStorage, StorageError},
+};
+
+/// How often the server looks for entries past the retention period.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How many days deleted entries stay restorable.
+pub fn retention_days() -> i32 {
+    std::env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .filter(|x| *x > 0)
+        .unwrap_or(30)
+}
+
+#[derive(Debug, Default)]
+pub struct PurgeReport {
+    pub estates: Vec<Uuid>,
+    pub users: Vec<Uuid>,
+    /// Objects removed from storage along with the estates.
+    pub objects: usize,
+}
+
+#[derive(Debug)]
+pub enum PurgeError {
+    Db(sqlx::Error),
+    Storage(StorageError),
+}
+
+impl std::fmt::Display for PurgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurgeError::Db(e) => write!(f, "database error: {e}"),
+            PurgeError::Storage(e) => write!(f, "storage error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PurgeError {}
+
+impl From<sqlx::Error> for PurgeError {
+    fn from(e: sqlx::Error) -> Self {
+        PurgeError::Db(e)
+    }
+}
+
+impl From<StorageError> for PurgeError {
+    fn from(e: StorageError) -> Self {
+        PurgeError::Storage(e)
+    }
+}
+
+/// Deletes everything that has been in the trash for more than
+/// `retention_days`, recording each purge in the audit log.
+///
+/// Rows go before their objects, so a failed object delete leaves an orphan
+/// for the image reconciliation rather than a row pointing at nothing.
+pub async fn purge(
+    pool: &PgPool,
+    storage: &dyn ImageStorage,
+    retention_days: i32,
+) -> Result<PurgeReport, PurgeError> {
+    let mut report = PurgeReport::default();
+
+    for id in crate::db::estates::get_purgeable_estate_ids(pool, retention_days).await? {
+        let images = crate::db::estate_images::get_estate_images(pool, id).await?;
+        let videos = crate::db::estate_videos::get_estate_videos(pool, id).await?;
+        if !crate::db::estates::purge_estate(pool, id).await? {
+            continue;
+        }
+        crate::db::audit::record_system(
+            pool,
+            "estate.purge",
+            AuditEntity::Estate,
+            Some(id),
+            None,
+            None,
+        )
+        .await?;
+        report.estates.push(id);
+
+        let keys = images
+            .iter()
+            .flat_map(|x| x.keys.stored())
+            .chain(videos.iter().flat_map(|x| x.stored()));
+        for key in keys {
+            storage.delete(key).await?;
+            report.objects += 1;
+        }
+    }
+
+    report.users = crate::db::users::purge_deleted_users(pool, retention_days).await?;
+    for id in &report.users {
+        crate::db::audit::record_system(
+            pool,
+            "user.purge",
+            AuditEntity::User,
+            Some(*id),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    Ok(report)
+}
+
+/// Runs [`purge`] every `period` for the life of the process.
+pub async fn run_periodically(pool: PgPool, storage: crate::storage::Storage, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match purge(&pool, storage.as_ref(), retention_days()).await {
+            Ok(report) if report.estates.is_empty() && report.users.is_empty() => {}
+            Ok(report) => leptos::logging::log!(
+                "trash purge: {} estates, {} users, {} objects",
+                report.estates.len(),
+                report.users.len(),
+                report.objects,
+            ),
+            Err(e) => leptos::logging::error!("trash purge failed: {e}"),
+        }
+    }
+}
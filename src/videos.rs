@@ -0,0 +1,576 @@
+//! Video walkthroughs: files reach storage through multipart uploads, and
+//! before a video is listed its container and codecs are read from the
+//! MP4/QuickTime boxes. Nothing is transcoded, so only formats browsers
+//! play natively are accepted.
+
+#[cfg(feature = "ssr")]
+use {
+    crate::{
+        auth::api_token::hash_token,
+        db::{estate_videos, video_uploads},
+        models::{VideoPart, VideoUpload, VideoUploadPlan},
+        storage::{ImageStorage, PUBLIC_PATH},
+    },
+    sqlx::PgPool,
+    std::{collections::HashSet, time::Duration},
+    uuid::Uuid,
+};
+
+/// Size of every part of a multipart upload but the last. S3 refuses parts
+/// under 5 MB.
+pub const PART_SIZE: i64 = 8 * 1024 * 1024;
+
+/// How long the urls of a plan stay valid. Large files take a while; when
+/// they run out the upload is resumed with fresh ones.
+#[cfg(feature = "ssr")]
+pub const PART_URL_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Upload size limit unless `VIDEO_MAX_BYTES` says otherwise.
+pub const DEFAULT_MAX_VIDEO_BYTES: i64 = 2 * 1024 * 1024 * 1024;
+
+/// The movie header holds the index of every sample; anything larger is
+/// not a walkthrough.
+#[cfg(feature = "ssr")]
+const MAX_MOVIE_HEADER_BYTES: u64 = 64 * 1024 * 1024;
+
+const VIDEO_CODECS: [&str; 6] = ["avc1", "avc3", "hvc1", "hev1", "av01", "vp09"];
+const AUDIO_CODECS: [&str; 4] = ["mp4a", "Opus", "ac-3", "ec-3"];
+
+#[cfg(feature = "ssr")]
+pub fn max_video_bytes() -> i64 {
+    std::env::var("VIDEO_MAX_BYTES")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(DEFAULT_MAX_VIDEO_BYTES)
+}
+
+/// Number of parts a file of `size` bytes is split into.
+pub fn part_count(size: i64) -> i32 {
+    ((size + PART_SIZE - 1) / PART_SIZE) as i32
+}
+
+/// Length of part `number` (counted from 1) of a file of `size` bytes.
+pub fn part_len(size: i64, number: i32) -> i64 {
+    (size - (number as i64 - 1) * PART_SIZE).clamp(0, PART_SIZE)
+}
+
+/// What a `Range` header asks of an object, which is how players seek in a
+/// walkthrough without downloading it first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No usable range; the whole object is sent.
+    Full,
+    /// The bytes from `start` to `end`, both included.
+    Partial { start: u64, end: u64 },
+    /// The range lies past the end of the object.
+    Unsatisfiable,
+}
+
+/// Reads a `Range` header for an object of `size` bytes. Only a single
+/// range is honoured; a list of ranges or a malformed one gets the whole
+/// object, as HTTP allows.
+pub fn byte_range(header: &str, size: u64) -> ByteRange {
+    let Some((start, end)) = header
+        .trim()
+        .strip_prefix("bytes=")
+        .filter(|x| !x.contains(','))
+        .and_then(|x| x.split_once('-'))
+        .map(|(start, end)| (start.trim(), end.trim()))
+    else {
+        return ByteRange::Full;
+    };
+    let last = size.checked_sub(1);
+    let (start, end) = if start.is_empty() {
+        // The last `end` bytes.
+        match end.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(len) => (size.saturating_sub(len), last),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        match end {
+            "" => (start, last),
+            end => match end.parse::<u64>() {
+                Ok(end) if end >= start => (start, last.map(|x| x.min(end))),
+                _ => return ByteRange::Full,
+            },
+        }
+    };
+    match end {
+        Some(end) if start <= end => ByteRange::Partial { start, end },
+        _ => ByteRange::Unsatisfiable,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoContainer {
+    Mp4,
+    QuickTime,
+}
+
+impl VideoContainer {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type {
+            "video/mp4" => Some(VideoContainer::Mp4),
+            "video/quicktime" => Some(VideoContainer::QuickTime),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "video/mp4",
+            VideoContainer::QuickTime => "video/quicktime",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "mp4",
+            VideoContainer::QuickTime => "mov",
+        }
+    }
+
+    /// Reads the container from the major brand of an `ftyp` box.
+    fn from_brand(brand: &[u8]) -> Option<Self> {
+        match brand {
+            b"qt  " => Some(VideoContainer::QuickTime),
+            b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1"
+            | b"M4V " => Some(VideoContainer::Mp4),
+            _ => None,
+        }
+    }
+}
+
+/// What [`inspect`] found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoInfo {
+    pub container: VideoContainer,
+    pub duration_secs: f64,
+    pub width: i32,
+    pub height: i32,
+    pub video_codec: String,
+    pub audio_codec: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum VideoError {
+    TooLarge(i64),
+    /// Not an MP4 or QuickTime file, or not the container it was announced as.
+    Unsupported,
+    UnsupportedCodec(String),
+    NoVideoTrack,
+    Malformed(&'static str),
+    /// Some parts of the upload have not reached storage yet.
+    Incomplete,
+    /// An upload that was never started, belongs to someone else or was
+    /// already finished.
+    UploadNotFound,
+    Storage(String),
+}
+
+impl std::fmt::Display for VideoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::TooLarge(limit) => {
+                write!(f, "the video is larger than {} MB", limit / (1024 * 1024))
+            }
+            VideoError::Unsupported => write!(f, "unsupported video format, use MP4 or MOV"),
+            VideoError::UnsupportedCodec(x) => {
+                write!(f, "unsupported codec {x:?}, use H.264, H.265, AV1 or VP9")
+            }
+            VideoError::NoVideoTrack => write!(f, "the file has no video track"),
+            VideoError::Malformed(e) => write!(f, "could not read the video: {e}"),
+            VideoError::Incomplete => write!(f, "the video has not been uploaded completely"),
+            VideoError::UploadNotFound => write!(f, "the video upload was not found"),
+            VideoError::Storage(e) => write!(f, "video upload failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VideoError {}
+
+/// Start of a box: its type, the length of the header and the length of the
+/// whole box, `None` when it runs to the end of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxHeader {
+    pub kind: [u8; 4],
+    pub header_len: u64,
+    pub size: Option<u64>,
+}
+
+/// Parses a box header from the first 8 or 16 bytes of `data`.
+pub fn read_box_header(data: &[u8]) -> Result<BoxHeader, VideoError> {
+    let short = data
+        .get(..8)
+        .ok_or(VideoError::Malformed("truncated box header"))?;
+    let kind = [short[4], short[5], short[6], short[7]];
+    let (header_len, size) = match u32::from_be_bytes([short[0], short[1], short[2], short[3]]) {
+        0 => (8, None),
+        1 => {
+            let large = data
+                .get(8..16)
+                .ok_or(VideoError::Malformed("truncated box header"))?;
+            (16, Some(u64::from_be_bytes(large.try_into().unwrap())))
+        }
+        x => (8, Some(x as u64)),
+    };
+    if size.is_some_and(|x| x < header_len) {
+        return Err(VideoError::Malformed("box smaller than its header"));
+    }
+    Ok(BoxHeader {
+        kind,
+        header_len,
+        size,
+    })
+}
+
+/// Boxes as (type, payload) pairs.
+type Boxes<'a> = Vec<([u8; 4], &'a [u8])>;
+
+/// The boxes directly inside `data`.
+fn children(data: &[u8]) -> Result<Boxes<'_>, VideoError> {
+    let mut boxes = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let header = read_box_header(rest)?;
+        let size = header.size.unwrap_or(rest.len() as u64);
+        let payload = rest
+            .get(header.header_len as usize..size as usize)
+            .ok_or(VideoError::Malformed("box larger than its parent"))?;
+        boxes.push((header.kind, payload));
+        rest = &rest[size as usize..];
+    }
+    Ok(boxes)
+}
+
+fn child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Result<Option<&'a [u8]>, VideoError> {
+    Ok(children(data)?
+        .into_iter()
+        .find(|x| &x.0 == kind)
+        .map(|x| x.1))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Result<u32, VideoError> {
+    data.get(at..at + 4)
+        .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+        .ok_or(VideoError::Malformed("truncated box"))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Result<u64, VideoError> {
+    data.get(at..at + 8)
+        .map(|x| u64::from_be_bytes(x.try_into().unwrap()))
+        .ok_or(VideoError::Malformed("truncated box"))
+}
+
+/// Duration in seconds from a movie header.
+fn movie_duration(mvhd: &[u8]) -> Result<f64, VideoError> {
+    let (timescale, duration) = match mvhd.first() {
+        Some(1) => (be_u32(mvhd, 20)?, be_u64(mvhd, 24)?),
+        _ => (be_u32(mvhd, 12)?, be_u32(mvhd, 16)? as u64),
+    };
+    if timescale == 0 {
+        return Err(VideoError::Malformed("movie without a timescale"));
+    }
+    Ok(duration as f64 / timescale as f64)
+}
+
+/// Handler type and the format of the first sample description of a track.
+fn track_format(trak: &[u8]) -> Result<Option<([u8; 4], String)>, VideoError> {
+    let Some(mdia) = child(trak, b"mdia")? else {
+        return Ok(None);
+    };
+    let handler = child(mdia, b"hdlr")?
+        .and_then(|x| x.get(8..12))
+        .ok_or(VideoError::Malformed("track without a handler"))?;
+    let format = child(mdia, b"minf")?
+        .map(|x| child(x, b"stbl"))
+        .transpose()?
+        .flatten()
+        .map(|x| child(x, b"stsd"))
+        .transpose()?
+        .flatten()
+        .and_then(|x| x.get(12..16))
+        .ok_or(VideoError::Malformed("track without a sample description"))?;
+    Ok(Some((
+        handler.try_into().unwrap(),
+        String::from_utf8_lossy(format).into_owned(),
+    )))
+}
+
+/// Checks the payloads of a file's `ftyp` and `moov` boxes against what
+/// browsers play, and against the container the file was announced as.
+pub fn inspect(
+    ftyp: &[u8],
+    moov: &[u8],
+    expected: VideoContainer,
+) -> Result<VideoInfo, VideoError> {
+    let container = ftyp
+        .get(..4)
+        .and_then(VideoContainer::from_brand)
+        .filter(|x| *x == expected)
+        .ok_or(VideoError::Unsupported)?;
+    let duration_secs = movie_duration(
+        child(moov, b"mvhd")?.ok_or(VideoError::Malformed("movie without a header"))?,
+    )?;
+
+    let mut video = None;
+    let mut audio_codec = None;
+    for (kind, trak) in children(moov)? {
+        if &kind != b"trak" {
+            continue;
+        }
+        match track_format(trak)? {
+            Some((handler, codec)) if &handler == b"vide" => {
+                if !VIDEO_CODECS.contains(&codec.as_str()) {
+                    return Err(VideoError::UnsupportedCodec(codec));
+                }
+                // Width and height close the track header as 16.16 fixed point.
+                let tkhd = child(trak, b"tkhd")?
+                    .filter(|x| x.len() >= 8)
+                    .ok_or(VideoError::Malformed("track without a header"))?;
+                let width = be_u32(tkhd, tkhd.len() - 8)? >> 16;
+                let height = be_u32(tkhd, tkhd.len() - 4)? >> 16;
+                video.get_or_insert((codec, width as i32, height as i32));
+            }
+            Some((handler, codec)) if &handler == b"soun" => {
+                if !AUDIO_CODECS.contains(&codec.as_str()) {
+                    return Err(VideoError::UnsupportedCodec(codec));
+                }
+                audio_codec.get_or_insert(codec);
+            }
+            _ => (),
+        }
+    }
+    let (video_codec, width, height) = video.ok_or(VideoError::NoVideoTrack)?;
+
+    Ok(VideoInfo {
+        container,
+        duration_secs,
+        width,
+        height,
+        video_codec,
+        audio_codec,
+    })
+}
+
+/// Walks the top-level boxes of a stored file with ranged reads, so only
+/// `ftyp` and `moov` are loaded, and runs [`inspect`] on them.
+#[cfg(feature = "ssr")]
+pub async fn probe(
+    storage: &dyn ImageStorage,
+    key: &str,
+    size: u64,
+    expected: VideoContainer,
+) -> Result<VideoInfo, VideoError> {
+    let read = |start: u64, len: u64| async move {
+        storage
+            .load_range(key, start, len)
+            .await
+            .map_err(|e| VideoError::Storage(e.to_string()))?
+            .filter(|x| x.len() as u64 == len)
+            .ok_or(VideoError::Malformed("file shorter than announced"))
+    };
+
+    let mut ftyp = None;
+    let mut moov = None;
+    let mut offset = 0;
+    // `offset` never passes `size`, so `size - offset` cannot underflow.
+    while size - offset >= 8 && moov.is_none() {
+        let header = read_box_header(&read(offset, 16.min(size - offset)).await?)?;
+        let box_size = header.size.unwrap_or(size - offset);
+        let end = offset
+            .checked_add(box_size)
+            .filter(|x| *x <= size)
+            .ok_or(VideoError::Malformed("box larger than the file"))?;
+        let payload = offset + header.header_len..end;
+        match &header.kind {
+            b"ftyp" if offset == 0 => {
+                ftyp = Some(read(payload.start, payload.end - payload.start).await?);
+            }
+            _ if offset == 0 => return Err(VideoError::Unsupported),
+            b"moov" if payload.end - payload.start > MAX_MOVIE_HEADER_BYTES => {
+                return Err(VideoError::Malformed("movie header too large"));
+            }
+            b"moov" => moov = Some(read(payload.start, payload.end - payload.start).await?),
+            _ => (),
+        }
+        offset = end;
+    }
+
+    let ftyp = ftyp.ok_or(VideoError::Unsupported)?;
+    let moov = moov.ok_or(VideoError::Malformed("file without a movie header"))?;
+    inspect(&ftyp, &moov, expected)
+}
+
+#[cfg(feature = "ssr")]
+fn db_error(e: sqlx::Error) -> VideoError {
+    VideoError::Storage(e.to_string())
+}
+
+#[cfg(feature = "ssr")]
+fn storage_error(e: crate::storage::StorageError) -> VideoError {
+    VideoError::Storage(e.to_string())
+}
+
+/// Starts a multipart upload of a file, or resumes the user's unfinished
+/// upload of the same file to the same estate, and plans the parts still
+/// missing.
+#[cfg(feature = "ssr")]
+pub async fn start_upload(
+    pool: &PgPool,
+    storage: &dyn ImageStorage,
+    estate_id: Uuid,
+    user_id: Uuid,
+    file_name: &str,
+    content_type: &str,
+    size: i64,
+) -> Result<VideoUploadPlan, VideoError> {
+    let limit = max_video_bytes();
+    if size > limit {
+        return Err(VideoError::TooLarge(limit));
+    }
+    if size <= 0 {
+        return Err(VideoError::Malformed("the file is empty"));
+    }
+    let container =
+        VideoContainer::from_content_type(content_type).ok_or(VideoError::Unsupported)?;
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+    let resumed = match video_uploads::find_video_upload(pool, estate_id, user_id, file_name, size)
+        .await
+        .map_err(db_error)?
+    {
+        Some(upload) => match storage
+            .list_parts(&upload.object_key, &upload.upload_id)
+            .await
+        {
+            Ok(parts) => Some((upload, parts)),
+            // Storage no longer knows the upload, the memory backend
+            // restarted for example
+            Err(_) => {
+                video_uploads::delete_video_upload(pool, &upload.upload_id)
+                    .await
+                    .map_err(db_error)?;
+                None
+            }
+        },
+        None => None,
+    };
+    let (upload, stored) = match resumed {
+        Some((upload, parts)) => {
+            video_uploads::set_video_upload_token(
+                pool,
+                &upload.upload_id,
+                &hash_token(&token),
+                PART_URL_TTL.as_secs_f64(),
+            )
+            .await
+            .map_err(db_error)?;
+            (upload, parts)
+        }
+        None => {
+            let object_key = format!("{}.{}", Uuid::new_v4(), container.extension());
+            let upload_id = storage
+                .create_multipart(&object_key, container.content_type())
+                .await
+                .map_err(storage_error)?;
+            let upload = VideoUpload {
+                upload_id,
+                object_key,
+                estate_id,
+                user_id,
+                file_name: file_name.to_string(),
+                content_type: container.content_type().to_string(),
+                size,
+                token_hash: hash_token(&token),
+            };
+            video_uploads::create_video_upload(pool, &upload, PART_URL_TTL.as_secs_f64())
+                .await
+                .map_err(db_error)?;
+            (upload, Vec::new())
+        }
+    };
+
+    let done = stored
+        .iter()
+        .filter(|x| x.size == part_len(size, x.number))
+        .map(|x| x.number)
+        .collect::<HashSet<_>>();
+    let mut parts = Vec::new();
+    for number in (1..=part_count(size)).filter(|x| !done.contains(x)) {
+        let url = storage
+            .presign_part(&upload.object_key, &upload.upload_id, number, PART_URL_TTL)
+            .await
+            .map_err(storage_error)?
+            .unwrap_or_else(|| {
+                format!(
+                    "{PUBLIC_PATH}/{}/parts/{number}?token={token}",
+                    upload.object_key
+                )
+            });
+        parts.push(VideoPart { number, url });
+    }
+
+    Ok(VideoUploadPlan {
+        uploaded: done.iter().map(|x| part_len(size, *x)).sum(),
+        upload_id: upload.upload_id,
+        parts,
+    })
+}
+
+/// Joins the parts of a finished upload, checks the file and adds it to the
+/// estate. A file that fails the check is deleted; an upload with parts
+/// missing is left to be resumed.
+#[cfg(feature = "ssr")]
+pub async fn finish_upload(
+    pool: &PgPool,
+    storage: &dyn ImageStorage,
+    upload: &VideoUpload,
+) -> Result<Uuid, VideoError> {
+    let parts = storage
+        .list_parts(&upload.object_key, &upload.upload_id)
+        .await
+        .map_err(storage_error)?;
+    let complete = parts.len() == part_count(upload.size) as usize
+        && parts
+            .iter()
+            .zip(1..)
+            .all(|(x, number)| x.number == number && x.size == part_len(upload.size, number));
+    if !complete {
+        return Err(VideoError::Incomplete);
+    }
+
+    storage
+        .complete_multipart(&upload.object_key, &upload.upload_id)
+        .await
+        .map_err(storage_error)?;
+    video_uploads::delete_video_upload(pool, &upload.upload_id)
+        .await
+        .map_err(db_error)?;
+
+    let container =
+        VideoContainer::from_content_type(&upload.content_type).ok_or(VideoError::Unsupported)?;
+    let info = match probe(storage, &upload.object_key, upload.size as u64, container).await {
+        Ok(x) => x,
+        Err(e) => {
+            storage
+                .delete(&upload.object_key)
+                .await
+                .map_err(storage_error)?;
+            return Err(e);
+        }
+    };
+    estate_videos::add_estate_video(
+        pool,
+        upload.estate_id,
+        &upload.object_key,
+        upload.size,
+        &info,
+    )
+    .await
+    .map_err(db_error)
+}
@@ -0,0 +1,101 @@
+use cryptos_site::auth::{
+    AuthError, Level, Permission, authorize, authorize_permission, authorize_scoped,
+};
+use uuid::Uuid;
+
+#[test]
+fn anonymous_caller_is_rejected() {
+    assert_eq!(
+        authorize(None, &Level::Admin),
+        Err(AuthError::Unauthenticated)
+    );
+    assert_eq!(
+        authorize(None, &Level::User),
+        Err(AuthError::Unauthenticated)
+    );
+}
+
+#[test]
+fn user_is_rejected_for_admin_operations() {
+    let caller = Some((Uuid::new_v4(), Level::User));
+    assert_eq!(
+        authorize(caller, &Level::Admin),
+        Err(AuthError::Forbidden(Level::Admin))
+    );
+}
+
+#[test]
+fn user_is_allowed_for_user_operations() {
+    let id = Uuid::new_v4();
+    assert_eq!(authorize(Some((id, Level::User)), &Level::User), Ok(id));
+}
+
+#[test]
+fn admin_is_allowed_for_every_operation() {
+    let id = Uuid::new_v4();
+    assert_eq!(authorize(Some((id, Level::Admin)), &Level::Admin), Ok(id));
+    assert_eq!(authorize(Some((id, Level::Admin)), &Level::User), Ok(id));
+}
+
+#[test]
+fn permission_must_be_granted_to_the_callers_level() {
+    let id = Uuid::new_v4();
+    let granted = [Permission::EstatesView, Permission::EstatesEdit];
+    assert_eq!(
+        authorize_permission(Some((id, Level::Agent)), &granted, Permission::EstatesEdit),
+        Ok(id)
+    );
+    assert_eq!(
+        authorize_permission(Some((id, Level::Admin)), &granted, Permission::UsersManage),
+        Err(AuthError::MissingPermission(Permission::UsersManage))
+    );
+    assert_eq!(
+        authorize_permission(None, &granted, Permission::EstatesView),
+        Err(AuthError::Unauthenticated)
+    );
+}
+
+#[test]
+fn super_admin_holds_every_permission() {
+    let id = Uuid::new_v4();
+    for permission in Permission::ALL {
+        assert_eq!(
+            authorize_permission(Some((id, Level::SuperAdmin)), &[], permission),
+            Ok(id)
+        );
+    }
+}
+
+#[test]
+fn token_is_limited_to_its_scopes() {
+    let id = Uuid::new_v4();
+    let granted = [Permission::EstatesView, Permission::EstatesEdit];
+    let scopes = [Permission::EstatesView];
+    assert_eq!(
+        authorize_scoped(
+            Some((id, Level::Agent)),
+            &granted,
+            &scopes,
+            Permission::EstatesView
+        ),
+        Ok(id)
+    );
+    assert_eq!(
+        authorize_scoped(
+            Some((id, Level::Agent)),
+            &granted,
+            &scopes,
+            Permission::EstatesEdit
+        ),
+        Err(AuthError::MissingPermission(Permission::EstatesEdit))
+    );
+    assert_eq!(
+        authorize_scoped(
+            Some((id, Level::SuperAdmin)),
+            &[],
+            &scopes,
+            Permission::UsersManage
+        ),
+        Err(AuthError::MissingPermission(Permission::UsersManage))
+    );
+}
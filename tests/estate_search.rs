@@ -0,0 +1,79 @@
+//! Runs against the database in `DATABASE_URL` and is skipped without one.
+#![cfg(feature = "ssr")]
+
+use cryptos_site::db::{create_pool, estates::get_estates_page, run_migrations};
+use cryptos_site::models::{EstateFilter, HIGHLIGHT_END, HIGHLIGHT_START};
+use uuid::Uuid;
+
+/// The highlighted words of a snippet.
+fn highlights(snippet: &str) -> Vec<&str> {
+    snippet
+        .split(HIGHLIGHT_START)
+        .skip(1)
+        .filter_map(|x| x.split_once(HIGHLIGHT_END).map(|(x, _)| x))
+        .collect()
+}
+
+#[test]
+fn snippets_highlight_normalized_matches_in_every_field() {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set; skipping");
+        return;
+    };
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        let pool = create_pool(&database_url).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        // A word of its own keeps other rows out of the results.
+        let tag = format!("وسم{}", Uuid::new_v4().simple());
+        let described = Uuid::new_v4();
+        let named = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO estates (id, name, address, image_key, description, price_in_cents, space_in_meters)
+            VALUES ($1, $3, 'مدينة نصر', 'x.webp', 'شقة مع إطلالة رائعة على الحديقة', 100, 100),
+                ($2, 'فيلا الياسمين', $3, 'x.webp', 'حديقة خاصة', 100, 100)",
+        )
+        .bind(described)
+        .bind(named)
+        .bind(&tag)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let search = |text: String| {
+            let pool = pool.clone();
+            async move {
+                let filter = EstateFilter {
+                    text: Some(text),
+                    ..Default::default()
+                };
+                let page = get_estates_page(&pool, &filter, None, 10).await.unwrap();
+                page.items
+                    .into_iter()
+                    .map(|x| (x.estate.id, x.snippet.unwrap_or_default()))
+                    .collect::<Vec<_>>()
+            }
+        };
+        // Typed without the hamza the description has.
+        let by_description = search(format!("{tag} اطلالة")).await;
+        // Both words are in the name and address, none in the description.
+        let by_name = search(format!("{tag} الياسمين")).await;
+
+        sqlx::query("DELETE FROM estates WHERE id = ANY($1)")
+            .bind(vec![described, named])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let [(id, snippet)] = by_description.as_slice() else {
+            panic!("expected one estate, got {by_description:?}");
+        };
+        assert_eq!(*id, described);
+        assert!(highlights(snippet).contains(&"اطلاله"), "{snippet:?}");
+        let [(id, snippet)] = by_name.as_slice() else {
+            panic!("expected one estate, got {by_name:?}");
+        };
+        assert_eq!(*id, named);
+        assert!(highlights(snippet).contains(&"الياسمين"), "{snippet:?}");
+    });
+}
@@ -0,0 +1,350 @@
+use cryptos_site::auth::{Level, Permission, authorize_permission};
+use cryptos_site::models::{
+    AuditCursor, EstateAttributes, EstateCursor, EstateSort, EstateStatus, Finishing,
+    HIGHLIGHT_END, HIGHLIGHT_START, ImageKeys, InstallmentFrequency, ListingPurpose,
+    ListingSchedule, PaymentPlan, PaymentTerms, PropertyType, floor_label, highlight_segments,
+    is_date, is_remote_url, is_schedule_time, public_url, validate_price, validate_space,
+};
+use uuid::Uuid;
+
+#[test]
+fn cursor_round_trips_through_its_string_form() {
+    let cursor = EstateCursor {
+        key: -1_792_304_113_393_815,
+        id: Uuid::new_v4(),
+    };
+    assert_eq!(EstateCursor::parse(&cursor.to_string()), Some(cursor));
+    assert_eq!(EstateCursor::parse("not-a-cursor"), None);
+    assert_eq!(EstateCursor::parse("12_not-a-uuid"), None);
+}
+
+#[test]
+fn sort_order_parses_from_its_query_value() {
+    for sort in EstateSort::ALL {
+        assert_eq!(EstateSort::parse(sort.as_str()), Some(sort));
+    }
+    assert_eq!(EstateSort::parse("cheapest"), None);
+}
+
+#[test]
+fn searches_default_to_relevance() {
+    assert_eq!(EstateSort::default_for(Some("فيلا")), EstateSort::Relevance);
+    assert_eq!(EstateSort::default_for(Some("  ")), EstateSort::Newest);
+    assert_eq!(EstateSort::default_for(None), EstateSort::Newest);
+}
+
+#[test]
+fn snippet_is_split_around_highlighted_matches() {
+    let snippet = format!("فيلا فاخرة في {HIGHLIGHT_START}التجمع{HIGHLIGHT_END} الخامس");
+    assert_eq!(
+        highlight_segments(&snippet),
+        vec![
+            ("فيلا فاخرة في ".to_string(), false),
+            ("التجمع".to_string(), true),
+            (" الخامس".to_string(), false),
+        ]
+    );
+    assert_eq!(
+        highlight_segments("بدون تطابق"),
+        vec![("بدون تطابق".to_string(), false)]
+    );
+}
+
+#[test]
+fn only_absolute_http_urls_count_as_remote_images() {
+    assert!(is_remote_url("https://cdn.example.com/a.jpg"));
+    assert!(is_remote_url("http://example.com"));
+    assert!(!is_remote_url("victim.webp"));
+    assert!(!is_remote_url("/uploads/victim.webp"));
+    assert!(!is_remote_url("ftp://example.com/a.jpg"));
+    assert!(!is_remote_url("https:///victim.webp"));
+    assert!(!is_remote_url("victim.webp?x=https://example.com"));
+}
+
+#[test]
+fn srcset_lists_only_the_stored_variants() {
+    let mut keys = ImageKeys {
+        original: "a.jpg".to_string(),
+        thumbnail: None,
+        medium: None,
+        large: None,
+    };
+    let urls = keys.public_urls("/uploads");
+    assert_eq!(urls.srcset(), None);
+    assert_eq!(urls.smallest(), "/uploads/a.jpg");
+
+    keys.thumbnail = Some("a_thumbnail.webp".to_string());
+    keys.large = Some("a_large.webp".to_string());
+    let urls = keys.public_urls("https://cdn.example.com/images/");
+    assert_eq!(
+        urls.srcset().as_deref(),
+        Some(
+            "https://cdn.example.com/images/a_thumbnail.webp 400w, \
+             https://cdn.example.com/images/a_large.webp 1920w"
+        )
+    );
+    assert_eq!(
+        urls.smallest(),
+        "https://cdn.example.com/images/a_thumbnail.webp"
+    );
+    assert_eq!(
+        keys.stored().collect::<Vec<_>>(),
+        vec!["a_thumbnail.webp", "a_large.webp", "a.jpg"]
+    );
+}
+
+#[test]
+fn images_hosted_elsewhere_keep_their_url() {
+    let url = "https://images.unsplash.com/photo-1?w=800";
+    assert_eq!(public_url("/uploads", url), url);
+    assert_eq!(public_url("/uploads", ""), "");
+
+    let keys = ImageKeys {
+        original: url.to_string(),
+        thumbnail: None,
+        medium: None,
+        large: None,
+    };
+    assert_eq!(keys.stored().count(), 0);
+}
+
+#[test]
+fn attribute_enums_parse_from_their_query_value() {
+    for property_type in PropertyType::ALL {
+        assert_eq!(
+            PropertyType::parse(property_type.as_str()),
+            Some(property_type)
+        );
+    }
+    for finishing in Finishing::ALL {
+        assert_eq!(Finishing::parse(finishing.as_str()), Some(finishing));
+    }
+    assert_eq!(PropertyType::parse("castle"), None);
+    assert_eq!(Finishing::parse(""), None);
+}
+
+#[test]
+fn facts_leave_out_unknown_attributes() {
+    let attributes = EstateAttributes {
+        property_type: PropertyType::Villa,
+        bedrooms: Some(4),
+        floor: Some(0),
+        furnished: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        attributes.facts(),
+        vec![
+            ("نوع العقار", "فيلا".to_string()),
+            ("غرف النوم", "4".to_string()),
+            ("الطابق", "الأرضي".to_string()),
+            ("الفرش", "مفروش".to_string()),
+        ]
+    );
+    assert_eq!(floor_label(3), "الدور 3");
+    assert_eq!(floor_label(-2), "البدروم 2");
+
+    let negative = EstateAttributes {
+        bathrooms: Some(-1),
+        ..Default::default()
+    };
+    assert!(negative.validate().is_err());
+    assert!(attributes.validate().is_ok());
+}
+
+#[test]
+fn installment_plans_are_all_or_nothing_and_only_for_sales() {
+    let mut terms = PaymentTerms {
+        purpose: ListingPurpose::Sale,
+        down_payment_in_cents: Some(10_000_000),
+        installment_years: Some(5),
+        installment_frequency: Some(InstallmentFrequency::Quarterly),
+        installment_in_cents: Some(2_000_000),
+    };
+    let plan = terms.plan().unwrap();
+    assert_eq!(plan.installments(), 20);
+    assert_eq!(plan.total_in_cents(), Some(50_000_000));
+    assert!(terms.validate().is_ok());
+
+    terms.purpose = ListingPurpose::RentMonthly;
+    assert!(terms.validate().is_err());
+
+    terms.purpose = ListingPurpose::Sale;
+    terms.installment_in_cents = None;
+    assert_eq!(terms.plan(), None);
+    assert!(terms.validate().is_err());
+
+    terms.installment_in_cents = Some(i64::MAX / 2);
+    assert_eq!(terms.plan().unwrap().total_in_cents(), None);
+    assert!(terms.validate().is_err());
+
+    terms.installment_in_cents = Some(2_000_000);
+    terms.down_payment_in_cents = Some(i64::MAX);
+    assert_eq!(terms.plan().unwrap().total_in_cents(), None);
+    assert!(terms.validate().is_err());
+
+    terms.down_payment_in_cents = Some(PaymentPlan::MAX_AMOUNT_IN_CENTS);
+    terms.installment_in_cents = Some(PaymentPlan::MAX_AMOUNT_IN_CENTS);
+    terms.installment_years = Some(30);
+    terms.installment_frequency = Some(InstallmentFrequency::Monthly);
+    assert!(terms.validate().is_ok());
+
+    let rent = PaymentTerms {
+        purpose: ListingPurpose::RentYearly,
+        ..Default::default()
+    };
+    assert!(rent.validate().is_ok());
+    for purpose in ListingPurpose::ALL {
+        assert_eq!(ListingPurpose::parse(purpose.as_str()), Some(purpose));
+    }
+}
+
+#[test]
+fn listings_move_through_their_lifecycle() {
+    use EstateStatus::*;
+    assert!(Draft.transition(Published).is_ok());
+    assert!(Published.transition(Reserved).is_ok());
+    assert!(Reserved.transition(Published).is_ok());
+    assert!(Reserved.transition(Sold).is_ok());
+    assert!(Sold.transition(Archived).is_ok());
+    assert!(Archived.transition(Draft).is_ok());
+
+    assert!(Draft.transition(Sold).is_err());
+    assert!(Sold.transition(Published).is_err());
+    assert!(Archived.transition(Published).is_err());
+    for status in EstateStatus::ALL {
+        assert!(status.transition(status).is_err());
+        assert_eq!(EstateStatus::parse(status.as_str()), Some(status));
+    }
+}
+
+#[test]
+fn agents_reserve_and_sell_their_listings_without_publishing_rights() {
+    use EstateStatus::*;
+    // The agent's permissions in the seeded role matrix.
+    let agent = [
+        Permission::EstatesView,
+        Permission::EstatesCreate,
+        Permission::EstatesEdit,
+        Permission::LeadsView,
+    ];
+    let caller = Some((Uuid::new_v4(), Level::Agent));
+    let may = |from: EstateStatus, to: EstateStatus| {
+        from.transition(to).is_ok()
+            && [Permission::EstatesEdit]
+                .into_iter()
+                .chain(from.transition_permission(to))
+                .all(|x| authorize_permission(caller.clone(), &agent, x).is_ok())
+    };
+    assert!(may(Published, Reserved));
+    assert!(may(Published, Sold));
+    assert!(may(Reserved, Sold));
+    assert!(may(Reserved, Published));
+    assert!(may(Sold, Archived));
+    assert!(!may(Draft, Published));
+    assert_eq!(
+        Draft.transition_permission(Published),
+        Some(Permission::EstatesPublish)
+    );
+}
+
+#[test]
+fn only_published_and_reserved_estates_are_listed() {
+    let listed: Vec<_> = EstateStatus::ALL
+        .into_iter()
+        .filter(EstateStatus::is_listed)
+        .collect();
+    assert_eq!(listed, [EstateStatus::Published, EstateStatus::Reserved]);
+    assert!(EstateStatus::Sold.is_public());
+    assert!(!EstateStatus::Draft.is_public());
+    assert!(!EstateStatus::Archived.is_public());
+}
+
+#[test]
+fn schedule_times_are_datetime_local_values() {
+    assert!(is_schedule_time("2024-01-31T18:30"));
+    assert!(!is_schedule_time("2024-01-31 18:30"));
+    assert!(!is_schedule_time("2024-13-01T10:00"));
+    assert!(!is_schedule_time("2024-01-31T24:00"));
+    assert!(!is_schedule_time("2024-01-31T18:30:00"));
+}
+
+#[test]
+fn schedule_times_must_exist_in_the_calendar() {
+    assert!(!is_schedule_time("2024-02-30T10:00"));
+    assert!(!is_schedule_time("2024-04-31T10:00"));
+    assert!(!is_schedule_time("2023-02-29T10:00"));
+    assert!(!is_schedule_time("1900-02-29T10:00"));
+    assert!(is_schedule_time("2024-02-29T10:00"));
+    assert!(is_schedule_time("2000-02-29T10:00"));
+    assert!(is_schedule_time("2024-12-31T23:59"));
+
+    let schedule = ListingSchedule {
+        publish_at: Some("2024-02-30T10:00".to_string()),
+        expire_at: None,
+    };
+    assert!(schedule.validate().is_err());
+}
+
+#[test]
+fn listings_expire_after_they_are_published() {
+    let schedule = |publish_at: &str, expire_at: &str| {
+        ListingSchedule {
+            publish_at: Some(publish_at.to_string()),
+            expire_at: Some(expire_at.to_string()),
+        }
+        .trimmed()
+    };
+    assert!(
+        schedule("2024-03-01T09:00", "2024-04-01T09:00")
+            .validate()
+            .is_ok()
+    );
+    assert!(
+        schedule("2024-03-01T09:00", "2024-03-01T09:00")
+            .validate()
+            .is_err()
+    );
+    assert!(
+        schedule("2024-03-01T09:00", "2024-02-01T09:00")
+            .validate()
+            .is_err()
+    );
+
+    let cleared = schedule("", " ");
+    assert_eq!(cleared, ListingSchedule::default());
+    assert!(cleared.validate().is_ok());
+    assert!(schedule("", "2024-04-01T09:00").validate().is_ok());
+}
+
+#[test]
+fn audit_cursor_round_trips_through_its_string_form() {
+    let cursor = AuditCursor {
+        created_at: 1_792_304_113_393_815,
+        id: Uuid::new_v4(),
+    };
+    assert_eq!(AuditCursor::parse(&cursor.to_string()), Some(cursor));
+    assert_eq!(AuditCursor::parse("yesterday_abc"), None);
+}
+
+#[test]
+fn only_calendar_days_are_dates() {
+    assert!(is_date("2024-02-29"));
+    assert!(is_date("2024-12-31"));
+    assert!(!is_date("2023-02-29"));
+    assert!(!is_date("2024-13-01"));
+    assert!(!is_date("0000-01-01"));
+    assert!(!is_date("2024-1-01"));
+    assert!(!is_date("2024-01-01'; --"));
+    assert!(!is_date("٢٠٢٤-٠١-٠١"));
+}
+
+#[test]
+fn prices_may_be_free_but_spaces_must_be_positive() {
+    assert!(validate_price(0).is_ok());
+    assert!(validate_price(150_000_000).is_ok());
+    assert!(validate_price(-1).is_err());
+    assert!(validate_space(1).is_ok());
+    assert!(validate_space(0).is_err());
+    assert!(validate_space(-80).is_err());
+}
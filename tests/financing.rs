@@ -0,0 +1,101 @@
+use cryptos_site::financing::{QuoteError, QuoteRequest, quote};
+use cryptos_site::models::{InstallmentFrequency, PaymentPlan};
+
+fn request(annual_rate_percent: f64) -> QuoteRequest {
+    QuoteRequest {
+        price_in_cents: 100_000_000,
+        down_payment_percent: 20.0,
+        years: 10,
+        annual_rate_percent,
+        frequency: InstallmentFrequency::Monthly,
+    }
+}
+
+#[test]
+fn interest_free_plans_split_the_rest_evenly() {
+    let quote = quote(request(0.0)).unwrap();
+    assert_eq!(quote.down_payment_in_cents, 20_000_000);
+    assert_eq!(quote.financed_in_cents, 80_000_000);
+    assert_eq!(quote.schedule.len(), 120);
+    assert_eq!(quote.installment_in_cents, 666_667);
+    assert_eq!(quote.total_interest_in_cents, 0);
+    assert_eq!(quote.total_paid_in_cents, 100_000_000);
+    assert_eq!(quote.schedule.last().unwrap().balance_in_cents, 0);
+}
+
+#[test]
+fn loans_are_amortized_with_interest_on_the_balance() {
+    let quote = quote(request(12.0)).unwrap();
+    // 800,000 over 120 months at 1% a month.
+    assert_eq!(quote.installment_in_cents, 1_147_768);
+    let first = quote.schedule[0];
+    assert_eq!(first.interest_in_cents, 800_000);
+    assert_eq!(first.principal_in_cents, 347_768);
+    assert_eq!(first.balance_in_cents, 80_000_000 - 347_768);
+
+    let last = quote.schedule.last().unwrap();
+    assert_eq!(last.balance_in_cents, 0);
+    assert!((last.payment_in_cents - quote.installment_in_cents).abs() < 100);
+    let principal: i64 = quote.schedule.iter().map(|x| x.principal_in_cents).sum();
+    assert_eq!(principal, quote.financed_in_cents);
+    assert_eq!(
+        quote.total_paid_in_cents,
+        quote.request.price_in_cents + quote.total_interest_in_cents
+    );
+}
+
+#[test]
+fn paying_everything_upfront_leaves_no_installments() {
+    let quote = quote(QuoteRequest {
+        down_payment_percent: 100.0,
+        ..request(20.0)
+    })
+    .unwrap();
+    assert_eq!(quote.financed_in_cents, 0);
+    assert!(quote.schedule.is_empty());
+    assert_eq!(quote.total_paid_in_cents, 100_000_000);
+}
+
+#[test]
+fn out_of_range_terms_are_refused() {
+    assert_eq!(
+        quote(QuoteRequest {
+            years: 0,
+            ..request(0.0)
+        }),
+        Err(QuoteError::Term)
+    );
+    assert_eq!(
+        quote(QuoteRequest {
+            down_payment_percent: 120.0,
+            ..request(0.0)
+        }),
+        Err(QuoteError::DownPayment)
+    );
+    assert_eq!(quote(request(-1.0)), Err(QuoteError::Rate));
+    assert_eq!(
+        quote(QuoteRequest {
+            price_in_cents: 0,
+            ..request(0.0)
+        }),
+        Err(QuoteError::Price)
+    );
+}
+
+#[test]
+fn calculator_starts_from_the_estate_plan() {
+    let plan = PaymentPlan {
+        down_payment_in_cents: 10_000_000,
+        years: 8,
+        frequency: InstallmentFrequency::Quarterly,
+        installment_in_cents: 2_812_500,
+    };
+    let request = QuoteRequest::for_estate(100_000_000, Some(plan));
+    assert_eq!(request.down_payment_percent, 10.0);
+    assert_eq!(request.years, 8);
+    assert_eq!(request.frequency, InstallmentFrequency::Quarterly);
+
+    let quote = quote(request).unwrap();
+    assert_eq!(quote.schedule.len(), 32);
+    assert_eq!(quote.installment_in_cents, plan.installment_in_cents);
+}
@@ -0,0 +1,49 @@
+use cryptos_site::auth::throttle::{AttemptState, Throttled, USERNAME_POLICY};
+
+#[test]
+fn first_failures_are_not_delayed() {
+    for failed_count in 0..USERNAME_POLICY.free_attempts {
+        let state = AttemptState {
+            failed_count,
+            ..Default::default()
+        };
+        assert_eq!(USERNAME_POLICY.check(&state), Ok(()));
+    }
+}
+
+#[test]
+fn delay_doubles_up_to_the_maximum() {
+    let free = USERNAME_POLICY.free_attempts;
+    assert_eq!(USERNAME_POLICY.delay_secs(free), 1);
+    assert_eq!(USERNAME_POLICY.delay_secs(free + 1), 2);
+    assert_eq!(USERNAME_POLICY.delay_secs(free + 3), 8);
+    assert_eq!(
+        USERNAME_POLICY.delay_secs(free + 100),
+        USERNAME_POLICY.max_delay_secs
+    );
+
+    let state = AttemptState {
+        failed_count: free + 2,
+        secs_since_last_failure: 1,
+        lock_remaining_secs: 0,
+    };
+    assert_eq!(
+        USERNAME_POLICY.check(&state),
+        Err(Throttled::Delayed { remaining_secs: 3 })
+    );
+}
+
+#[test]
+fn locked_key_is_rejected_until_the_lock_expires() {
+    let state = AttemptState {
+        failed_count: USERNAME_POLICY.lock_after,
+        secs_since_last_failure: 600,
+        lock_remaining_secs: 300,
+    };
+    assert_eq!(
+        USERNAME_POLICY.check(&state),
+        Err(Throttled::Locked {
+            remaining_secs: 300
+        })
+    );
+}
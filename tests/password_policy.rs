@@ -0,0 +1,35 @@
+use cryptos_site::auth::password::{PasswordPolicyError, check_password_strength};
+
+#[test]
+fn short_or_single_class_passwords_are_rejected() {
+    assert_eq!(
+        check_password_strength("abc123", "sara"),
+        Err(PasswordPolicyError::TooShort)
+    );
+    assert_eq!(
+        check_password_strength("12345678901", "sara"),
+        Err(PasswordPolicyError::MissingLetter)
+    );
+    assert_eq!(
+        check_password_strength("onlyletters", "sara"),
+        Err(PasswordPolicyError::MissingDigit)
+    );
+}
+
+#[test]
+fn username_and_common_passwords_are_rejected() {
+    assert_eq!(
+        check_password_strength("Mahmoud2024!", "mahmoud"),
+        Err(PasswordPolicyError::ContainsUsername)
+    );
+    assert_eq!(
+        check_password_strength("Password123", "sara"),
+        Err(PasswordPolicyError::TooCommon)
+    );
+}
+
+#[test]
+fn strong_password_is_accepted() {
+    assert_eq!(check_password_strength("نخلة-البحر-42", "sara"), Ok(()));
+    assert_eq!(check_password_strength("blue7Harbor", "admin"), Ok(()));
+}
@@ -0,0 +1,7 @@
+use cryptos_site::auth::two_factor::normalize_code;
+
+#[test]
+fn codes_are_compared_without_separators_or_case() {
+    assert_eq!(normalize_code(" 123 456 "), "123456");
+    assert_eq!(normalize_code("A1B2C-3D4E5"), "a1b2c3d4e5");
+}
@@ -0,0 +1,128 @@
+use cryptos_site::videos::{
+    ByteRange, PART_SIZE, VideoContainer, VideoError, byte_range, inspect, part_count, part_len,
+    read_box_header,
+};
+
+fn boxed(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend(kind);
+    data.extend(payload);
+    data
+}
+
+fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+    [brand.as_slice(), &[0, 0, 2, 0], b"isomiso2mp41"].concat()
+}
+
+/// A version 0 movie header: timescale 1000, duration 90.5 s.
+fn mvhd() -> Vec<u8> {
+    let mut data = vec![0; 100];
+    data[12..16].copy_from_slice(&1000u32.to_be_bytes());
+    data[16..20].copy_from_slice(&90_500u32.to_be_bytes());
+    boxed(b"mvhd", &data)
+}
+
+fn trak(handler: &[u8; 4], codec: &[u8; 4], width: u32, height: u32) -> Vec<u8> {
+    let mut tkhd = vec![0; 84];
+    tkhd[76..80].copy_from_slice(&(width << 16).to_be_bytes());
+    tkhd[80..84].copy_from_slice(&(height << 16).to_be_bytes());
+    let hdlr = [&[0; 8], handler.as_slice(), &[0; 12]].concat();
+    let stsd = [&[0, 0, 0, 0, 0, 0, 0, 1], boxed(codec, &[0; 8]).as_slice()].concat();
+    let stbl = boxed(b"stbl", &boxed(b"stsd", &stsd));
+    let mdia = [boxed(b"hdlr", &hdlr), boxed(b"minf", &stbl)].concat();
+    boxed(
+        b"trak",
+        &[boxed(b"tkhd", &tkhd), boxed(b"mdia", &mdia)].concat(),
+    )
+}
+
+#[test]
+fn reads_duration_size_and_codecs_of_a_movie() {
+    let moov = [
+        mvhd(),
+        trak(b"vide", b"avc1", 1920, 1080),
+        trak(b"soun", b"mp4a", 0, 0),
+    ]
+    .concat();
+    let info = inspect(&ftyp(b"isom"), &moov, VideoContainer::Mp4).unwrap();
+    assert_eq!(info.container, VideoContainer::Mp4);
+    assert_eq!(info.duration_secs, 90.5);
+    assert_eq!((info.width, info.height), (1920, 1080));
+    assert_eq!(info.video_codec, "avc1");
+    assert_eq!(info.audio_codec.as_deref(), Some("mp4a"));
+}
+
+#[test]
+fn refuses_codecs_browsers_cannot_play() {
+    let moov = [mvhd(), trak(b"vide", b"mp4v", 640, 480)].concat();
+    assert!(matches!(
+        inspect(&ftyp(b"mp42"), &moov, VideoContainer::Mp4),
+        Err(VideoError::UnsupportedCodec(x)) if x == "mp4v"
+    ));
+}
+
+#[test]
+fn refuses_files_without_video_or_of_another_container() {
+    let audio_only = [mvhd(), trak(b"soun", b"mp4a", 0, 0)].concat();
+    assert!(matches!(
+        inspect(&ftyp(b"isom"), &audio_only, VideoContainer::Mp4),
+        Err(VideoError::NoVideoTrack)
+    ));
+
+    let moov = [mvhd(), trak(b"vide", b"hvc1", 1280, 720)].concat();
+    assert!(inspect(&ftyp(b"qt  "), &moov, VideoContainer::QuickTime).is_ok());
+    assert!(matches!(
+        inspect(&ftyp(b"qt  "), &moov, VideoContainer::Mp4),
+        Err(VideoError::Unsupported)
+    ));
+    assert!(matches!(
+        inspect(&ftyp(b"3gp4"), &moov, VideoContainer::Mp4),
+        Err(VideoError::Unsupported)
+    ));
+}
+
+#[test]
+fn box_headers_support_64_bit_and_open_ended_sizes() {
+    let large = [
+        &1u32.to_be_bytes(),
+        b"mdat",
+        &(5u64 << 32).to_be_bytes()[..],
+    ]
+    .concat();
+    let header = read_box_header(&large).unwrap();
+    assert_eq!((&header.kind, header.header_len), (b"mdat", 16));
+    assert_eq!(header.size, Some(5 << 32));
+
+    let open = [0u32.to_be_bytes(), *b"mdat"].concat();
+    assert_eq!(read_box_header(&open).unwrap().size, None);
+
+    let broken = [4u32.to_be_bytes(), *b"free"].concat();
+    assert!(read_box_header(&broken).is_err());
+}
+
+#[test]
+fn files_are_cut_into_full_parts_and_a_remainder() {
+    let size = 2 * PART_SIZE + 10;
+    assert_eq!(part_count(size), 3);
+    assert_eq!(part_len(size, 1), PART_SIZE);
+    assert_eq!(part_len(size, 3), 10);
+    assert_eq!(part_len(size, 4), 0);
+    assert_eq!(part_count(PART_SIZE), 1);
+}
+
+#[test]
+fn range_requests_pick_the_bytes_to_send() {
+    let partial = |start, end| ByteRange::Partial { start, end };
+    assert_eq!(byte_range("bytes=0-99", 1000), partial(0, 99));
+    assert_eq!(byte_range("bytes=500-", 1000), partial(500, 999));
+    assert_eq!(byte_range("bytes=900-5000", 1000), partial(900, 999));
+    assert_eq!(byte_range("bytes=-100", 1000), partial(900, 999));
+    assert_eq!(byte_range("bytes=-5000", 1000), partial(0, 999));
+    assert_eq!(byte_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+    assert_eq!(byte_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+    assert_eq!(byte_range("bytes=0-", 0), ByteRange::Unsatisfiable);
+    assert_eq!(byte_range("bytes=0-1,5-6", 1000), ByteRange::Full);
+    assert_eq!(byte_range("bytes=9-3", 1000), ByteRange::Full);
+    assert_eq!(byte_range("items=0-9", 1000), ByteRange::Full);
+    assert_eq!(byte_range("bytes=x-", 1000), ByteRange::Full);
+}